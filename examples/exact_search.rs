@@ -0,0 +1,34 @@
+//! Times the exact search algorithms on DNA for a few pattern lengths.
+//!
+//! Run with `cargo run --release --example exact_search`.
+
+use std::time::Instant;
+
+use bio_oxide::pattern::exact::{find_all, Algorithm};
+
+fn main() {
+    let mut state = 42u64;
+    let target: Vec<u8> = (0..20_000_000)
+        .map(|_| {
+            state = state
+                .wrapping_mul(6_364_136_223_846_793_005)
+                .wrapping_add(1);
+            b"ACGT"[(state >> 33) as usize & 3]
+        })
+        .collect();
+
+    for len in [4, 8, 16, 32, 64] {
+        let pattern = &target[1_000_000..1_000_000 + len];
+        for algorithm in [Algorithm::Memchr, Algorithm::Horspool, Algorithm::TwoWay] {
+            let started = Instant::now();
+            let hits = find_all(pattern, &target, algorithm).len();
+            println!(
+                "len {:>2}  {:<8}  {:>8} hits  {:>8.2} ms",
+                len,
+                format!("{:?}", algorithm),
+                hits,
+                started.elapsed().as_secs_f64() * 1000.0
+            );
+        }
+    }
+}
@@ -0,0 +1,133 @@
+//! Pairwise local alignment.
+
+use std::ops::Range;
+
+use crate::align::stats::KarlinAltschul;
+
+pub mod matrix;
+pub mod stats;
+
+/// Linear-gap scoring for nucleotide alignment. Symbols are compared
+/// ignoring case.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Scoring {
+    pub match_score: i32,
+    pub mismatch: i32,
+    /// Score of each gap position; negative.
+    pub gap: i32,
+}
+
+impl Default for Scoring {
+    /// The BLASTN-style `+1/-2` scheme with `-2` per gap position.
+    fn default() -> Self {
+        Scoring {
+            match_score: 1,
+            mismatch: -2,
+            gap: -2,
+        }
+    }
+}
+
+impl Scoring {
+    /// Score of aligning `a` with `b`.
+    pub fn score(&self, a: u8, b: u8) -> i32 {
+        if a.eq_ignore_ascii_case(&b) {
+            self.match_score
+        } else {
+            self.mismatch
+        }
+    }
+}
+
+/// Best local alignment of two sequences: `a[a_range]` aligned with
+/// `b[b_range]`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LocalAlignment {
+    pub score: i32,
+    pub a_range: Range<usize>,
+    pub b_range: Range<usize>,
+}
+
+impl LocalAlignment {
+    pub fn bit_score(&self, stats: &KarlinAltschul) -> f64 {
+        stats.bit_score(self.score)
+    }
+
+    /// E-value of the alignment's score for a search of a sequence of
+    /// length `m` against `n` symbols.
+    pub fn evalue(&self, stats: &KarlinAltschul, m: usize, n: usize) -> f64 {
+        stats.evalue(self.score, m, n)
+    }
+}
+
+/// Smith–Waterman local alignment. Returns `None` when no pair of symbols
+/// scores above 0. Ties go to the alignment ending first in `a`, then `b`.
+pub fn local_align(a: &[u8], b: &[u8], scoring: &Scoring) -> Option<LocalAlignment> {
+    let width = b.len() + 1;
+    let mut h = vec![0i32; (a.len() + 1) * width];
+    let mut best = (0, 0, 0);
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let score = (h[(i - 1) * width + j - 1] + scoring.score(a[i - 1], b[j - 1]))
+                .max(h[(i - 1) * width + j] + scoring.gap)
+                .max(h[i * width + j - 1] + scoring.gap)
+                .max(0);
+            h[i * width + j] = score;
+            if score > best.0 {
+                best = (score, i, j);
+            }
+        }
+    }
+    let (score, a_end, b_end) = best;
+    if score == 0 {
+        return None;
+    }
+    let (mut i, mut j) = (a_end, b_end);
+    while h[i * width + j] > 0 {
+        let here = h[i * width + j];
+        if here == h[(i - 1) * width + j - 1] + scoring.score(a[i - 1], b[j - 1]) {
+            i -= 1;
+            j -= 1;
+        } else if here == h[(i - 1) * width + j] + scoring.gap {
+            i -= 1;
+        } else {
+            j -= 1;
+        }
+    }
+    Some(LocalAlignment {
+        score,
+        a_range: i..a_end,
+        b_range: j..b_end,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn finds_best_local_alignment() {
+        let aln = local_align(b"TTTTACGTACGTTTTT", b"GGACGTACGGG", &Scoring::default()).unwrap();
+        assert_eq!(aln.score, 7);
+        assert_eq!(aln.a_range, 4..11);
+        assert_eq!(aln.b_range, 2..9);
+        assert_eq!(local_align(b"AAAA", b"cccc", &Scoring::default()), None);
+
+        let stats =
+            stats::KarlinAltschul::ungapped(&Scoring::default(), &stats::UNIFORM_DNA).unwrap();
+        assert!(aln.evalue(&stats, 16, 11) < stats.evalue(aln.score - 1, 16, 11));
+        assert!(aln.bit_score(&stats) > 0.0);
+    }
+
+    #[test]
+    fn aligns_across_gaps() {
+        let scoring = Scoring {
+            match_score: 2,
+            mismatch: -3,
+            gap: -1,
+        };
+        let aln = local_align(b"ACGTTACGT", b"ACGTACGT", &scoring).unwrap();
+        assert_eq!(aln.score, 15);
+        assert_eq!((aln.a_range, aln.b_range), (0..9, 0..8));
+    }
+}
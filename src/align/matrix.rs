@@ -0,0 +1,100 @@
+//! Substitution matrices scoring every pair of symbols.
+
+use crate::align::Scoring;
+
+const BLOSUM62_SYMBOLS: &[u8] = b"ARNDCQEGHILKMFPSTWYVBZX*";
+
+#[rustfmt::skip]
+const BLOSUM62: [[i8; 24]; 24] = [
+    [ 4, -1, -2, -2,  0, -1, -1,  0, -2, -1, -1, -1, -1, -2, -1,  1,  0, -3, -2,  0, -2, -1,  0, -4],
+    [-1,  5,  0, -2, -3,  1,  0, -2,  0, -3, -2,  2, -1, -3, -2, -1, -1, -3, -2, -3, -1,  0, -1, -4],
+    [-2,  0,  6,  1, -3,  0,  0,  0,  1, -3, -3,  0, -2, -3, -2,  1,  0, -4, -2, -3,  3,  0, -1, -4],
+    [-2, -2,  1,  6, -3,  0,  2, -1, -1, -3, -4, -1, -3, -3, -1,  0, -1, -4, -3, -3,  4,  1, -1, -4],
+    [ 0, -3, -3, -3,  9, -3, -4, -3, -3, -1, -1, -3, -1, -2, -3, -1, -1, -2, -2, -1, -3, -3, -2, -4],
+    [-1,  1,  0,  0, -3,  5,  2, -2,  0, -3, -2,  1,  0, -3, -1,  0, -1, -2, -1, -2,  0,  3, -1, -4],
+    [-1,  0,  0,  2, -4,  2,  5, -2,  0, -3, -3,  1, -2, -3, -1,  0, -1, -3, -2, -2,  1,  4, -1, -4],
+    [ 0, -2,  0, -1, -3, -2, -2,  6, -2, -4, -4, -2, -3, -3, -2,  0, -2, -2, -3, -3, -1, -2, -1, -4],
+    [-2,  0,  1, -1, -3,  0,  0, -2,  8, -3, -3, -1, -2, -1, -2, -1, -2, -2,  2, -3,  0,  0, -1, -4],
+    [-1, -3, -3, -3, -1, -3, -3, -4, -3,  4,  2, -3,  1,  0, -3, -2, -1, -3, -1,  3, -3, -3, -1, -4],
+    [-1, -2, -3, -4, -1, -2, -3, -4, -3,  2,  4, -2,  2,  0, -3, -2, -1, -2, -1,  1, -4, -3, -1, -4],
+    [-1,  2,  0, -1, -3,  1,  1, -2, -1, -3, -2,  5, -1, -3, -1,  0, -1, -3, -2, -2,  0,  1, -1, -4],
+    [-1, -1, -2, -3, -1,  0, -2, -3, -2,  1,  2, -1,  5,  0, -2, -1, -1, -1, -1,  1, -3, -1, -1, -4],
+    [-2, -3, -3, -3, -2, -3, -3, -3, -1,  0,  0, -3,  0,  6, -4, -2, -2,  1,  3, -1, -3, -3, -1, -4],
+    [-1, -2, -2, -1, -3, -1, -1, -2, -2, -3, -3, -1, -2, -4,  7, -1, -1, -4, -3, -2, -2, -1, -2, -4],
+    [ 1, -1,  1,  0, -1,  0,  0,  0, -1, -2, -2,  0, -1, -2, -1,  4,  1, -3, -2, -2,  0,  0,  0, -4],
+    [ 0, -1,  0, -1, -1, -1, -1, -2, -2, -1, -1, -1, -1, -2, -1,  1,  5, -2, -2,  0, -1, -1,  0, -4],
+    [-3, -3, -4, -4, -2, -2, -3, -2, -2, -3, -2, -3, -1,  1, -4, -3, -2, 11,  2, -3, -4, -3, -2, -4],
+    [-2, -2, -2, -3, -2, -1, -2, -3,  2, -1, -1, -2, -1,  3, -3, -2, -2,  2,  7, -1, -3, -2, -1, -4],
+    [ 0, -3, -3, -3, -1, -2, -2, -3, -3,  3,  1, -2,  1, -1, -2, -2,  0, -3, -1,  4, -3, -2, -1, -4],
+    [-2, -1,  3,  4, -3,  0,  1, -1,  0, -3, -4,  0, -3, -3, -2,  0, -1, -4, -3, -3,  4,  1, -1, -4],
+    [-1,  0,  0,  1, -3,  3,  4, -2,  0, -3, -3,  1, -1, -3, -1,  0, -1, -3, -2, -2,  1,  4, -1, -4],
+    [ 0, -1, -1, -1, -2, -1, -1, -1, -1, -1, -1, -1, -1, -1, -2,  0,  0, -2, -1, -1, -1, -1, -1, -4],
+    [-4, -4, -4, -4, -4, -4, -4, -4, -4, -4, -4, -4, -4, -4, -4, -4, -4, -4, -4, -4, -4, -4, -4,  1],
+];
+
+/// Scores for every pair of byte symbols. Symbols are compared ignoring
+/// case.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SubstitutionMatrix {
+    scores: Vec<i32>,
+}
+
+impl SubstitutionMatrix {
+    /// Matrix with `score(a, b)` for every pair of uppercase symbols.
+    pub fn from_fn<F: Fn(u8, u8) -> i32>(score: F) -> Self {
+        let scores = (0..=255u8)
+            .flat_map(|a| (0..=255u8).map(move |b| (a, b)))
+            .map(|(a, b)| score(a, b))
+            .collect();
+        SubstitutionMatrix { scores }
+    }
+
+    /// Match/mismatch matrix of a linear-gap [`Scoring`].
+    pub fn from_scoring(scoring: &Scoring) -> Self {
+        SubstitutionMatrix::from_fn(|a, b| scoring.score(a, b))
+    }
+
+    /// BLOSUM62 over the 20 amino acids, `B`, `Z`, `X` and the stop `*`.
+    /// Other symbols score as `X`.
+    pub fn blosum62() -> Self {
+        let index = |symbol: u8| {
+            BLOSUM62_SYMBOLS
+                .iter()
+                .position(|&s| s == symbol)
+                .unwrap_or(BLOSUM62_SYMBOLS.len() - 2)
+        };
+        SubstitutionMatrix::from_fn(|a, b| i32::from(BLOSUM62[index(a)][index(b)]))
+    }
+
+    /// Score of aligning `a` with `b`.
+    pub fn score(&self, a: u8, b: u8) -> i32 {
+        let (a, b) = (a.to_ascii_uppercase(), b.to_ascii_uppercase());
+        self.scores[a as usize * 256 + b as usize]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn blosum62_is_symmetric() {
+        for (i, row) in BLOSUM62.iter().enumerate() {
+            for (j, &score) in row.iter().enumerate() {
+                assert_eq!(score, BLOSUM62[j][i], "{} {}", i, j);
+            }
+        }
+        let matrix = SubstitutionMatrix::blosum62();
+        assert_eq!(matrix.score(b'W', b'W'), 11);
+        assert_eq!(matrix.score(b'a', b'R'), -1);
+        assert_eq!(matrix.score(b'J', b'A'), 0);
+        assert_eq!(matrix.score(b'*', b'*'), 1);
+    }
+
+    #[test]
+    fn wraps_linear_scoring() {
+        let matrix = SubstitutionMatrix::from_scoring(&Scoring::default());
+        assert_eq!(matrix.score(b'a', b'A'), 1);
+        assert_eq!(matrix.score(b'A', b'C'), -2);
+    }
+}
@@ -0,0 +1,309 @@
+//! Karlin–Altschul statistics: turning raw local alignment scores into
+//! bit scores and e-values.
+//!
+//! [`KarlinAltschul::ungapped`] derives `lambda`, `K` and the relative
+//! entropy `H` from a scoring system and background frequencies. Gapped
+//! alignments have no closed form; use [`KarlinAltschul::new`] with
+//! published, simulation-fitted parameters for those.
+
+use std::fmt;
+
+use crate::align::Scoring;
+
+/// Equal frequencies of the four nucleotides.
+pub const UNIFORM_DNA: [(u8, f64); 4] = [(b'A', 0.25), (b'C', 0.25), (b'G', 0.25), (b'T', 0.25)];
+
+/// Limit on the terms of the series for `K`; it normally converges in
+/// well under a hundred.
+const MAX_K_TERMS: usize = 1000;
+
+/// Limit on the doublings and halvings that bracket `lambda`.
+const MAX_BRACKET_STEPS: usize = 1100;
+
+/// Errors raised when a scoring system has no valid statistics.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum StatsError {
+    /// Probabilities are negative, not finite or do not sum to 1.
+    InvalidProbabilities,
+    /// The expected score of a random pair is not negative, so local
+    /// alignments grow with length and scores carry no significance.
+    NonNegativeExpectedScore(f64),
+    /// No positive score can occur.
+    NoPositiveScore,
+    /// No positive `lambda` could be bracketed, typically because the
+    /// expected score is too close to 0 to resolve.
+    NoLambda,
+}
+
+impl fmt::Display for StatsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StatsError::InvalidProbabilities => {
+                write!(f, "score probabilities must be non-negative and sum to 1")
+            }
+            StatsError::NonNegativeExpectedScore(expected) => {
+                write!(f, "expected score {} is not negative", expected)
+            }
+            StatsError::NoPositiveScore => write!(f, "no positive score is possible"),
+            StatsError::NoLambda => write!(f, "no positive lambda solves the scoring system"),
+        }
+    }
+}
+
+impl std::error::Error for StatsError {}
+
+/// Statistical parameters of a scoring system.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct KarlinAltschul {
+    pub lambda: f64,
+    pub k: f64,
+    /// Relative entropy in nats per aligned pair.
+    pub h: f64,
+}
+
+fn gcd(a: u32, b: u32) -> u32 {
+    if b == 0 {
+        a
+    } else {
+        gcd(b, a % b)
+    }
+}
+
+impl KarlinAltschul {
+    pub fn new(lambda: f64, k: f64, h: f64) -> Self {
+        KarlinAltschul { lambda, k, h }
+    }
+
+    /// Parameters for ungapped alignment under `scoring`, with symbols
+    /// drawn independently from `background`.
+    pub fn ungapped(scoring: &Scoring, background: &[(u8, f64)]) -> Result<Self, StatsError> {
+        let mut probabilities: Vec<(i32, f64)> = Vec::new();
+        for &(a, pa) in background {
+            for &(b, pb) in background {
+                let score = scoring.score(a, b);
+                match probabilities.iter_mut().find(|(s, _)| *s == score) {
+                    Some(slot) => slot.1 += pa * pb,
+                    None => probabilities.push((score, pa * pb)),
+                }
+            }
+        }
+        KarlinAltschul::from_score_probabilities(&probabilities)
+    }
+
+    /// Parameters from the probability of each score of a random aligned
+    /// pair. Probabilities summing to within `1e-6` of 1 are rescaled to
+    /// sum to exactly 1.
+    pub fn from_score_probabilities(probabilities: &[(i32, f64)]) -> Result<Self, StatsError> {
+        let total: f64 = probabilities.iter().map(|&(_, p)| p).sum();
+        if probabilities
+            .iter()
+            .any(|&(_, p)| !p.is_finite() || p < 0.0)
+            || (total - 1.0).abs() > 1e-6
+        {
+            return Err(StatsError::InvalidProbabilities);
+        }
+        let scores: Vec<(i32, f64)> = probabilities
+            .iter()
+            .filter(|&&(_, p)| p > 0.0)
+            .map(|&(s, p)| (s, p / total))
+            .collect();
+        let expected: f64 = scores.iter().map(|&(s, p)| s as f64 * p).sum();
+        if expected >= 0.0 {
+            return Err(StatsError::NonNegativeExpectedScore(expected));
+        }
+        if scores.iter().all(|&(s, _)| s <= 0) {
+            return Err(StatsError::NoPositiveScore);
+        }
+        let lambda = solve_lambda(&scores)?;
+        let h = lambda
+            * scores
+                .iter()
+                .map(|&(s, p)| p * s as f64 * (lambda * s as f64).exp())
+                .sum::<f64>();
+        let k = solve_k(&scores, lambda, h);
+        Ok(KarlinAltschul { lambda, k, h })
+    }
+
+    /// Score in bits, comparable across scoring systems.
+    pub fn bit_score(&self, raw: i32) -> f64 {
+        (self.lambda * raw as f64 - self.k.ln()) / std::f64::consts::LN_2
+    }
+
+    /// Expected number of chance alignments scoring at least `raw` between
+    /// sequences of lengths `m` and `n`.
+    pub fn evalue(&self, raw: i32, m: usize, n: usize) -> f64 {
+        self.k * m as f64 * n as f64 * (-self.lambda * raw as f64).exp()
+    }
+
+    /// Probability of at least one chance alignment scoring at least `raw`.
+    pub fn pvalue(&self, raw: i32, m: usize, n: usize) -> f64 {
+        -(-self.evalue(raw, m, n)).exp_m1()
+    }
+}
+
+/// The positive root of `sum(p * exp(lambda * s)) = 1`, by bisection.
+/// The probabilities must sum to 1.
+fn solve_lambda(scores: &[(i32, f64)]) -> Result<f64, StatsError> {
+    // With the probabilities summing to 1, `exp_m1` keeps precision for
+    // the tiny lambdas of near-zero expected scores.
+    let f = |lambda: f64| {
+        scores
+            .iter()
+            .map(|&(s, p)| p * (lambda * s as f64).exp_m1())
+            .sum::<f64>()
+    };
+    let mut high = 1.0;
+    let mut steps = 0;
+    while f(high) <= 0.0 {
+        high *= 2.0;
+        steps += 1;
+        if steps > MAX_BRACKET_STEPS || !high.is_finite() {
+            return Err(StatsError::NoLambda);
+        }
+    }
+    // f dips below 0 just above the trivial root at 0.
+    let mut low = high / 2.0;
+    while f(low) >= 0.0 {
+        low /= 2.0;
+        steps += 1;
+        if steps > MAX_BRACKET_STEPS || low == 0.0 {
+            return Err(StatsError::NoLambda);
+        }
+    }
+    for _ in 0..200 {
+        let mid = (low + high) / 2.0;
+        if f(mid) > 0.0 {
+            high = mid;
+        } else {
+            low = mid;
+        }
+    }
+    Ok((low + high) / 2.0)
+}
+
+/// `K` from the Karlin–Altschul series over the distribution of the sum
+/// of `i` random scores, on the lattice of the scores' common divisor.
+fn solve_k(scores: &[(i32, f64)], lambda: f64, h: f64) -> f64 {
+    let delta = scores.iter().fold(0, |d, &(s, _)| gcd(d, s.unsigned_abs())) as i32;
+    let steps: Vec<(i32, f64)> = scores.iter().map(|&(s, p)| (s / delta, p)).collect();
+    let low = steps.iter().map(|&(s, _)| s).min().unwrap_or(0);
+    let high = steps.iter().map(|&(s, _)| s).max().unwrap_or(0);
+    let lambda_delta = lambda * delta as f64;
+
+    // dist[j] is the probability that the sum is `offset + j`.
+    let mut dist = vec![1.0];
+    let mut offset = 0;
+    let mut sigma = 0.0;
+    for i in 1..=MAX_K_TERMS {
+        let mut next = vec![0.0; dist.len() + (high - low) as usize];
+        for (j, &p) in dist.iter().enumerate() {
+            for &(s, q) in &steps {
+                next[j + (s - low) as usize] += p * q;
+            }
+        }
+        dist = next;
+        offset += low;
+        let term: f64 = dist
+            .iter()
+            .enumerate()
+            .map(|(j, &p)| {
+                let sum = offset + j as i32;
+                if sum < 0 {
+                    p * (lambda_delta * sum as f64).exp()
+                } else {
+                    p
+                }
+            })
+            .sum::<f64>()
+            / i as f64;
+        sigma += term;
+        if term < 1e-12 {
+            break;
+        }
+    }
+    (-2.0 * sigma).exp() * lambda_delta / (h * -(-lambda_delta).exp_m1())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(actual: f64, expected: f64, tolerance: f64) {
+        assert!(
+            (actual - expected).abs() < tolerance,
+            "{} is not within {} of {}",
+            actual,
+            tolerance,
+            expected
+        );
+    }
+
+    fn dna(match_score: i32, mismatch: i32) -> KarlinAltschul {
+        let scoring = Scoring {
+            match_score,
+            mismatch,
+            ..Scoring::default()
+        };
+        KarlinAltschul::ungapped(&scoring, &UNIFORM_DNA).unwrap()
+    }
+
+    #[test]
+    fn matches_blast_ungapped_parameters() {
+        let stats = dna(1, -3);
+        assert_close(stats.lambda, 1.374, 1e-3);
+        assert_close(stats.k, 0.711, 1e-3);
+        assert_close(stats.h, 1.31, 1e-2);
+
+        let stats = dna(1, -2);
+        assert_close(stats.lambda, 1.333, 1e-3);
+        assert_close(stats.k, 0.621, 1e-3);
+    }
+
+    #[test]
+    fn scaled_scores_share_k() {
+        let unit = dna(1, -2);
+        let doubled = dna(2, -4);
+        assert_close(doubled.lambda, unit.lambda / 2.0, 1e-9);
+        assert_close(doubled.k, unit.k, 1e-9);
+        assert_close(doubled.bit_score(20), unit.bit_score(10), 1e-9);
+    }
+
+    #[test]
+    fn converts_scores() {
+        let stats = KarlinAltschul::new(0.267, 0.041, 0.14);
+        assert_close(stats.bit_score(100), 43.13, 1e-2);
+        let evalue = stats.evalue(100, 300, 1_000_000);
+        assert_close(evalue, 300.0 * 1e6 * 2f64.powf(-stats.bit_score(100)), 1e-9);
+        assert!(stats.pvalue(100, 300, 1_000_000) <= evalue);
+        assert!(stats.evalue(200, 300, 1_000_000) < evalue);
+    }
+
+    #[test]
+    fn rejects_scoring_without_statistics() {
+        assert_eq!(
+            KarlinAltschul::from_score_probabilities(&[(1, 0.5), (-1, 0.4)]),
+            Err(StatsError::InvalidProbabilities)
+        );
+        assert_eq!(
+            KarlinAltschul::from_score_probabilities(&[(1, 0.5), (-1, 0.5)]),
+            Err(StatsError::NonNegativeExpectedScore(0.0))
+        );
+        assert_eq!(
+            KarlinAltschul::from_score_probabilities(&[(0, 0.5), (-1, 0.5)]),
+            Err(StatsError::NoPositiveScore)
+        );
+    }
+
+    #[test]
+    fn rescales_probabilities_near_zero_expected_score() {
+        // Sums to 1 + 5e-7 with an expected score of -1e-9: lambda for
+        // +1/-1 is ln(q / p), about 2e-9 here.
+        let slack = 2.5e-7;
+        let stats = KarlinAltschul::from_score_probabilities(&[
+            (1, 0.5 - 5e-10 + slack),
+            (-1, 0.5 + 5e-10 + slack),
+        ])
+        .unwrap();
+        assert_close(stats.lambda / 2e-9, 1.0, 1e-3);
+    }
+}
@@ -0,0 +1,296 @@
+//! Sequence types and the alphabets their symbols are drawn from.
+
+use std::cmp::Ordering;
+use std::fmt;
+
+/// The kind of molecule a sequence describes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SeqType {
+    Dna,
+    Rna,
+    Protein,
+}
+
+impl fmt::Display for SeqType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            SeqType::Dna => "DNA",
+            SeqType::Rna => "RNA",
+            SeqType::Protein => "protein",
+        };
+        f.write_str(name)
+    }
+}
+
+/// The set of symbols a sequence is allowed to contain.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Alphabet {
+    /// Unambiguous DNA: `ACGT`.
+    Dna,
+    /// DNA with the IUPAC ambiguity codes.
+    DnaIupac,
+    /// Unambiguous RNA: `ACGU`.
+    Rna,
+    /// RNA with the IUPAC ambiguity codes.
+    RnaIupac,
+    /// The 20 standard amino acids plus the stop symbol `*`.
+    Protein,
+    /// Protein with the ambiguity (`BZJX`) and rare (`UO`) residues.
+    ProteinExtended,
+}
+
+impl Alphabet {
+    /// The molecule type this alphabet belongs to.
+    pub fn seq_type(&self) -> SeqType {
+        match self {
+            Alphabet::Dna | Alphabet::DnaIupac => SeqType::Dna,
+            Alphabet::Rna | Alphabet::RnaIupac => SeqType::Rna,
+            Alphabet::Protein | Alphabet::ProteinExtended => SeqType::Protein,
+        }
+    }
+
+    /// Upper-case symbols of the alphabet, excluding the gap character.
+    pub fn symbols(&self) -> &'static [u8] {
+        match self {
+            Alphabet::Dna => b"ACGT",
+            Alphabet::DnaIupac => b"ACGTRYSWKMBDHVN",
+            Alphabet::Rna => b"ACGU",
+            Alphabet::RnaIupac => b"ACGURYSWKMBDHVN",
+            Alphabet::Protein => b"ACDEFGHIKLMNPQRSTVWY*",
+            Alphabet::ProteinExtended => b"ACDEFGHIKLMNPQRSTVWY*BZJXUO",
+        }
+    }
+
+    /// Whether `symbol` belongs to the alphabet, ignoring case.
+    pub fn contains(&self, symbol: u8) -> bool {
+        self.symbols().contains(&symbol.to_ascii_uppercase())
+    }
+}
+
+impl fmt::Display for Alphabet {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Alphabet::Dna => "DNA",
+            Alphabet::DnaIupac => "IUPAC DNA",
+            Alphabet::Rna => "RNA",
+            Alphabet::RnaIupac => "IUPAC RNA",
+            Alphabet::Protein => "protein",
+            Alphabet::ProteinExtended => "extended protein",
+        };
+        f.write_str(name)
+    }
+}
+
+/// Symbols that only occur in protein sequences.
+const PROTEIN_ONLY: &[u8] = b"EFILPQJOZX*";
+
+/// Symbols that count as nucleotide evidence: unambiguous bases plus `N`.
+const NUCLEOTIDE_CORE: &[u8] = b"ACGTUN";
+
+/// Tunable heuristics for guessing a sequence's type from its symbols.
+///
+/// A sequence is a protein when protein-only symbols (`EFILPQJOZX*`) make
+/// up at least `protein_threshold` of the sample, and a nucleotide sequence
+/// when `ACGTUN` make up at least `nucleotide_threshold`. Otherwise, and
+/// for empty sequences, `prefer` decides. Nucleotide sequences are RNA when
+/// they contain more `U` than `T` and DNA when they contain more `T` than
+/// `U`; on a tie a nucleotide `prefer` decides, and DNA is assumed otherwise.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct InferenceConfig {
+    /// Number of leading non-gap symbols examined; `None` examines all.
+    pub sample_size: Option<usize>,
+    pub protein_threshold: f64,
+    pub nucleotide_threshold: f64,
+    /// Type assumed when neither threshold is reached, and the nucleotide
+    /// type assumed when `U` and `T` counts tie.
+    pub prefer: SeqType,
+}
+
+impl Default for InferenceConfig {
+    fn default() -> Self {
+        InferenceConfig {
+            sample_size: Some(10_000),
+            protein_threshold: 0.1,
+            nucleotide_threshold: 0.75,
+            prefer: SeqType::Dna,
+        }
+    }
+}
+
+impl InferenceConfig {
+    /// Guesses the molecule type and alphabet of a raw sequence. The type
+    /// comes from the sample; the alphabet is the narrowest of that type
+    /// covering the whole sequence. Gaps (`-`, `.`) and case are ignored.
+    pub fn infer(&self, sequence: &str) -> (SeqType, Alphabet) {
+        let limit = self.sample_size.unwrap_or(usize::MAX);
+        let (mut total, mut protein, mut nucleotide, mut t, mut u) = (0usize, 0, 0, 0, 0);
+        for b in sequence
+            .bytes()
+            .filter(|b| !matches!(b, b'-' | b'.'))
+            .take(limit)
+        {
+            let b = b.to_ascii_uppercase();
+            total += 1;
+            if PROTEIN_ONLY.contains(&b) {
+                protein += 1;
+            }
+            if NUCLEOTIDE_CORE.contains(&b) {
+                nucleotide += 1;
+            }
+            match b {
+                b'T' => t += 1,
+                b'U' => u += 1,
+                _ => {}
+            }
+        }
+
+        let fraction = |count: usize| count as f64 / total.max(1) as f64;
+        let nucleotide_type = match u.cmp(&t) {
+            Ordering::Greater => SeqType::Rna,
+            Ordering::Less => SeqType::Dna,
+            Ordering::Equal if self.prefer == SeqType::Protein => SeqType::Dna,
+            Ordering::Equal => self.prefer,
+        };
+        let seq_type = if total == 0 {
+            self.prefer
+        } else if protein > 0 && fraction(protein) >= self.protein_threshold {
+            SeqType::Protein
+        } else if fraction(nucleotide) >= self.nucleotide_threshold {
+            nucleotide_type
+        } else if self.prefer == SeqType::Protein {
+            SeqType::Protein
+        } else {
+            nucleotide_type
+        };
+        (seq_type, infer_alphabet(sequence, seq_type))
+    }
+}
+
+/// Guesses the molecule type and alphabet of a raw sequence with the
+/// default [`InferenceConfig`].
+pub fn infer_type_and_alphabet(sequence: &str) -> (SeqType, Alphabet) {
+    InferenceConfig::default().infer(sequence)
+}
+
+/// The narrowest alphabet of `seq_type` covering every symbol in `sequence`,
+/// falling back to the widest one of the type.
+pub fn infer_alphabet(sequence: &str, seq_type: SeqType) -> Alphabet {
+    let candidates = match seq_type {
+        SeqType::Dna => [Alphabet::Dna, Alphabet::DnaIupac],
+        SeqType::Rna => [Alphabet::Rna, Alphabet::RnaIupac],
+        SeqType::Protein => [Alphabet::Protein, Alphabet::ProteinExtended],
+    };
+    let fits = |alphabet: &Alphabet| {
+        sequence
+            .bytes()
+            .filter(|b| !matches!(b, b'-' | b'.'))
+            .all(|b| alphabet.symbols().contains(&b.to_ascii_uppercase()))
+    };
+    candidates
+        .iter()
+        .copied()
+        .find(fits)
+        .unwrap_or(candidates[1])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn infers_nucleotide_types() {
+        assert_eq!(
+            infer_type_and_alphabet("ACGT-acgt"),
+            (SeqType::Dna, Alphabet::Dna)
+        );
+        assert_eq!(
+            infer_type_and_alphabet("ACGTN"),
+            (SeqType::Dna, Alphabet::DnaIupac)
+        );
+        assert_eq!(
+            infer_type_and_alphabet("ACGU"),
+            (SeqType::Rna, Alphabet::Rna)
+        );
+        assert_eq!(
+            infer_type_and_alphabet("ACGURY"),
+            (SeqType::Rna, Alphabet::RnaIupac)
+        );
+    }
+
+    #[test]
+    fn infers_protein_types() {
+        assert_eq!(
+            infer_type_and_alphabet("MKLV*"),
+            (SeqType::Protein, Alphabet::Protein)
+        );
+        assert_eq!(
+            infer_type_and_alphabet("MKXZ"),
+            (SeqType::Protein, Alphabet::ProteinExtended)
+        );
+    }
+
+    #[test]
+    fn tolerates_noise_in_nucleotide_data() {
+        let noisy = "ACGTACGTACGTACGTACGTACGTACGTACGTACGTACGX";
+        assert_eq!(infer_type_and_alphabet(noisy).0, SeqType::Dna);
+        let strict = InferenceConfig {
+            protein_threshold: 0.0,
+            ..InferenceConfig::default()
+        };
+        assert_eq!(strict.infer(noisy).0, SeqType::Protein);
+    }
+
+    #[test]
+    fn config_controls_sampling_and_ties() {
+        let sampled = InferenceConfig {
+            sample_size: Some(4),
+            ..InferenceConfig::default()
+        };
+        let (seq_type, alphabet) = sampled.infer("ACGTMKLVEEPQ");
+        assert_eq!(seq_type, SeqType::Dna);
+        // The alphabet still has to cover the symbols beyond the sample.
+        assert_eq!(alphabet, Alphabet::DnaIupac);
+
+        // Mostly ambiguity codes: neither threshold is reached.
+        let ambiguous = "RYKMSWHV";
+        assert_eq!(infer_type_and_alphabet(ambiguous).0, SeqType::Dna);
+        let prefer_protein = InferenceConfig {
+            prefer: SeqType::Protein,
+            ..InferenceConfig::default()
+        };
+        assert_eq!(prefer_protein.infer(ambiguous).0, SeqType::Protein);
+        assert_eq!(prefer_protein.infer("").0, SeqType::Protein);
+
+        let prefer_rna = InferenceConfig {
+            prefer: SeqType::Rna,
+            ..InferenceConfig::default()
+        };
+        assert_eq!(prefer_rna.infer(ambiguous).0, SeqType::Rna);
+        assert_eq!(prefer_rna.infer("ACGACG").0, SeqType::Rna);
+        assert_eq!(prefer_rna.infer("ACGT").0, SeqType::Dna);
+        assert_eq!(prefer_rna.infer("RYKMSWHT").0, SeqType::Dna);
+        assert_eq!(infer_type_and_alphabet("ACGACG").0, SeqType::Dna);
+    }
+
+    #[test]
+    fn infers_alphabet_for_known_type() {
+        assert_eq!(infer_alphabet("ACGT", SeqType::Dna), Alphabet::Dna);
+        assert_eq!(infer_alphabet("ACGTW", SeqType::Dna), Alphabet::DnaIupac);
+        assert_eq!(infer_alphabet("ACGT", SeqType::Protein), Alphabet::Protein);
+    }
+
+    #[test]
+    fn alphabet_membership_ignores_case() {
+        assert!(Alphabet::Dna.contains(b'a'));
+        assert!(!Alphabet::Dna.contains(b'N'));
+        assert!(Alphabet::DnaIupac.contains(b'n'));
+        assert!(!Alphabet::Protein.contains(b'-'));
+    }
+
+    #[test]
+    fn alphabet_maps_to_seq_type() {
+        assert_eq!(Alphabet::DnaIupac.seq_type(), SeqType::Dna);
+        assert_eq!(Alphabet::Rna.seq_type(), SeqType::Rna);
+        assert_eq!(Alphabet::ProteinExtended.seq_type(), SeqType::Protein);
+    }
+}
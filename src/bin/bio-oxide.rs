@@ -0,0 +1,409 @@
+//! `bio-oxide`: command-line access to the library's core operations.
+//!
+//! Build with `cargo build --features cli`. Every command reads FASTA or
+//! FASTQ from the given files, or standard input for `-`, and writes to
+//! standard output.
+
+use std::error::Error;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, BufWriter, Write};
+use std::process::ExitCode;
+
+use bio_oxide::codon::{translate, GeneticCode};
+use bio_oxide::fasta::{FastaReader, FastaSeq};
+use bio_oxide::fasta_index::FastaIndex;
+use bio_oxide::fastq::{fasta_to_fastq, fastq_to_fasta, FastqReader, FastqSeq};
+use bio_oxide::msa::is_gap;
+use bio_oxide::region::Region;
+use bio_oxide::seq::reverse_complement;
+
+const USAGE: &str = "\
+usage: bio-oxide <command> [options] [file...]
+
+commands:
+  stats [file...]                        summary table per file
+  revcomp [file]                         reverse-complement every sequence
+  translate [--table N] [file]           translate in frame 1 (default table 1)
+  extract-region <file> <id[:start-end]> 1-based inclusive region of an indexed FASTA
+  convert --to fasta|fastq [--quality Q] [file]
+                                         convert between FASTA and FASTQ
+
+Files default to standard input; `-` also means standard input.";
+
+type CliResult = Result<(), Box<dyn Error>>;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Format {
+    Fasta,
+    Fastq,
+}
+
+fn open(path: &str) -> io::Result<Box<dyn BufRead>> {
+    Ok(if path == "-" {
+        Box::new(BufReader::new(io::stdin()))
+    } else {
+        Box::new(BufReader::new(File::open(path)?))
+    })
+}
+
+/// Guesses the format from the first non-blank byte, `>` or `@`.
+fn detect(reader: &mut dyn BufRead) -> io::Result<Option<Format>> {
+    loop {
+        let buf = reader.fill_buf()?;
+        let Some(&first) = buf.first() else {
+            return Ok(None);
+        };
+        if first.is_ascii_whitespace() {
+            reader.consume(1);
+            continue;
+        }
+        return Ok(match first {
+            b'>' => Some(Format::Fasta),
+            b'@' => Some(Format::Fastq),
+            _ => None,
+        });
+    }
+}
+
+fn open_detected(path: &str) -> Result<(Box<dyn BufRead>, Format), Box<dyn Error>> {
+    let mut reader = open(path)?;
+    match detect(&mut reader)? {
+        Some(format) => Ok((reader, format)),
+        None => Err(format!("{}: not FASTA or FASTQ", path).into()),
+    }
+}
+
+/// Removes `--name value` from `args`, returning the value.
+fn take_option(args: &mut Vec<String>, name: &str) -> Result<Option<String>, Box<dyn Error>> {
+    let Some(i) = args.iter().position(|a| a == name) else {
+        return Ok(None);
+    };
+    if i + 1 >= args.len() {
+        return Err(format!("{} needs a value", name).into());
+    }
+    let value = args.remove(i + 1);
+    args.remove(i);
+    Ok(Some(value))
+}
+
+/// The single input file, defaulting to standard input.
+fn single_input(args: &[String]) -> Result<&str, Box<dyn Error>> {
+    match args {
+        [] => Ok("-"),
+        [path] => Ok(path),
+        _ => Err("expected at most one input file".into()),
+    }
+}
+
+fn stats(args: &[String], out: &mut dyn Write) -> CliResult {
+    let paths: Vec<&str> = if args.is_empty() {
+        vec!["-"]
+    } else {
+        args.iter().map(String::as_str).collect()
+    };
+    writeln!(
+        out,
+        "file\tformat\ttype\tnum_seqs\tsum_len\tmin_len\tavg_len\tmax_len\tgc_percent"
+    )?;
+    for path in paths {
+        let (reader, format) = open_detected(path)?;
+        let seqs: Box<dyn Iterator<Item = Result<FastaSeq, Box<dyn Error>>>> = match format {
+            Format::Fasta => Box::new(FastaReader::new(reader).map(|r| r.map_err(Into::into))),
+            Format::Fastq => Box::new(FastqReader::new(reader).map(|r| Ok(r?.to_fasta()?))),
+        };
+        let (mut count, mut total, mut gc) = (0u64, 0u64, 0f64);
+        // GC is a fraction of non-gap symbols, so weight it by those.
+        let mut residues = 0u64;
+        let (mut min, mut max) = (u64::MAX, 0u64);
+        let mut seq_type = None;
+        for seq in seqs {
+            let seq = seq?;
+            let len = seq.len() as u64;
+            count += 1;
+            total += len;
+            min = min.min(len);
+            max = max.max(len);
+            let ungapped = seq.sequence().bytes().filter(|&b| !is_gap(b)).count() as u64;
+            residues += ungapped;
+            gc += seq.gc_content() * ungapped as f64;
+            seq_type.get_or_insert(seq.seq_type());
+        }
+        let (format, seq_type) = (
+            if format == Format::Fasta {
+                "FASTA"
+            } else {
+                "FASTQ"
+            },
+            seq_type.map_or_else(|| "-".to_string(), |t| t.to_string()),
+        );
+        let (avg, gc) = if count == 0 {
+            (0.0, 0.0)
+        } else {
+            (
+                total as f64 / count as f64,
+                100.0 * gc / residues.max(1) as f64,
+            )
+        };
+        writeln!(
+            out,
+            "{}\t{}\t{}\t{}\t{}\t{}\t{:.1}\t{}\t{:.2}",
+            path,
+            format,
+            seq_type,
+            count,
+            total,
+            if count == 0 { 0 } else { min },
+            avg,
+            max,
+            gc
+        )?;
+    }
+    Ok(())
+}
+
+/// Copy of `seq` with a new sequence and the same header and type.
+fn replace_sequence(seq: &FastaSeq, sequence: &[u8]) -> Result<FastaSeq, Box<dyn Error>> {
+    Ok(FastaSeq::builder()
+        .id(seq.id())
+        .description(seq.description())
+        .sequence(&String::from_utf8_lossy(sequence))
+        .seq_type(seq.seq_type())
+        .build()?)
+}
+
+fn revcomp(args: &[String], out: &mut dyn Write) -> CliResult {
+    let (reader, format) = open_detected(single_input(args)?)?;
+    match format {
+        Format::Fasta => {
+            for seq in FastaReader::new(reader) {
+                let seq = seq?;
+                let rc = reverse_complement(seq.sequence().as_bytes());
+                write!(out, "{}", replace_sequence(&seq, &rc)?)?;
+            }
+        }
+        Format::Fastq => {
+            for read in FastqReader::new(reader) {
+                let read = read?;
+                let rc = reverse_complement(read.sequence().as_bytes());
+                let quality: String = read.quality().chars().rev().collect();
+                let rc = FastqSeq::new(
+                    read.id(),
+                    read.description(),
+                    &String::from_utf8_lossy(&rc),
+                    &quality,
+                )?;
+                write!(out, "{}", rc)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+fn translate_cmd(mut args: Vec<String>, out: &mut dyn Write) -> CliResult {
+    let code = match take_option(&mut args, "--table")? {
+        Some(id) => {
+            let id: u8 = id.parse().map_err(|_| format!("invalid table {:?}", id))?;
+            GeneticCode::from_id(id).ok_or_else(|| format!("unsupported genetic code {}", id))?
+        }
+        None => GeneticCode::STANDARD,
+    };
+    let (reader, format) = open_detected(single_input(&args)?)?;
+    let seqs: Box<dyn Iterator<Item = Result<FastaSeq, Box<dyn Error>>>> = match format {
+        Format::Fasta => Box::new(FastaReader::new(reader).map(|r| r.map_err(Into::into))),
+        Format::Fastq => Box::new(FastqReader::new(reader).map(|r| Ok(r?.to_fasta()?))),
+    };
+    for seq in seqs {
+        let seq = seq?;
+        let protein = translate(seq.sequence().as_bytes(), &code);
+        let protein = FastaSeq::builder()
+            .id(seq.id())
+            .description(seq.description())
+            .sequence(&String::from_utf8_lossy(&protein))
+            .build()?;
+        write!(out, "{}", protein)?;
+    }
+    Ok(())
+}
+
+fn extract_region(args: &[String], out: &mut dyn Write) -> CliResult {
+    let [path, spec] = args else {
+        return Err("extract-region needs a FASTA file and a region".into());
+    };
+    let mut region: Region = spec.parse()?;
+    let id = region.seqid.clone();
+    let index = FastaIndex::from_file(path)?;
+    let mut file = File::open(path)?;
+    let seq = index
+        .fetch(&mut file, &id)?
+        .ok_or_else(|| format!("no sequence {:?} in {}", id, path))?;
+    let (start, end) = (region.start(), region.end().min(seq.len()));
+    if start >= end {
+        return Err(format!("region {} is outside {} ({} bases)", spec, id, seq.len()).into());
+    }
+    region.interval.end = end;
+    let sub = FastaSeq::builder()
+        .id(&region.to_string())
+        .description(seq.description())
+        .sequence(&seq.sequence()[start..end])
+        .seq_type(seq.seq_type())
+        .build()?;
+    write!(out, "{}", sub)?;
+    Ok(())
+}
+
+fn convert(mut args: Vec<String>, mut out: &mut dyn Write) -> CliResult {
+    let to = take_option(&mut args, "--to")?.ok_or("convert needs --to fasta|fastq")?;
+    let quality = take_option(&mut args, "--quality")?
+        .map(|q| {
+            q.parse::<u8>()
+                .map_err(|_| format!("invalid quality {:?}", q))
+        })
+        .transpose()?
+        .unwrap_or(40);
+    let (reader, format) = open_detected(single_input(&args)?)?;
+    match (format, to.to_ascii_lowercase().as_str()) {
+        (Format::Fastq, "fasta") => {
+            fastq_to_fasta(FastqReader::new(reader), &mut out)?;
+        }
+        (Format::Fasta, "fastq") => {
+            fasta_to_fastq(FastaReader::new(reader), quality, &mut out)?;
+        }
+        (Format::Fasta, "fasta") | (Format::Fastq, "fastq") => {
+            return Err(format!("input is already {}", to).into())
+        }
+        _ => return Err(format!("unknown output format {:?}", to).into()),
+    }
+    Ok(())
+}
+
+fn run(mut args: Vec<String>) -> CliResult {
+    if args.is_empty() || matches!(args[0].as_str(), "-h" | "--help" | "help") {
+        println!("{}", USAGE);
+        return Ok(());
+    }
+    let command = args.remove(0);
+    let stdout = io::stdout();
+    let mut out = BufWriter::new(stdout.lock());
+    match command.as_str() {
+        "stats" => stats(&args, &mut out)?,
+        "revcomp" => revcomp(&args, &mut out)?,
+        "translate" => translate_cmd(args, &mut out)?,
+        "extract-region" => extract_region(&args, &mut out)?,
+        "convert" => convert(args, &mut out)?,
+        other => return Err(format!("unknown command {:?}\n\n{}", other, USAGE).into()),
+    }
+    out.flush()?;
+    Ok(())
+}
+
+fn main() -> ExitCode {
+    match run(std::env::args().skip(1).collect()) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            eprintln!("bio-oxide: {}", err);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    /// Writes `text` to a file unique to this test process and `name`.
+    fn input(name: &str, text: &str) -> PathBuf {
+        let path =
+            std::env::temp_dir().join(format!("bio_oxide_cli_{}_{}", std::process::id(), name));
+        std::fs::write(&path, text).unwrap();
+        path
+    }
+
+    fn args(values: &[&str]) -> Vec<String> {
+        values.iter().map(|v| v.to_string()).collect()
+    }
+
+    #[test]
+    fn detects_format_after_blank_lines() {
+        let mut reader: &[u8] = b"\n  \n>a\nACGT\n";
+        assert_eq!(detect(&mut reader).unwrap(), Some(Format::Fasta));
+        assert_eq!(reader, b">a\nACGT\n");
+        assert_eq!(
+            detect(&mut &b"@r\nA\n+\nI\n"[..]).unwrap(),
+            Some(Format::Fastq)
+        );
+        assert_eq!(detect(&mut &b"ACGT\n"[..]).unwrap(), None);
+        assert_eq!(detect(&mut &b"\n\n"[..]).unwrap(), None);
+    }
+
+    #[test]
+    fn parses_options_and_inputs() {
+        let mut list = args(&["--table", "11", "in.fa"]);
+        assert_eq!(
+            take_option(&mut list, "--table").unwrap().as_deref(),
+            Some("11")
+        );
+        assert_eq!(list, args(&["in.fa"]));
+        assert_eq!(take_option(&mut list, "--to").unwrap(), None);
+        let mut list = args(&["in.fa", "--table"]);
+        assert_eq!(
+            take_option(&mut list, "--table").unwrap_err().to_string(),
+            "--table needs a value"
+        );
+
+        assert_eq!(single_input(&[]).unwrap(), "-");
+        assert_eq!(single_input(&args(&["a.fa"])).unwrap(), "a.fa");
+        assert_eq!(
+            single_input(&args(&["a.fa", "b.fa"]))
+                .unwrap_err()
+                .to_string(),
+            "expected at most one input file"
+        );
+    }
+
+    #[test]
+    fn stats_writes_one_row_per_file() {
+        let path = input("stats.fa", ">a\nACGT\n>b\nGG\n");
+        let path = path.to_str().unwrap();
+        let mut out = Vec::new();
+        stats(&args(&[path]), &mut out).unwrap();
+        let text = String::from_utf8(out).unwrap();
+        let rows: Vec<&str> = text.lines().collect();
+        assert_eq!(rows.len(), 2);
+        assert!(rows[0].starts_with("file\tformat\ttype\tnum_seqs"));
+        assert_eq!(
+            rows[1],
+            format!("{}\tFASTA\tDNA\t2\t6\t2\t3.0\t4\t66.67", path)
+        );
+        std::fs::remove_file(path).unwrap();
+
+        let path = input("stats_gapped.fa", ">a\nAC--\n>b\nAT\n");
+        let path = path.to_str().unwrap();
+        let mut out = Vec::new();
+        stats(&args(&[path]), &mut out).unwrap();
+        let text = String::from_utf8(out).unwrap();
+        assert!(text.ends_with("\t25.00\n"), "{}", text);
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn revcomp_reverses_fastq_qualities() {
+        let path = input("revcomp.fq", "@r1 x\nAACG\n+\nABCD\n");
+        let mut out = Vec::new();
+        revcomp(&args(&[path.to_str().unwrap()]), &mut out).unwrap();
+        assert_eq!(String::from_utf8(out).unwrap(), "@r1 x\nCGTT\n+\nDCBA\n");
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn convert_rejects_same_format() {
+        let path = input("convert.fa", ">a\nACGT\n");
+        let path = path.to_str().unwrap();
+        let err = convert(args(&["--to", "fasta", path]), &mut Vec::new()).unwrap_err();
+        assert_eq!(err.to_string(), "input is already fasta");
+        let mut out = Vec::new();
+        convert(args(&["--to", "fastq", "--quality", "30", path]), &mut out).unwrap();
+        assert_eq!(String::from_utf8(out).unwrap(), "@a\nACGT\n+\n????\n");
+        std::fs::remove_file(path).unwrap();
+    }
+}
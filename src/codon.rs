@@ -0,0 +1,516 @@
+//! Genetic codes, translation of nucleotide sequences and codon usage.
+
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+
+use crate::seq::reverse_complement;
+
+/// Codon order used by the NCBI tables: first base varies slowest, each
+/// position running through `TCAG`.
+const NCBI_BASES: &[u8; 4] = b"TCAG";
+
+/// A translation table in the layout of the NCBI `gc.prt` file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GeneticCode {
+    id: u8,
+    name: &'static str,
+    amino_acids: &'static [u8; 64],
+    starts: &'static [u8; 64],
+}
+
+impl GeneticCode {
+    /// NCBI table 1, the standard code.
+    pub const STANDARD: GeneticCode = GeneticCode {
+        id: 1,
+        name: "Standard",
+        amino_acids: b"FFLLSSSSYY**CC*WLLLLPPPPHHQQRRRRIIIMTTTTNNKKSSRRVVVVAAAADDEEGGGG",
+        starts: b"---M------**--*----M---------------M----------------------------",
+    };
+
+    /// NCBI table 11, bacterial, archaeal and plant plastid code.
+    pub const BACTERIAL: GeneticCode = GeneticCode {
+        id: 11,
+        name: "Bacterial, Archaeal and Plant Plastid",
+        amino_acids: b"FFLLSSSSYY**CC*WLLLLPPPPHHQQRRRRIIIMTTTTNNKKSSRRVVVVAAAADDEEGGGG",
+        starts: b"---M------**--*----M------------MMMM---------------M------------",
+    };
+
+    /// Looks up a built-in table by its NCBI identifier.
+    pub fn from_id(id: u8) -> Option<GeneticCode> {
+        match id {
+            1 => Some(Self::STANDARD),
+            11 => Some(Self::BACTERIAL),
+            _ => None,
+        }
+    }
+
+    pub fn id(&self) -> u8 {
+        self.id
+    }
+
+    pub fn name(&self) -> &'static str {
+        self.name
+    }
+
+    /// Amino acid encoded by `codon`, `*` for stops and `X` for codons that
+    /// are ambiguous or not exactly three nucleotides long.
+    pub fn translate_codon(&self, codon: &[u8]) -> u8 {
+        codon_index(codon).map_or(b'X', |i| self.amino_acids[i])
+    }
+
+    pub fn is_start(&self, codon: &[u8]) -> bool {
+        codon_index(codon).is_some_and(|i| self.starts[i] == b'M')
+    }
+
+    pub fn is_stop(&self, codon: &[u8]) -> bool {
+        codon_index(codon).is_some_and(|i| self.amino_acids[i] == b'*')
+    }
+
+    /// The 64 codons of the table paired with the amino acid they encode.
+    pub fn codons(&self) -> impl Iterator<Item = ([u8; 3], u8)> + '_ {
+        (0..64).map(move |i| (codon_at(i), self.amino_acids[i]))
+    }
+}
+
+impl Default for GeneticCode {
+    fn default() -> Self {
+        Self::STANDARD
+    }
+}
+
+fn base_index(base: u8) -> Option<usize> {
+    match base.to_ascii_uppercase() {
+        b'T' | b'U' => Some(0),
+        b'C' => Some(1),
+        b'A' => Some(2),
+        b'G' => Some(3),
+        _ => None,
+    }
+}
+
+fn codon_index(codon: &[u8]) -> Option<usize> {
+    match codon {
+        [a, b, c] => Some(base_index(*a)? * 16 + base_index(*b)? * 4 + base_index(*c)?),
+        _ => None,
+    }
+}
+
+/// Translates `seq` codon by codon, ignoring a trailing partial codon.
+pub fn translate(seq: &[u8], code: &GeneticCode) -> Vec<u8> {
+    seq.chunks_exact(3)
+        .map(|c| code.translate_codon(c))
+        .collect()
+}
+
+fn codon_at(index: usize) -> [u8; 3] {
+    [
+        NCBI_BASES[index / 16],
+        NCBI_BASES[(index / 4) % 4],
+        NCBI_BASES[index % 4],
+    ]
+}
+
+/// Codon counts of an organism, e.g. from a Kazusa table or its CDS set.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CodonUsage {
+    counts: [f64; 64],
+}
+
+impl CodonUsage {
+    /// Builds a table from `(codon, count)` pairs; unlisted codons count 0.
+    /// Pairs whose codon is not three unambiguous nucleotides are ignored.
+    pub fn from_counts<'a, I>(counts: I) -> Self
+    where
+        I: IntoIterator<Item = (&'a str, f64)>,
+    {
+        let mut table = [0.0; 64];
+        for (codon, count) in counts {
+            if let Some(i) = codon_index(codon.as_bytes()) {
+                table[i] += count;
+            }
+        }
+        CodonUsage { counts: table }
+    }
+
+    /// Counts in-frame codons over a set of coding sequences.
+    pub fn from_sequences<'a, I>(cds: I) -> Self
+    where
+        I: IntoIterator<Item = &'a [u8]>,
+    {
+        let mut table = [0.0; 64];
+        for seq in cds {
+            for codon in seq.chunks_exact(3) {
+                if let Some(i) = codon_index(codon) {
+                    table[i] += 1.0;
+                }
+            }
+        }
+        CodonUsage { counts: table }
+    }
+
+    pub fn count(&self, codon: &[u8]) -> f64 {
+        codon_index(codon).map_or(0.0, |i| self.counts[i])
+    }
+
+    /// Share of `codon` among the codons synonymous with it under `code`.
+    pub fn fraction(&self, codon: &[u8], code: &GeneticCode) -> f64 {
+        let Some(i) = codon_index(codon) else {
+            return 0.0;
+        };
+        let aa = code.amino_acids[i];
+        let total: f64 = (0..64)
+            .filter(|&j| code.amino_acids[j] == aa)
+            .map(|j| self.counts[j])
+            .sum();
+        if total > 0.0 {
+            self.counts[i] / total
+        } else {
+            0.0
+        }
+    }
+}
+
+/// How [`optimize`] chooses among synonymous codons.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OptimizeStrategy {
+    /// Always use the most frequent codon of each amino acid.
+    #[default]
+    MostFrequent,
+    /// Spread codons so each amino acid's codon mix tracks the target usage.
+    MatchDistribution,
+}
+
+/// Parameters for [`optimize`].
+#[derive(Debug, Clone, Default)]
+pub struct OptimizeOptions {
+    pub genetic_code: GeneticCode,
+    pub strategy: OptimizeStrategy,
+    /// Sites that must not occur on either strand, e.g. `GAATTC` for EcoRI.
+    pub avoid_sites: Vec<String>,
+    /// Longest single-base run allowed in the output.
+    pub max_homopolymer: Option<usize>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum OptimizeError {
+    /// The coding sequence length is not a multiple of three.
+    PartialCodon { len: usize },
+    /// The codon at `position` (0-based, in nucleotides) is ambiguous.
+    InvalidCodon { position: usize, codon: String },
+    /// An amino acid has no codon with non-zero usage in the target table.
+    NoUsage { amino_acid: char },
+    /// An entry of `avoid_sites` is empty.
+    EmptySite,
+    /// No choice of synonymous codons satisfies the site and homopolymer
+    /// constraints.
+    Unsatisfiable,
+}
+
+impl fmt::Display for OptimizeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            OptimizeError::PartialCodon { len } => {
+                write!(f, "coding sequence length {} is not a multiple of 3", len)
+            }
+            OptimizeError::InvalidCodon { position, codon } => {
+                write!(f, "invalid codon '{}' at position {}", codon, position)
+            }
+            OptimizeError::NoUsage { amino_acid } => {
+                write!(f, "target usage has no codon for '{}'", amino_acid)
+            }
+            OptimizeError::EmptySite => write!(f, "sites to avoid must not be empty"),
+            OptimizeError::Unsatisfiable => {
+                write!(f, "no synonymous recoding satisfies the constraints")
+            }
+        }
+    }
+}
+
+impl std::error::Error for OptimizeError {}
+
+/// Recodes a coding sequence with the codons preferred by `usage`, keeping
+/// the encoded protein (including stops) unchanged.
+///
+/// Candidate codons are tried in order of preference and the search
+/// backtracks when a choice would create an avoided site or an overlong
+/// homopolymer. The output is upper-case DNA.
+pub fn optimize(
+    cds: &[u8],
+    usage: &CodonUsage,
+    options: &OptimizeOptions,
+) -> Result<Vec<u8>, OptimizeError> {
+    if !cds.len().is_multiple_of(3) {
+        return Err(OptimizeError::PartialCodon { len: cds.len() });
+    }
+    let code = &options.genetic_code;
+
+    let mut synonyms: HashMap<u8, Vec<usize>> = HashMap::new();
+    for i in 0..64 {
+        if usage.counts[i] > 0.0 {
+            synonyms.entry(code.amino_acids[i]).or_default().push(i);
+        }
+    }
+    for codons in synonyms.values_mut() {
+        codons.sort_by(|&a, &b| usage.counts[b].total_cmp(&usage.counts[a]).then(a.cmp(&b)));
+    }
+
+    let mut protein = Vec::with_capacity(cds.len() / 3);
+    for (n, codon) in cds.chunks_exact(3).enumerate() {
+        let i = codon_index(codon).ok_or_else(|| OptimizeError::InvalidCodon {
+            position: n * 3,
+            codon: String::from_utf8_lossy(codon).into_owned(),
+        })?;
+        let aa = code.amino_acids[i];
+        if !synonyms.contains_key(&aa) {
+            return Err(OptimizeError::NoUsage {
+                amino_acid: aa as char,
+            });
+        }
+        protein.push(aa);
+    }
+
+    let preferences = match options.strategy {
+        OptimizeStrategy::MostFrequent => protein.iter().map(|aa| synonyms[aa].clone()).collect(),
+        OptimizeStrategy::MatchDistribution => distribute(&protein, &synonyms, usage),
+    };
+
+    let mut sites: Vec<Vec<u8>> = Vec::new();
+    for site in &options.avoid_sites {
+        if site.is_empty() {
+            return Err(OptimizeError::EmptySite);
+        }
+        let site = site.to_ascii_uppercase().into_bytes();
+        let rc = reverse_complement(&site);
+        if rc != site {
+            sites.push(rc);
+        }
+        sites.push(site);
+    }
+
+    // Depth-first search over codon choices. Constraints only look back
+    // `context` bases before the newest codon, so whether the remaining
+    // codons can be placed depends on the position and that suffix alone;
+    // failed states are remembered, bounding the search.
+    let context = sites
+        .iter()
+        .map(|site| site.len().saturating_sub(1))
+        .chain(options.max_homopolymer)
+        .max()
+        .unwrap_or(0);
+    let state = |out: &[u8], pos: usize| (pos, out[out.len().saturating_sub(context)..].to_vec());
+    let mut dead: HashSet<(usize, Vec<u8>)> = HashSet::new();
+    let mut out: Vec<u8> = Vec::with_capacity(cds.len());
+    let mut choice = vec![0usize; protein.len()];
+    let mut pos = 0;
+    while pos < protein.len() {
+        let candidates: &Vec<usize> = &preferences[pos];
+        let mut placed = false;
+        while choice[pos] < candidates.len() {
+            out.extend_from_slice(&codon_at(candidates[choice[pos]]));
+            if violates(&out, &sites, options.max_homopolymer)
+                || dead.contains(&state(&out, pos + 1))
+            {
+                out.truncate(out.len() - 3);
+                choice[pos] += 1;
+            } else {
+                placed = true;
+                break;
+            }
+        }
+        if placed {
+            pos += 1;
+        } else {
+            dead.insert(state(&out, pos));
+            if pos == 0 {
+                return Err(OptimizeError::Unsatisfiable);
+            }
+            choice[pos] = 0;
+            pos -= 1;
+            out.truncate(out.len() - 3);
+            choice[pos] += 1;
+        }
+    }
+    Ok(out)
+}
+
+/// Per-position codon preference lists that walk each amino acid's codons
+/// in proportion to their usage: every occurrence prefers the codon lagging
+/// furthest behind its target share.
+fn distribute(
+    protein: &[u8],
+    synonyms: &HashMap<u8, Vec<usize>>,
+    usage: &CodonUsage,
+) -> Vec<Vec<usize>> {
+    let mut used = [0.0f64; 64];
+    let mut seen: HashMap<u8, f64> = HashMap::new();
+    protein
+        .iter()
+        .map(|aa| {
+            let codons = &synonyms[aa];
+            let total: f64 = codons.iter().map(|&c| usage.counts[c]).sum();
+            let n = seen.entry(*aa).or_insert(0.0);
+            *n += 1.0;
+            let deficit = |c: usize| usage.counts[c] / total * *n - used[c];
+            let mut ranked = codons.clone();
+            ranked.sort_by(|&a, &b| deficit(b).total_cmp(&deficit(a)).then(a.cmp(&b)));
+            used[ranked[0]] += 1.0;
+            ranked
+        })
+        .collect()
+}
+
+/// Whether the codon just appended to `seq` completes an avoided site or
+/// extends a homopolymer past the limit.
+fn violates(seq: &[u8], sites: &[Vec<u8>], max_homopolymer: Option<usize>) -> bool {
+    let len = seq.len();
+    for site in sites {
+        let from = (len + 1).saturating_sub(3 + site.len());
+        if seq[from..]
+            .windows(site.len())
+            .any(|w| w == site.as_slice())
+        {
+            return true;
+        }
+    }
+    if let Some(max) = max_homopolymer {
+        let tail = &seq[(len + 1).saturating_sub(3 + max + 1)..];
+        let mut run = 0;
+        let mut prev = 0u8;
+        for &b in tail {
+            run = if b == prev { run + 1 } else { 1 };
+            prev = b;
+            if run > max {
+                return true;
+            }
+        }
+    }
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn translates_standard_code() {
+        let code = GeneticCode::STANDARD;
+        assert_eq!(translate(b"ATGGCCTAAG", &code), b"MA*");
+        assert_eq!(translate(b"augnnn", &code), b"MX");
+    }
+
+    #[test]
+    fn start_codons_differ_between_tables() {
+        assert!(GeneticCode::STANDARD.is_start(b"ATG"));
+        assert!(!GeneticCode::STANDARD.is_start(b"GTG"));
+        assert!(GeneticCode::BACTERIAL.is_start(b"GTG"));
+        assert!(GeneticCode::BACTERIAL.is_stop(b"TGA"));
+    }
+
+    fn ecoli_like_usage() -> CodonUsage {
+        CodonUsage::from_counts([
+            ("ATG", 10.0),
+            ("AAA", 30.0),
+            ("AAG", 10.0),
+            ("GAA", 20.0),
+            ("GAG", 20.0),
+            ("TTC", 6.0),
+            ("TTT", 5.0),
+            ("TAA", 8.0),
+            ("TGA", 2.0),
+        ])
+    }
+
+    #[test]
+    fn optimize_picks_most_frequent_codons() {
+        let out = optimize(
+            b"ATGAAGAAGTGA",
+            &ecoli_like_usage(),
+            &OptimizeOptions::default(),
+        );
+        assert_eq!(out.unwrap(), b"ATGAAAAAATAA");
+    }
+
+    #[test]
+    fn optimize_matches_distribution() {
+        let options = OptimizeOptions {
+            strategy: OptimizeStrategy::MatchDistribution,
+            ..OptimizeOptions::default()
+        };
+        let out = optimize(b"AAAAAAAAAAAA", &ecoli_like_usage(), &options).unwrap();
+        let usage = CodonUsage::from_sequences([out.as_slice()]);
+        assert_eq!(usage.count(b"AAA"), 3.0);
+        assert_eq!(usage.count(b"AAG"), 1.0);
+    }
+
+    #[test]
+    fn optimize_avoids_sites_and_homopolymers() {
+        // AAA AAA would create a six-base A run.
+        let options = OptimizeOptions {
+            max_homopolymer: Some(4),
+            ..OptimizeOptions::default()
+        };
+        let out = optimize(b"AAGAAG", &ecoli_like_usage(), &options).unwrap();
+        assert_eq!(out, b"AAGAAA");
+
+        // GAA TTC is EcoRI; the site must be avoided across codon borders.
+        let out = optimize(b"GAGTTT", &ecoli_like_usage(), &OptimizeOptions::default());
+        assert_eq!(out.unwrap(), b"GAATTC");
+        let options = OptimizeOptions {
+            avoid_sites: vec!["GAATTC".to_string()],
+            ..OptimizeOptions::default()
+        };
+        let out = optimize(b"GAGTTT", &ecoli_like_usage(), &options).unwrap();
+        assert_eq!(out, b"GAATTT");
+    }
+
+    #[test]
+    fn optimize_reports_errors() {
+        let usage = ecoli_like_usage();
+        let options = OptimizeOptions::default();
+        assert_eq!(
+            optimize(b"ATGA", &usage, &options),
+            Err(OptimizeError::PartialCodon { len: 4 })
+        );
+        assert!(matches!(
+            optimize(b"ATGNNN", &usage, &options),
+            Err(OptimizeError::InvalidCodon { position: 3, .. })
+        ));
+        assert_eq!(
+            optimize(b"TGG", &usage, &options),
+            Err(OptimizeError::NoUsage { amino_acid: 'W' })
+        );
+        let options = OptimizeOptions {
+            avoid_sites: vec!["ATG".to_string()],
+            ..OptimizeOptions::default()
+        };
+        assert_eq!(
+            optimize(b"ATG", &usage, &options),
+            Err(OptimizeError::Unsatisfiable)
+        );
+
+        let empty = OptimizeOptions {
+            avoid_sites: vec![String::new()],
+            ..OptimizeOptions::default()
+        };
+        assert_eq!(
+            optimize(b"ATG", &usage, &empty),
+            Err(OptimizeError::EmptySite)
+        );
+
+        // Every recoding of the lysines fails at the forced methionine;
+        // this must not explore all 2^40 combinations.
+        let cds = [b"AAA".repeat(40), b"ATG".to_vec()].concat();
+        assert_eq!(
+            optimize(&cds, &usage, &options),
+            Err(OptimizeError::Unsatisfiable)
+        );
+    }
+
+    #[test]
+    fn codons_follow_ncbi_order() {
+        let codons: Vec<_> = GeneticCode::STANDARD.codons().collect();
+        assert_eq!(codons.len(), 64);
+        assert_eq!(codons[0], (*b"TTT", b'F'));
+        assert_eq!(codons[63], (*b"GGG", b'G'));
+    }
+}
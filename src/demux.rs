@@ -0,0 +1,520 @@
+//! Barcode demultiplexing: assigning reads to samples.
+
+use std::collections::HashSet;
+use std::fmt;
+use std::fmt::Write as _;
+use std::fs::File;
+use std::io::{self, BufRead, BufWriter, Write};
+use std::path::Path;
+
+use crate::fastq::{FastqError, FastqReader, FastqSeq};
+
+/// File name stem for reads matching no sample.
+pub const UNDETERMINED: &str = "undetermined";
+
+/// Where a read's barcode is found.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum BarcodeLocation {
+    /// At the start of the read sequence, removed from assigned reads.
+    #[default]
+    Inline,
+    /// In the last `:`-separated field of an Illumina 1.8+ description,
+    /// e.g. `1:N:0:ATCACG` or `1:N:0:ATCACG+GGTACC` for dual indexes.
+    Index,
+}
+
+/// A sample and the barcode identifying it. Dual index barcodes are
+/// written `i7+i5` and only read from [`BarcodeLocation::Index`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Sample {
+    pub name: String,
+    pub barcode: String,
+}
+
+impl Sample {
+    pub fn new(name: &str, barcode: &str) -> Self {
+        Sample {
+            name: name.to_string(),
+            barcode: barcode.to_ascii_uppercase(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DemuxOptions {
+    pub location: BarcodeLocation,
+    /// Barcode mismatches tolerated when assigning a read.
+    pub max_mismatches: usize,
+}
+
+impl Default for DemuxOptions {
+    fn default() -> Self {
+        DemuxOptions {
+            location: BarcodeLocation::Inline,
+            max_mismatches: 1,
+        }
+    }
+}
+
+/// Errors raised while configuring or running a [`Demultiplexer`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DemuxError {
+    NoSamples,
+    /// Two samples share a name.
+    DuplicateSample(String),
+    /// The sample's name cannot serve as a file name, or its barcode is
+    /// empty, not all bases, or a dual index on an inline barcode.
+    InvalidSample(String),
+    /// The sample's barcode differs in length from the first sample's.
+    BarcodeLength(String),
+    /// Two barcodes are so close that, with the mismatch tolerance, a read
+    /// could match both.
+    BarcodeCollision {
+        first: String,
+        second: String,
+        distance: usize,
+    },
+    /// Reading or writing FASTQ failed.
+    Fastq(FastqError),
+}
+
+impl From<FastqError> for DemuxError {
+    fn from(err: FastqError) -> Self {
+        DemuxError::Fastq(err)
+    }
+}
+
+impl From<io::Error> for DemuxError {
+    fn from(err: io::Error) -> Self {
+        DemuxError::Fastq(err.into())
+    }
+}
+
+impl fmt::Display for DemuxError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DemuxError::NoSamples => write!(f, "no samples to demultiplex into"),
+            DemuxError::DuplicateSample(name) => write!(f, "sample {} is listed twice", name),
+            DemuxError::InvalidSample(name) => {
+                write!(f, "sample {} has an invalid name or barcode", name)
+            }
+            DemuxError::BarcodeLength(name) => {
+                write!(
+                    f,
+                    "barcode of sample {} differs in length from the others",
+                    name
+                )
+            }
+            DemuxError::BarcodeCollision {
+                first,
+                second,
+                distance,
+            } => write!(
+                f,
+                "barcodes of samples {} and {} are only {} mismatches apart",
+                first, second, distance
+            ),
+            DemuxError::Fastq(err) => err.fmt(f),
+        }
+    }
+}
+
+impl std::error::Error for DemuxError {}
+
+/// Result of matching one read's barcode.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Assignment {
+    /// Index into the demultiplexer's samples, and barcode mismatches.
+    Sample { index: usize, mismatches: usize },
+    /// No barcode within the tolerance, or no barcode found.
+    Undetermined,
+    /// Several samples' barcodes match equally well.
+    Ambiguous,
+}
+
+/// Read counts for one sample.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SampleStats {
+    pub name: String,
+    pub barcode: String,
+    pub reads: u64,
+    pub perfect: u64,
+}
+
+/// Assignment statistics of a demultiplexing run.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DemuxStats {
+    pub samples: Vec<SampleStats>,
+    pub undetermined: u64,
+    pub ambiguous: u64,
+}
+
+impl DemuxStats {
+    pub fn total(&self) -> u64 {
+        self.samples.iter().map(|s| s.reads).sum::<u64>() + self.undetermined + self.ambiguous
+    }
+
+    /// Tab-separated table with one row per sample, then `undetermined`
+    /// and `ambiguous` rows with empty barcodes.
+    pub fn to_tsv(&self) -> String {
+        let total = self.total().max(1) as f64;
+        let mut out = String::from("sample\tbarcode\treads\tperfect\tpercent\n");
+        for s in &self.samples {
+            let _ = writeln!(
+                out,
+                "{}\t{}\t{}\t{}\t{:.2}",
+                s.name,
+                s.barcode,
+                s.reads,
+                s.perfect,
+                100.0 * s.reads as f64 / total
+            );
+        }
+        for (name, reads) in [
+            (UNDETERMINED, self.undetermined),
+            ("ambiguous", self.ambiguous),
+        ] {
+            let _ = writeln!(
+                out,
+                "{}\t\t{}\t0\t{:.2}",
+                name,
+                reads,
+                100.0 * reads as f64 / total
+            );
+        }
+        out
+    }
+}
+
+/// Assigns reads to samples by barcode.
+#[derive(Debug, Clone)]
+pub struct Demultiplexer {
+    samples: Vec<Sample>,
+    options: DemuxOptions,
+}
+
+fn mismatches(a: &[u8], b: &[u8]) -> usize {
+    a.iter()
+        .zip(b)
+        .filter(|(x, y)| !x.eq_ignore_ascii_case(y))
+        .count()
+}
+
+impl Demultiplexer {
+    /// Checks the sample sheet. Barcodes must all have the same length and
+    /// be more than `2 * max_mismatches` apart, so no read can be within
+    /// tolerance of two samples.
+    pub fn new(samples: Vec<Sample>, options: DemuxOptions) -> Result<Self, DemuxError> {
+        let Some(first) = samples.first() else {
+            return Err(DemuxError::NoSamples);
+        };
+        let len = first.barcode.len();
+        let mut names = HashSet::new();
+        for sample in &samples {
+            let valid_name = !sample.name.is_empty()
+                && sample.name != UNDETERMINED
+                && !sample.name.contains(['/', '\\']);
+            let dual_allowed = options.location == BarcodeLocation::Index;
+            let valid_barcode = !sample.barcode.is_empty()
+                && sample
+                    .barcode
+                    .bytes()
+                    .all(|b| b.is_ascii_alphabetic() || dual_allowed && b == b'+');
+            if !valid_name || !valid_barcode {
+                return Err(DemuxError::InvalidSample(sample.name.clone()));
+            }
+            if !names.insert(&sample.name) {
+                return Err(DemuxError::DuplicateSample(sample.name.clone()));
+            }
+            if sample.barcode.len() != len {
+                return Err(DemuxError::BarcodeLength(sample.name.clone()));
+            }
+        }
+        for (i, a) in samples.iter().enumerate() {
+            for b in &samples[i + 1..] {
+                let distance = mismatches(a.barcode.as_bytes(), b.barcode.as_bytes());
+                if distance <= 2 * options.max_mismatches {
+                    return Err(DemuxError::BarcodeCollision {
+                        first: a.name.clone(),
+                        second: b.name.clone(),
+                        distance,
+                    });
+                }
+            }
+        }
+        Ok(Demultiplexer { samples, options })
+    }
+
+    pub fn samples(&self) -> &[Sample] {
+        &self.samples
+    }
+
+    fn barcode_len(&self) -> usize {
+        self.samples[0].barcode.len()
+    }
+
+    /// The barcode as read, or `None` if the read is too short or has no
+    /// index field.
+    fn read_barcode<'r>(&self, read: &'r FastqSeq) -> Option<&'r str> {
+        let len = self.barcode_len();
+        let barcode = match self.options.location {
+            BarcodeLocation::Inline => read.sequence().get(..len)?,
+            BarcodeLocation::Index => read.description().rsplit(':').next()?,
+        };
+        (barcode.len() == len).then_some(barcode)
+    }
+
+    pub fn assign(&self, read: &FastqSeq) -> Assignment {
+        let Some(barcode) = self.read_barcode(read) else {
+            return Assignment::Undetermined;
+        };
+        let mut best: Option<(usize, usize)> = None;
+        let mut tied = false;
+        for (index, sample) in self.samples.iter().enumerate() {
+            let n = mismatches(sample.barcode.as_bytes(), barcode.as_bytes());
+            match best {
+                Some((_, m)) if n > m => {}
+                Some((_, m)) if n == m => tied = true,
+                _ => {
+                    best = Some((index, n));
+                    tied = false;
+                }
+            }
+        }
+        match best {
+            Some((_, m)) if m > self.options.max_mismatches => Assignment::Undetermined,
+            Some(_) if tied => Assignment::Ambiguous,
+            Some((index, mismatches)) => Assignment::Sample { index, mismatches },
+            None => Assignment::Undetermined,
+        }
+    }
+
+    /// Writes every read to the writer of its sample, in sample order, or
+    /// to `undetermined`, which also receives ambiguous reads. Inline
+    /// barcodes are removed from assigned reads.
+    ///
+    /// # Panics
+    ///
+    /// Panics if there are fewer writers than samples.
+    pub fn run<R: BufRead, W: Write>(
+        &self,
+        reader: FastqReader<R>,
+        outputs: &mut [W],
+        undetermined: &mut W,
+    ) -> Result<DemuxStats, DemuxError> {
+        assert!(
+            outputs.len() >= self.samples.len(),
+            "one writer per sample is required"
+        );
+        let mut stats = DemuxStats {
+            samples: self
+                .samples
+                .iter()
+                .map(|s| SampleStats {
+                    name: s.name.clone(),
+                    barcode: s.barcode.clone(),
+                    reads: 0,
+                    perfect: 0,
+                })
+                .collect(),
+            undetermined: 0,
+            ambiguous: 0,
+        };
+        for read in reader {
+            let read = read?;
+            match self.assign(&read) {
+                Assignment::Sample { index, mismatches } => {
+                    let sample = &mut stats.samples[index];
+                    sample.reads += 1;
+                    if mismatches == 0 {
+                        sample.perfect += 1;
+                    }
+                    match self.options.location {
+                        BarcodeLocation::Inline => {
+                            let trimmed = read.slice(self.barcode_len()..read.len());
+                            write!(outputs[index], "{}", trimmed)?;
+                        }
+                        BarcodeLocation::Index => write!(outputs[index], "{}", read)?,
+                    }
+                }
+                other => {
+                    if other == Assignment::Ambiguous {
+                        stats.ambiguous += 1;
+                    } else {
+                        stats.undetermined += 1;
+                    }
+                    write!(undetermined, "{}", read)?;
+                }
+            }
+        }
+        Ok(stats)
+    }
+
+    /// Like [`run`](Self::run), writing `<sample>.fastq` files and
+    /// `undetermined.fastq` into `dir`, which must exist.
+    pub fn run_to_dir<R: BufRead>(
+        &self,
+        reader: FastqReader<R>,
+        dir: &Path,
+    ) -> Result<DemuxStats, DemuxError> {
+        let create =
+            |stem: &str| File::create(dir.join(format!("{}.fastq", stem))).map(BufWriter::new);
+        let mut outputs = self
+            .samples
+            .iter()
+            .map(|s| create(&s.name))
+            .collect::<Result<Vec<_>, _>>()?;
+        let mut undetermined = create(UNDETERMINED)?;
+        let stats = self.run(reader, &mut outputs, &mut undetermined)?;
+        for out in outputs.iter_mut().chain([&mut undetermined]) {
+            out.flush()?;
+        }
+        Ok(stats)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn samples() -> Vec<Sample> {
+        vec![Sample::new("s1", "AAAAAA"), Sample::new("s2", "CCCCCC")]
+    }
+
+    fn read(header: &str, seq: &str) -> FastqSeq {
+        let (id, desc) = header.split_once(' ').unwrap_or((header, ""));
+        FastqSeq::new(id, desc, seq, &"I".repeat(seq.len())).unwrap()
+    }
+
+    #[test]
+    fn assigns_inline_barcodes_with_mismatches() {
+        let demux = Demultiplexer::new(samples(), DemuxOptions::default()).unwrap();
+        assert_eq!(
+            demux.assign(&read("r", "AAAAAAGT")),
+            Assignment::Sample {
+                index: 0,
+                mismatches: 0
+            }
+        );
+        assert_eq!(
+            demux.assign(&read("r", "CCGCCCGT")),
+            Assignment::Sample {
+                index: 1,
+                mismatches: 1
+            }
+        );
+        assert_eq!(
+            demux.assign(&read("r", "AACCCCGT")),
+            Assignment::Undetermined
+        );
+        assert_eq!(demux.assign(&read("r", "AAA")), Assignment::Undetermined);
+    }
+
+    #[test]
+    fn reports_ambiguous_reads_without_collision_check() {
+        let options = DemuxOptions {
+            max_mismatches: 3,
+            ..DemuxOptions::default()
+        };
+        assert!(matches!(
+            Demultiplexer::new(samples(), options),
+            Err(DemuxError::BarcodeCollision { distance: 6, .. })
+        ));
+        let demux = Demultiplexer {
+            samples: samples(),
+            options,
+        };
+        assert_eq!(demux.assign(&read("r", "AAACCC")), Assignment::Ambiguous);
+    }
+
+    #[test]
+    fn reads_index_barcodes_from_description() {
+        let options = DemuxOptions {
+            location: BarcodeLocation::Index,
+            max_mismatches: 0,
+        };
+        let demux = Demultiplexer::new(
+            vec![Sample::new("a", "ACGT+TTGG"), Sample::new("b", "GTCA+CCAA")],
+            options,
+        )
+        .unwrap();
+        assert_eq!(
+            demux.assign(&read("r 1:N:0:GTCA+CCAA", "NNNN")),
+            Assignment::Sample {
+                index: 1,
+                mismatches: 0
+            }
+        );
+        assert_eq!(demux.assign(&read("r", "ACGT")), Assignment::Undetermined);
+    }
+
+    #[test]
+    fn writes_outputs_and_stats() {
+        let demux = Demultiplexer::new(samples(), DemuxOptions::default()).unwrap();
+        let input =
+            "@r1\nAAAAAAGT\n+\nIIIIII#I\n@r2\nCCCCCTGG\n+\nIIIIIIII\n@r3\nGGGGGGGG\n+\nIIIIIIII\n";
+        let mut outputs = vec![Vec::new(), Vec::new()];
+        let mut undetermined = Vec::new();
+        let stats = demux
+            .run(
+                FastqReader::new(input.as_bytes()),
+                &mut outputs,
+                &mut undetermined,
+            )
+            .unwrap();
+        assert_eq!(
+            String::from_utf8(outputs[0].clone()).unwrap(),
+            "@r1\nGT\n+\n#I\n"
+        );
+        assert_eq!(
+            String::from_utf8(outputs[1].clone()).unwrap(),
+            "@r2\nGG\n+\nII\n"
+        );
+        assert!(String::from_utf8(undetermined)
+            .unwrap()
+            .starts_with("@r3\n"));
+        assert_eq!((stats.samples[1].reads, stats.samples[1].perfect), (1, 0));
+        assert_eq!(stats.total(), 3);
+        assert!(stats.to_tsv().contains("s1\tAAAAAA\t1\t1\t33.33\n"));
+        assert!(stats.to_tsv().ends_with("ambiguous\t\t0\t0\t0.00\n"));
+    }
+
+    #[test]
+    fn validates_sample_sheet() {
+        let options = DemuxOptions::default();
+        assert_eq!(
+            Demultiplexer::new(vec![], options).err(),
+            Some(DemuxError::NoSamples)
+        );
+        assert_eq!(
+            Demultiplexer::new(
+                vec![Sample::new("a", "ACGT"), Sample::new("a", "TTTT")],
+                options
+            )
+            .err(),
+            Some(DemuxError::DuplicateSample("a".to_string()))
+        );
+        assert_eq!(
+            Demultiplexer::new(
+                vec![Sample::new("a", "ACGT"), Sample::new("b", "TTT")],
+                options
+            )
+            .err(),
+            Some(DemuxError::BarcodeLength("b".to_string()))
+        );
+        assert_eq!(
+            Demultiplexer::new(vec![Sample::new("x/y", "ACGT")], options).err(),
+            Some(DemuxError::InvalidSample("x/y".to_string()))
+        );
+        let dual = vec![Sample::new("d", "ACGT+TTGA")];
+        assert_eq!(
+            Demultiplexer::new(dual.clone(), options).err(),
+            Some(DemuxError::InvalidSample("d".to_string()))
+        );
+        let index = DemuxOptions {
+            location: BarcodeLocation::Index,
+            ..options
+        };
+        assert!(Demultiplexer::new(dual, index).is_ok());
+    }
+}
@@ -0,0 +1,1556 @@
+//! FASTA sequences and record sets.
+
+use std::cmp::Ordering;
+use std::collections::HashSet;
+use std::fmt;
+use std::fs::File;
+use std::hash::{Hash, Hasher};
+use std::io::{self, BufRead, BufReader, BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+use regex::Regex;
+
+use crate::alphabet::{infer_alphabet, Alphabet, InferenceConfig, SeqType};
+use crate::iupac;
+use crate::progress::{ProgressFn, ProgressTracker, DEFAULT_INTERVAL};
+use crate::seq;
+
+/// Number of sequence characters per line when writing FASTA.
+pub const LINE_WIDTH: usize = 60;
+
+/// A single FASTA entry: header fields plus the sequence itself.
+///
+/// `Eq` and `Hash` compare every field. To compare, hash or sort on one
+/// aspect only, wrap sequences in [`ById`] or [`BySequence`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct FastaSeq {
+    id: String,
+    description: String,
+    sequence: String,
+    seq_type: SeqType,
+    alphabet: Alphabet,
+}
+
+/// Errors raised while constructing or parsing FASTA data.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FastaError {
+    /// The sequence has no identifier.
+    MissingId,
+    /// The identifier contains whitespace.
+    InvalidId(String),
+    /// The description spans more than one line.
+    InvalidDescription(String),
+    /// The sequence contains whitespace or a non-printable character.
+    InvalidSequence { position: usize, symbol: char },
+    /// The alphabet belongs to a different molecule type.
+    AlphabetMismatch {
+        seq_type: SeqType,
+        alphabet: Alphabet,
+    },
+    /// A symbol is outside the declared alphabet; `position` is 0-based.
+    SymbolNotInAlphabet {
+        position: usize,
+        symbol: char,
+        alphabet: Alphabet,
+    },
+    /// The input is empty or only whitespace.
+    EmptyInput,
+    /// Sequence data appears before the first `>` header, at the given
+    /// 1-based line.
+    MissingHeader { line: usize },
+    /// The header is not terminated by a line break, so there is no
+    /// sequence part; carries the header text.
+    MissingLineBreak(String),
+    /// A single-entry parser found another `>` header at the given 1-based
+    /// line.
+    ExtraRecord { line: usize },
+    /// Reading the underlying input failed.
+    Io {
+        kind: io::ErrorKind,
+        message: String,
+    },
+}
+
+impl From<io::Error> for FastaError {
+    fn from(err: io::Error) -> Self {
+        FastaError::Io {
+            kind: err.kind(),
+            message: err.to_string(),
+        }
+    }
+}
+
+impl fmt::Display for FastaError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FastaError::MissingId => write!(f, "sequence has no identifier"),
+            FastaError::InvalidId(id) => write!(f, "identifier {:?} contains whitespace", id),
+            FastaError::InvalidDescription(desc) => {
+                write!(f, "description {:?} spans several lines", desc)
+            }
+            FastaError::InvalidSequence { position, symbol } => {
+                write!(
+                    f,
+                    "invalid symbol {:?} at sequence position {}",
+                    symbol, position
+                )
+            }
+            FastaError::AlphabetMismatch { seq_type, alphabet } => {
+                write!(
+                    f,
+                    "{} alphabet does not fit a {} sequence",
+                    alphabet, seq_type
+                )
+            }
+            FastaError::SymbolNotInAlphabet {
+                position,
+                symbol,
+                alphabet,
+            } => write!(
+                f,
+                "symbol {:?} at sequence position {} is not in the {} alphabet",
+                symbol, position, alphabet
+            ),
+            FastaError::EmptyInput => write!(f, "input contains no FASTA data"),
+            FastaError::MissingHeader { line } => {
+                write!(f, "sequence data before the first header at line {}", line)
+            }
+            FastaError::MissingLineBreak(header) => {
+                write!(f, "header {:?} is not followed by a line break", header)
+            }
+            FastaError::ExtraRecord { line } => write!(
+                f,
+                "unexpected second record at line {}; parse it as a FastaRecord",
+                line
+            ),
+            FastaError::Io { message, .. } => write!(f, "I/O error: {}", message),
+        }
+    }
+}
+
+impl std::error::Error for FastaError {}
+
+/// Fluent constructor for [`FastaSeq`].
+///
+/// Only the identifier is required. A missing sequence type and alphabet
+/// are inferred from the sequence using the builder's [`InferenceConfig`];
+/// if only one is given the other is derived from it.
+#[derive(Debug, Clone, Default)]
+pub struct FastaSeqBuilder {
+    id: Option<String>,
+    description: String,
+    sequence: String,
+    seq_type: Option<SeqType>,
+    alphabet: Option<Alphabet>,
+    inference: InferenceConfig,
+    strict: bool,
+}
+
+impl FastaSeqBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn id(mut self, id: &str) -> Self {
+        self.id = Some(id.to_string());
+        self
+    }
+
+    pub fn description(mut self, description: &str) -> Self {
+        self.description = description.to_string();
+        self
+    }
+
+    pub fn sequence(mut self, sequence: &str) -> Self {
+        self.sequence = sequence.to_string();
+        self
+    }
+
+    pub fn seq_type(mut self, seq_type: SeqType) -> Self {
+        self.seq_type = Some(seq_type);
+        self
+    }
+
+    pub fn alphabet(mut self, alphabet: Alphabet) -> Self {
+        self.alphabet = Some(alphabet);
+        self
+    }
+
+    pub fn inference(mut self, config: InferenceConfig) -> Self {
+        self.inference = config;
+        self
+    }
+
+    /// Reject sequences with symbols outside the alphabet. Gaps (`-`) are
+    /// always accepted.
+    pub fn strict(mut self, strict: bool) -> Self {
+        self.strict = strict;
+        self
+    }
+
+    pub fn build(self) -> Result<FastaSeq, FastaError> {
+        let id = self.id.ok_or(FastaError::MissingId)?;
+        check_fields(&id, &self.description, &self.sequence)?;
+
+        let (seq_type, alphabet) = match (self.seq_type, self.alphabet) {
+            (Some(seq_type), Some(alphabet)) => {
+                if alphabet.seq_type() != seq_type {
+                    return Err(FastaError::AlphabetMismatch { seq_type, alphabet });
+                }
+                (seq_type, alphabet)
+            }
+            (None, Some(alphabet)) => (alphabet.seq_type(), alphabet),
+            (Some(seq_type), None) => (seq_type, infer_alphabet(&self.sequence, seq_type)),
+            (None, None) => self.inference.infer(&self.sequence),
+        };
+        if self.strict {
+            if let Some((position, symbol)) = self
+                .sequence
+                .bytes()
+                .enumerate()
+                .find(|&(_, b)| b != b'-' && !alphabet.contains(b))
+            {
+                return Err(FastaError::SymbolNotInAlphabet {
+                    position,
+                    symbol: symbol as char,
+                    alphabet,
+                });
+            }
+        }
+
+        Ok(FastaSeq {
+            id,
+            description: self.description,
+            sequence: self.sequence,
+            seq_type,
+            alphabet,
+        })
+    }
+}
+
+/// Header and sequence checks shared by the builder and the reader.
+fn check_fields(id: &str, description: &str, sequence: &str) -> Result<(), FastaError> {
+    if id.is_empty() {
+        return Err(FastaError::MissingId);
+    }
+    if id.chars().any(char::is_whitespace) {
+        return Err(FastaError::InvalidId(id.to_string()));
+    }
+    if description.contains(['\n', '\r']) {
+        return Err(FastaError::InvalidDescription(description.to_string()));
+    }
+    if let Some((position, symbol)) = sequence.char_indices().find(|(_, c)| !c.is_ascii_graphic()) {
+        return Err(FastaError::InvalidSequence { position, symbol });
+    }
+    Ok(())
+}
+
+impl Default for FastaSeq {
+    /// An empty placeholder with no identifier, meant to be filled by
+    /// [`FastaReader::read_into`].
+    fn default() -> Self {
+        FastaSeq {
+            id: String::new(),
+            description: String::new(),
+            sequence: String::new(),
+            seq_type: SeqType::Dna,
+            alphabet: Alphabet::Dna,
+        }
+    }
+}
+
+impl FastaSeq {
+    pub fn builder() -> FastaSeqBuilder {
+        FastaSeqBuilder::new()
+    }
+
+    /// Parses a single FASTA entry (`>id description` followed by sequence
+    /// lines) with the given type and alphabet. Symbols are not checked
+    /// against the alphabet; see [`from_string_validated`](Self::from_string_validated).
+    ///
+    /// Malformed input is reported rather than panicking: empty input,
+    /// text before the `>` header, a header without a line break, a missing
+    /// identifier, non-printable sequence symbols, an alphabet of the wrong
+    /// type and a second `>` record each have their own [`FastaError`]
+    /// variant. Use [`FastaRecord::from_string`] for multi-entry text.
+    pub fn from_string(
+        text: &str,
+        seq_type: SeqType,
+        alphabet: Alphabet,
+    ) -> Result<FastaSeq, FastaError> {
+        Self::parse_single(text)?
+            .seq_type(seq_type)
+            .alphabet(alphabet)
+            .build()
+    }
+
+    /// Like [`from_string`](Self::from_string), inferring type and alphabet.
+    pub fn from_string_inferred(text: &str) -> Result<FastaSeq, FastaError> {
+        Self::parse_single(text)?.build()
+    }
+
+    /// Like [`from_string`](Self::from_string), but fails with
+    /// [`FastaError::SymbolNotInAlphabet`] on the first symbol outside
+    /// `alphabet`.
+    pub fn from_string_validated(
+        text: &str,
+        seq_type: SeqType,
+        alphabet: Alphabet,
+    ) -> Result<FastaSeq, FastaError> {
+        Self::parse_single(text)?
+            .seq_type(seq_type)
+            .alphabet(alphabet)
+            .strict(true)
+            .build()
+    }
+
+    fn parse_single(text: &str) -> Result<FastaSeqBuilder, FastaError> {
+        let trimmed = text.trim_start();
+        if trimmed.is_empty() {
+            return Err(FastaError::EmptyInput);
+        }
+        let Some(body) = trimmed.strip_prefix('>') else {
+            let line = text[..text.len() - trimmed.len()].matches('\n').count() + 1;
+            return Err(FastaError::MissingHeader { line });
+        };
+        let (header, sequence) = body
+            .split_once('\n')
+            .ok_or_else(|| FastaError::MissingLineBreak(body.trim_end().to_string()))?;
+        let header_line = text[..text.len() - trimmed.len()].matches('\n').count() + 1;
+        if let Some(offset) = sequence.lines().position(|l| l.starts_with('>')) {
+            return Err(FastaError::ExtraRecord {
+                line: header_line + 1 + offset,
+            });
+        }
+        let (id, description) = parse_header(header);
+        let sequence: String = sequence.chars().filter(|c| !c.is_whitespace()).collect();
+        Ok(FastaSeq::builder()
+            .id(id)
+            .description(description)
+            .sequence(&sequence))
+    }
+
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    pub fn description(&self) -> &str {
+        &self.description
+    }
+
+    pub fn sequence(&self) -> &str {
+        &self.sequence
+    }
+
+    pub fn seq_type(&self) -> SeqType {
+        self.seq_type
+    }
+
+    pub fn alphabet(&self) -> Alphabet {
+        self.alphabet
+    }
+
+    pub fn len(&self) -> usize {
+        self.sequence.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.sequence.is_empty()
+    }
+
+    /// Fraction of G+C symbols, see [`seq::gc_content`].
+    pub fn gc_content(&self) -> f64 {
+        seq::gc_content(self.sequence.as_bytes())
+    }
+
+    /// Length, composition and ambiguity counts in one pass.
+    pub fn stats(&self) -> seq::SeqStats {
+        seq::SeqStats::compute(self.sequence.as_bytes(), self.seq_type)
+    }
+
+    /// Whether both sequences are the same apart from case. Headers are not
+    /// compared.
+    pub fn equals_ignore_case(&self, other: &FastaSeq) -> bool {
+        iupac::equals_ignore_case(self.sequence.as_bytes(), other.sequence.as_bytes())
+    }
+
+    /// Whether both sequences could encode the same bases under the IUPAC
+    /// codes, e.g. `ACN` and `acR`. Headers are not compared.
+    pub fn matches_iupac(&self, other: &FastaSeq) -> bool {
+        iupac::sequences_match(self.sequence.as_bytes(), other.sequence.as_bytes())
+    }
+
+    /// Every concrete sequence this degenerate one stands for, named
+    /// `<id>_v<n>`; see [`iupac::expand`].
+    pub fn expand_degenerate(
+        &self,
+        max_variants: usize,
+    ) -> Result<Vec<FastaSeq>, iupac::ExpansionError> {
+        let variants = iupac::expand(self.sequence.as_bytes(), max_variants)?;
+        Ok(variants
+            .into_iter()
+            .enumerate()
+            .map(|(i, variant)| {
+                // Expansion only emits ASCII bases.
+                let mut seq = self.with_sequence(String::from_utf8_lossy(&variant).into_owned());
+                seq.id = format!("{}_v{}", self.id, i + 1);
+                seq
+            })
+            .collect())
+    }
+
+    /// Windows of `size` symbols every `step` symbols, with their
+    /// coordinates; see [`seq::windows`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if `size` or `step` is 0.
+    pub fn windows(&self, size: usize, step: usize) -> seq::Windows<'_> {
+        seq::windows(self.sequence.as_bytes(), size, step)
+    }
+
+    /// Splits a scaffold into contigs at runs of at least `min_run`
+    /// ambiguous bases (see [`seq::split_at_ambiguous_runs`]). Contigs are
+    /// named `<id>_ctg<n>` and keep the scaffold's description.
+    pub fn split_contigs(&self, min_run: usize) -> Vec<Contig> {
+        seq::split_at_ambiguous_runs(self.sequence.as_bytes(), min_run)
+            .into_iter()
+            .enumerate()
+            .map(|(i, range)| {
+                let mut contig = self.with_sequence(self.sequence[range.clone()].to_string());
+                contig.id = format!("{}_ctg{}", self.id, i + 1);
+                Contig {
+                    source_id: self.id.clone(),
+                    start: range.start,
+                    end: range.end,
+                    seq: contig,
+                }
+            })
+            .collect()
+    }
+
+    /// Copy with a new sequence, keeping the header and sequence type. The
+    /// alphabet is re-inferred within that type.
+    fn with_sequence(&self, sequence: String) -> FastaSeq {
+        FastaSeq {
+            id: self.id.clone(),
+            description: self.description.clone(),
+            alphabet: infer_alphabet(&sequence, self.seq_type),
+            sequence,
+            seq_type: self.seq_type,
+        }
+    }
+}
+
+impl fmt::Display for FastaSeq {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.description.is_empty() {
+            writeln!(f, ">{}", self.id)?;
+        } else {
+            writeln!(f, ">{} {}", self.id, self.description)?;
+        }
+        for line in self.sequence.as_bytes().chunks(LINE_WIDTH) {
+            // Sequences are ASCII, so every chunk boundary is a char boundary.
+            writeln!(f, "{}", std::str::from_utf8(line).map_err(|_| fmt::Error)?)?;
+        }
+        Ok(())
+    }
+}
+
+/// Borrowed [`FastaSeq`] that is compared, hashed and ordered by its
+/// identifier alone.
+#[derive(Debug, Clone, Copy)]
+pub struct ById<'a>(pub &'a FastaSeq);
+
+/// Borrowed [`FastaSeq`] that is compared, hashed and ordered by its
+/// sequence string alone (case-sensitive), ignoring the header.
+#[derive(Debug, Clone, Copy)]
+pub struct BySequence<'a>(pub &'a FastaSeq);
+
+macro_rules! impl_key_traits {
+    ($wrapper:ident, $field:ident) => {
+        impl PartialEq for $wrapper<'_> {
+            fn eq(&self, other: &Self) -> bool {
+                self.0.$field == other.0.$field
+            }
+        }
+
+        impl Eq for $wrapper<'_> {}
+
+        impl Hash for $wrapper<'_> {
+            fn hash<H: Hasher>(&self, state: &mut H) {
+                self.0.$field.hash(state);
+            }
+        }
+
+        impl PartialOrd for $wrapper<'_> {
+            fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+                Some(self.cmp(other))
+            }
+        }
+
+        impl Ord for $wrapper<'_> {
+            fn cmp(&self, other: &Self) -> Ordering {
+                self.0.$field.cmp(&other.0.$field)
+            }
+        }
+    };
+}
+
+impl_key_traits!(ById, id);
+impl_key_traits!(BySequence, sequence);
+
+/// A piece of a scaffold between runs of ambiguous bases. `start` and
+/// `end` are 0-based, half-open coordinates on the source sequence.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Contig {
+    pub source_id: String,
+    pub start: usize,
+    pub end: usize,
+    pub seq: FastaSeq,
+}
+
+/// Criteria for [`FastaRecord::filter_by`]; a sequence is kept when it
+/// meets every criterion that is set.
+#[derive(Debug, Clone, Default)]
+pub struct SeqFilter {
+    min_len: Option<usize>,
+    max_len: Option<usize>,
+    min_gc: Option<f64>,
+    max_gc: Option<f64>,
+    id_pattern: Option<Regex>,
+}
+
+impl SeqFilter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn min_len(mut self, len: usize) -> Self {
+        self.min_len = Some(len);
+        self
+    }
+
+    pub fn max_len(mut self, len: usize) -> Self {
+        self.max_len = Some(len);
+        self
+    }
+
+    /// Lowest GC fraction to keep, between 0 and 1.
+    pub fn min_gc(mut self, gc: f64) -> Self {
+        self.min_gc = Some(gc);
+        self
+    }
+
+    /// Highest GC fraction to keep, between 0 and 1.
+    pub fn max_gc(mut self, gc: f64) -> Self {
+        self.max_gc = Some(gc);
+        self
+    }
+
+    /// Keeps sequences whose identifier matches `pattern` anywhere.
+    pub fn id_matches(mut self, pattern: Regex) -> Self {
+        self.id_pattern = Some(pattern);
+        self
+    }
+
+    pub fn matches(&self, seq: &FastaSeq) -> bool {
+        let len = seq.len();
+        if self.min_len.is_some_and(|min| len < min) || self.max_len.is_some_and(|max| len > max) {
+            return false;
+        }
+        if self.min_gc.is_some() || self.max_gc.is_some() {
+            let gc = seq.gc_content();
+            if self.min_gc.is_some_and(|min| gc < min) || self.max_gc.is_some_and(|max| gc > max) {
+                return false;
+            }
+        }
+        self.id_pattern
+            .as_ref()
+            .is_none_or(|pattern| pattern.is_match(&seq.id))
+    }
+}
+
+/// How [`FastaRecord::split`] groups entries.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SplitMode {
+    /// One entry per group.
+    PerRecord,
+    /// Consecutive groups of this many entries; the last may be smaller.
+    Groups(usize),
+    /// This many groups with total sequence lengths as even as possible,
+    /// for sharding a genome into similar-sized jobs.
+    Balanced(usize),
+}
+
+/// An ordered set of FASTA entries, typically the contents of one file.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct FastaRecord {
+    seqs: Vec<FastaSeq>,
+}
+
+impl FastaRecord {
+    pub fn new(seqs: Vec<FastaSeq>) -> Self {
+        FastaRecord { seqs }
+    }
+
+    pub fn seqs(&self) -> &[FastaSeq] {
+        &self.seqs
+    }
+
+    pub fn len(&self) -> usize {
+        self.seqs.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.seqs.is_empty()
+    }
+
+    pub fn iter(&self) -> std::slice::Iter<'_, FastaSeq> {
+        self.seqs.iter()
+    }
+
+    pub fn iter_mut(&mut self) -> std::slice::IterMut<'_, FastaSeq> {
+        self.seqs.iter_mut()
+    }
+
+    pub fn push(&mut self, seq: FastaSeq) {
+        self.seqs.push(seq);
+    }
+
+    /// Sequence with the given identifier, if any.
+    pub fn get(&self, id: &str) -> Option<&FastaSeq> {
+        self.seqs.iter().find(|seq| seq.id == id)
+    }
+
+    /// Removes entries whose sequence string repeats an earlier entry's,
+    /// keeping the first occurrence. Returns the number removed.
+    pub fn dedup_by_sequence(&mut self) -> usize {
+        self.dedup_by_key(|seq| &seq.sequence)
+    }
+
+    /// Removes entries whose identifier repeats an earlier entry's, keeping
+    /// the first occurrence. Returns the number removed.
+    pub fn dedup_by_id(&mut self) -> usize {
+        self.dedup_by_key(|seq| &seq.id)
+    }
+
+    fn dedup_by_key<F>(&mut self, key: F) -> usize
+    where
+        F: Fn(&FastaSeq) -> &String,
+    {
+        let keep: Vec<bool> = {
+            let mut seen = HashSet::new();
+            self.seqs.iter().map(|seq| seen.insert(key(seq))).collect()
+        };
+        let before = self.seqs.len();
+        let mut keep = keep.into_iter();
+        self.seqs.retain(|_| keep.next().unwrap_or(true));
+        before - self.seqs.len()
+    }
+
+    /// Sorts entries by identifier; the sort is stable.
+    pub fn sort_by_id(&mut self) {
+        self.seqs.sort_by(|a, b| ById(a).cmp(&ById(b)));
+    }
+
+    /// Sorts entries by sequence string; the sort is stable.
+    pub fn sort_by_sequence(&mut self) {
+        self.seqs.sort_by(|a, b| BySequence(a).cmp(&BySequence(b)));
+    }
+
+    /// Contigs of every sequence, see [`FastaSeq::split_contigs`].
+    pub fn split_contigs(&self, min_run: usize) -> Vec<Contig> {
+        self.iter()
+            .flat_map(|seq| seq.split_contigs(min_run))
+            .collect()
+    }
+
+    /// New record set with the sequences matching `filter`, e.g.
+    /// `record.filter_by(&SeqFilter::new().min_len(1000))` keeps contigs of
+    /// at least 1 kb.
+    pub fn filter_by(&self, filter: &SeqFilter) -> FastaRecord {
+        self.filter(|seq| filter.matches(seq))
+    }
+
+    /// New record set with the sequences for which `predicate` holds.
+    pub fn filter<P>(&self, mut predicate: P) -> FastaRecord
+    where
+        P: FnMut(&FastaSeq) -> bool,
+    {
+        self.iter().filter(|seq| predicate(seq)).cloned().collect()
+    }
+
+    /// New record set with every sequence string transformed by `f`, e.g.
+    /// upper-casing or masking. Headers and sequence types are kept.
+    pub fn map_sequences<F>(&self, mut f: F) -> FastaRecord
+    where
+        F: FnMut(&str) -> String,
+    {
+        self.iter()
+            .map(|seq| seq.with_sequence(f(&seq.sequence)))
+            .collect()
+    }
+
+    /// Splits the set into the sequences for which `predicate` holds and the
+    /// rest, preserving order in both.
+    pub fn partition<P>(&self, mut predicate: P) -> (FastaRecord, FastaRecord)
+    where
+        P: FnMut(&FastaSeq) -> bool,
+    {
+        self.iter().cloned().partition(|seq| predicate(seq))
+    }
+
+    /// Splits the set into groups, each keeping the original entry order.
+    /// No group is empty, so there may be fewer groups than asked for.
+    ///
+    /// `Balanced` assigns entries longest first to the group with the
+    /// least sequence so far, breaking ties by fewest entries.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a group size or count is 0.
+    pub fn split(&self, mode: SplitMode) -> Vec<FastaRecord> {
+        match mode {
+            SplitMode::PerRecord => self.split(SplitMode::Groups(1)),
+            SplitMode::Groups(size) => {
+                assert!(size > 0, "group size must be non-zero");
+                self.seqs
+                    .chunks(size)
+                    .map(|chunk| FastaRecord::new(chunk.to_vec()))
+                    .collect()
+            }
+            SplitMode::Balanced(count) => {
+                assert!(count > 0, "group count must be non-zero");
+                let count = count.min(self.len());
+                let mut order: Vec<usize> = (0..self.len()).collect();
+                order.sort_by_key(|&i| std::cmp::Reverse(self.seqs[i].len()));
+                let mut totals = vec![0usize; count];
+                let mut members = vec![0usize; count];
+                let mut group_of = vec![0usize; self.len()];
+                for i in order {
+                    let group = (0..count)
+                        .min_by_key(|&g| (totals[g], members[g]))
+                        .unwrap_or(0);
+                    totals[group] += self.seqs[i].len();
+                    members[group] += 1;
+                    group_of[i] = group;
+                }
+                let mut groups = vec![FastaRecord::default(); count];
+                for (seq, group) in self.iter().zip(group_of) {
+                    groups[group].push(seq.clone());
+                }
+                groups
+            }
+        }
+    }
+
+    /// Writes the groups of [`split`](Self::split) to files in `dir`, which
+    /// must exist, and returns their paths. `PerRecord` files are named
+    /// `<id>.fasta`, with characters other than ASCII alphanumerics, `.`,
+    /// `-` and `_` replaced by `_`; other modes write
+    /// `<stem>.part_001.fasta` onwards. Entries sharing an identifier
+    /// overwrite each other's `PerRecord` file; use
+    /// [`dedup_by_id`](Self::dedup_by_id) first if that matters.
+    pub fn write_split(
+        &self,
+        dir: &Path,
+        stem: &str,
+        mode: SplitMode,
+    ) -> Result<Vec<PathBuf>, FastaError> {
+        let groups = self.split(mode);
+        let width = groups.len().to_string().len().max(3);
+        let mut paths = Vec::with_capacity(groups.len());
+        for (i, group) in groups.iter().enumerate() {
+            let name = match mode {
+                SplitMode::PerRecord => {
+                    let id: String = group.seqs[0]
+                        .id
+                        .chars()
+                        .map(|c| {
+                            if c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | '_') {
+                                c
+                            } else {
+                                '_'
+                            }
+                        })
+                        .collect();
+                    format!("{}.fasta", id)
+                }
+                _ => format!("{}.part_{:0width$}.fasta", stem, i + 1, width = width),
+            };
+            let path = dir.join(name);
+            let mut out = BufWriter::new(File::create(&path)?);
+            for seq in group.iter() {
+                write!(out, "{}", seq)?;
+            }
+            out.flush()?;
+            paths.push(path);
+        }
+        Ok(paths)
+    }
+}
+
+impl FastaRecord {
+    /// Parses a string holding any number of FASTA entries, inferring each
+    /// entry's type. Empty input gives an empty record set.
+    pub fn from_string(text: &str) -> Result<FastaRecord, FastaError> {
+        Self::from_reader(text.as_bytes())
+    }
+
+    /// Parses every entry of a FASTA stream.
+    pub fn from_reader<R: BufRead>(reader: R) -> Result<FastaRecord, FastaError> {
+        FastaReader::new(reader).collect()
+    }
+
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<FastaRecord, FastaError> {
+        Self::from_reader(BufReader::new(File::open(path)?))
+    }
+}
+
+/// Splits a header line (without the `>`) into identifier and description.
+pub(crate) fn parse_header(header: &str) -> (&str, &str) {
+    let header = header.trim();
+    match header.split_once(char::is_whitespace) {
+        Some((id, description)) => (id, description.trim_start()),
+        None => (header, ""),
+    }
+}
+
+/// Streaming FASTA parser yielding one [`FastaSeq`] at a time.
+///
+/// Sequence lines are concatenated with whitespace removed, and sequence
+/// types are inferred with the reader's [`InferenceConfig`].
+pub struct FastaReader<R> {
+    reader: R,
+    line: Vec<u8>,
+    line_no: usize,
+    /// Header of the entry being read, without the `>`; valid only when
+    /// `has_header` is set.
+    header: String,
+    has_header: bool,
+    done: bool,
+    progress: Option<ProgressTracker>,
+    inference: InferenceConfig,
+}
+
+impl<R: BufRead> FastaReader<R> {
+    pub fn new(reader: R) -> Self {
+        FastaReader {
+            reader,
+            line: Vec::new(),
+            line_no: 0,
+            header: String::new(),
+            has_header: false,
+            done: false,
+            progress: None,
+            inference: InferenceConfig::default(),
+        }
+    }
+
+    /// Heuristics used to infer each entry's sequence type.
+    pub fn with_inference(mut self, config: InferenceConfig) -> Self {
+        self.inference = config;
+        self
+    }
+
+    /// Calls `callback` after every parsed entry, every
+    /// [`DEFAULT_INTERVAL`] bytes within long entries, and once at the end.
+    pub fn with_progress(self, callback: ProgressFn) -> Self {
+        self.with_progress_interval(callback, DEFAULT_INTERVAL)
+    }
+
+    /// Like [`with_progress`](Self::with_progress) with a custom byte interval.
+    pub fn with_progress_interval(mut self, callback: ProgressFn, interval: u64) -> Self {
+        self.progress = Some(ProgressTracker::new(callback, interval));
+        self
+    }
+
+    /// Parses the next entry into `seq`, reusing its string buffers, and
+    /// returns `Ok(false)` at the end of input. Reading a whole file into
+    /// one `FastaSeq` this way avoids allocating per entry once the buffers
+    /// have grown to the longest entry.
+    ///
+    /// On error `seq` holds partial data; reading can continue with the
+    /// next entry unless the input itself is malformed.
+    pub fn read_into(&mut self, seq: &mut FastaSeq) -> Result<bool, FastaError> {
+        if self.done {
+            return Ok(false);
+        }
+        let entry = self.read_raw_into(seq);
+        if let Some(progress) = self.progress.as_mut() {
+            if let Ok(true) = entry {
+                progress.add_record();
+            }
+            // Bad entries are skipped and reading goes on, so only the end
+            // of input or a malformed stream finishes the report.
+            if self.done {
+                progress.finish();
+            }
+        }
+        entry
+    }
+
+    fn read_raw_into(&mut self, seq: &mut FastaSeq) -> Result<bool, FastaError> {
+        seq.sequence.clear();
+        loop {
+            self.line.clear();
+            let read = self.reader.read_until(b'\n', &mut self.line)?;
+            if let Some(progress) = self.progress.as_mut() {
+                progress.add_bytes(read);
+            }
+            if read == 0 {
+                self.done = true;
+                if !self.has_header {
+                    return Ok(false);
+                }
+                self.has_header = false;
+                set_header(&self.header, seq);
+                finish_entry(seq, &self.inference)?;
+                return Ok(true);
+            }
+            self.line_no += 1;
+            let line = String::from_utf8_lossy(&self.line);
+            if let Some(header) = line.strip_prefix('>') {
+                let previous = self.has_header;
+                if previous {
+                    set_header(&self.header, seq);
+                }
+                self.header.clear();
+                self.header.push_str(header.trim_end());
+                self.has_header = true;
+                if previous {
+                    finish_entry(seq, &self.inference)?;
+                    return Ok(true);
+                }
+                continue;
+            }
+            if line.trim().is_empty() {
+                continue;
+            }
+            if !self.has_header {
+                self.done = true;
+                return Err(FastaError::MissingHeader { line: self.line_no });
+            }
+            seq.sequence
+                .extend(line.chars().filter(|c| !c.is_whitespace()));
+        }
+    }
+}
+
+/// Copies the ID and description of `header` into `seq`'s buffers.
+fn set_header(header: &str, seq: &mut FastaSeq) {
+    let (id, description) = parse_header(header);
+    seq.id.clear();
+    seq.id.push_str(id);
+    seq.description.clear();
+    seq.description.push_str(description);
+}
+
+/// Validates a filled entry and infers its type, as the builder would.
+fn finish_entry(seq: &mut FastaSeq, inference: &InferenceConfig) -> Result<(), FastaError> {
+    check_fields(&seq.id, &seq.description, &seq.sequence)?;
+    (seq.seq_type, seq.alphabet) = inference.infer(&seq.sequence);
+    Ok(())
+}
+
+impl<R: BufRead> Iterator for FastaReader<R> {
+    type Item = Result<FastaSeq, FastaError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut seq = FastaSeq::default();
+        match self.read_into(&mut seq) {
+            Ok(true) => Some(Ok(seq)),
+            Ok(false) => None,
+            Err(err) => Some(Err(err)),
+        }
+    }
+}
+
+impl IntoIterator for FastaRecord {
+    type Item = FastaSeq;
+    type IntoIter = std::vec::IntoIter<FastaSeq>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.seqs.into_iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a FastaRecord {
+    type Item = &'a FastaSeq;
+    type IntoIter = std::slice::Iter<'a, FastaSeq>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.seqs.iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a mut FastaRecord {
+    type Item = &'a mut FastaSeq;
+    type IntoIter = std::slice::IterMut<'a, FastaSeq>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.seqs.iter_mut()
+    }
+}
+
+impl FromIterator<FastaSeq> for FastaRecord {
+    fn from_iter<I: IntoIterator<Item = FastaSeq>>(iter: I) -> Self {
+        FastaRecord {
+            seqs: iter.into_iter().collect(),
+        }
+    }
+}
+
+impl Extend<FastaSeq> for FastaRecord {
+    fn extend<I: IntoIterator<Item = FastaSeq>>(&mut self, iter: I) {
+        self.seqs.extend(iter);
+    }
+}
+
+impl<'a> Extend<&'a FastaSeq> for FastaRecord {
+    fn extend<I: IntoIterator<Item = &'a FastaSeq>>(&mut self, iter: I) {
+        self.seqs.extend(iter.into_iter().cloned());
+    }
+}
+
+impl fmt::Display for FastaRecord {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for seq in &self.seqs {
+            write!(f, "{}", seq)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn builder_infers_type_and_alphabet() {
+        let seq = FastaSeq::builder()
+            .id("s1")
+            .sequence("ACGUN")
+            .build()
+            .unwrap();
+        assert_eq!(seq.seq_type(), SeqType::Rna);
+        assert_eq!(seq.alphabet(), Alphabet::RnaIupac);
+
+        let seq = FastaSeq::builder()
+            .id("s2")
+            .sequence("ACGT")
+            .seq_type(SeqType::Protein)
+            .build()
+            .unwrap();
+        assert_eq!(seq.alphabet(), Alphabet::Protein);
+
+        let seq = FastaSeq::builder()
+            .id("s3")
+            .alphabet(Alphabet::DnaIupac)
+            .build()
+            .unwrap();
+        assert_eq!(seq.seq_type(), SeqType::Dna);
+        assert!(seq.is_empty());
+    }
+
+    #[test]
+    fn builder_uses_inference_config() {
+        let config = InferenceConfig {
+            prefer: SeqType::Protein,
+            ..InferenceConfig::default()
+        };
+        let seq = FastaSeq::builder()
+            .id("s1")
+            .sequence("MKVHWRY")
+            .inference(config)
+            .build()
+            .unwrap();
+        assert_eq!(seq.seq_type(), SeqType::Protein);
+    }
+
+    #[test]
+    fn from_string_parses_single_entry() {
+        let seq = FastaSeq::from_string(">s1 desc\nACGT\nNN\n", SeqType::Dna, Alphabet::DnaIupac)
+            .unwrap();
+        assert_eq!(seq.id(), "s1");
+        assert_eq!(seq.description(), "desc");
+        assert_eq!(seq.sequence(), "ACGTNN");
+
+        let seq = FastaSeq::from_string_inferred(">p\nMKLVE").unwrap();
+        assert_eq!(seq.alphabet(), Alphabet::Protein);
+    }
+
+    #[test]
+    fn from_string_reports_each_malformed_input() {
+        let parse = |text: &str| FastaSeq::from_string(text, SeqType::Dna, Alphabet::Dna);
+        assert_eq!(parse(""), Err(FastaError::EmptyInput));
+        assert_eq!(parse(" \n\t"), Err(FastaError::EmptyInput));
+        assert_eq!(parse("ACGT\n"), Err(FastaError::MissingHeader { line: 1 }));
+        assert_eq!(
+            parse("\n\nACGT\n>s1\nACGT"),
+            Err(FastaError::MissingHeader { line: 3 })
+        );
+        assert_eq!(
+            parse(">s1 ACGT"),
+            Err(FastaError::MissingLineBreak("s1 ACGT".to_string()))
+        );
+        assert_eq!(parse(">\nACGT"), Err(FastaError::MissingId));
+        assert_eq!(
+            parse(">s1\nAC\u{7}GT"),
+            Err(FastaError::InvalidSequence {
+                position: 2,
+                symbol: '\u{7}'
+            })
+        );
+        assert_eq!(
+            FastaSeq::from_string(">s1\nACGT", SeqType::Rna, Alphabet::Dna),
+            Err(FastaError::AlphabetMismatch {
+                seq_type: SeqType::Rna,
+                alphabet: Alphabet::Dna
+            })
+        );
+    }
+
+    #[test]
+    fn from_string_rejects_extra_records() {
+        let text = ">s1\nACGT\n>s2\nGGCC\n";
+        assert_eq!(
+            FastaSeq::from_string(text, SeqType::Dna, Alphabet::Dna),
+            Err(FastaError::ExtraRecord { line: 3 })
+        );
+        assert_eq!(
+            FastaSeq::from_string_inferred("\n>s1\nAC\nGT\n>s2\n"),
+            Err(FastaError::ExtraRecord { line: 5 })
+        );
+    }
+
+    #[test]
+    fn record_from_string_splits_entries() {
+        let text = ">s1 one\nACGT\nAC\n>s2\nMKLVE\n\n>s3\n";
+        let record = FastaRecord::from_string(text).unwrap();
+        let ids: Vec<&str> = record.iter().map(FastaSeq::id).collect();
+        assert_eq!(ids, vec!["s1", "s2", "s3"]);
+        assert_eq!(record.seqs()[0].sequence(), "ACGTAC");
+        assert_eq!(record.seqs()[1].seq_type(), SeqType::Protein);
+        assert!(FastaRecord::from_string("").unwrap().is_empty());
+        assert_eq!(
+            FastaRecord::from_string("ACGT\n>s1\nA"),
+            Err(FastaError::MissingHeader { line: 1 })
+        );
+    }
+
+    #[test]
+    fn from_string_inferred_reports_malformed_input() {
+        assert_eq!(
+            FastaSeq::from_string_inferred(""),
+            Err(FastaError::EmptyInput)
+        );
+        assert_eq!(
+            FastaSeq::from_string_inferred("MKLV"),
+            Err(FastaError::MissingHeader { line: 1 })
+        );
+        assert_eq!(
+            FastaSeq::from_string_inferred(">p1"),
+            Err(FastaError::MissingLineBreak("p1".to_string()))
+        );
+        let empty = FastaSeq::from_string_inferred(">p1\n").unwrap();
+        assert!(empty.is_empty());
+        let err = FastaSeq::from_string_inferred(">p1").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "header \"p1\" is not followed by a line break"
+        );
+    }
+
+    #[test]
+    fn from_string_validated_rejects_foreign_symbols() {
+        let text = ">s1\nACG@7T";
+        assert!(FastaSeq::from_string(text, SeqType::Dna, Alphabet::Dna).is_ok());
+        assert_eq!(
+            FastaSeq::from_string_validated(text, SeqType::Dna, Alphabet::Dna),
+            Err(FastaError::SymbolNotInAlphabet {
+                position: 3,
+                symbol: '@',
+                alphabet: Alphabet::Dna
+            })
+        );
+        let ok = FastaSeq::from_string_validated(">s1\nac-gt", SeqType::Dna, Alphabet::Dna);
+        assert_eq!(ok.unwrap().sequence(), "ac-gt");
+        let err =
+            FastaSeq::from_string_validated(">s1\nACGN", SeqType::Dna, Alphabet::Dna).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "symbol 'N' at sequence position 3 is not in the DNA alphabet"
+        );
+    }
+
+    #[test]
+    fn builder_validates_fields() {
+        assert_eq!(
+            FastaSeq::builder().sequence("ACGT").build(),
+            Err(FastaError::MissingId)
+        );
+        assert_eq!(
+            FastaSeq::builder().id("a b").build(),
+            Err(FastaError::InvalidId("a b".to_string()))
+        );
+        assert!(matches!(
+            FastaSeq::builder().id("a").description("x\ny").build(),
+            Err(FastaError::InvalidDescription(_))
+        ));
+        assert_eq!(
+            FastaSeq::builder().id("a").sequence("AC GT").build(),
+            Err(FastaError::InvalidSequence {
+                position: 2,
+                symbol: ' '
+            })
+        );
+        assert_eq!(
+            FastaSeq::builder()
+                .id("a")
+                .seq_type(SeqType::Dna)
+                .alphabet(Alphabet::Protein)
+                .build(),
+            Err(FastaError::AlphabetMismatch {
+                seq_type: SeqType::Dna,
+                alphabet: Alphabet::Protein
+            })
+        );
+    }
+
+    #[test]
+    fn display_wraps_sequence_lines() {
+        let seq = "A".repeat(LINE_WIDTH + 5);
+        let fasta = FastaSeq::builder()
+            .id("s1")
+            .description("test seq")
+            .sequence(&seq)
+            .build()
+            .unwrap();
+        let text = fasta.to_string();
+        let lines: Vec<&str> = text.lines().collect();
+        assert_eq!(lines[0], ">s1 test seq");
+        assert_eq!(lines[1].len(), LINE_WIDTH);
+        assert_eq!(lines[2], "AAAAA");
+    }
+
+    fn dna(id: &str, sequence: &str) -> FastaSeq {
+        FastaSeq::builder()
+            .id(id)
+            .sequence(sequence)
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn record_collects_and_iterates() {
+        let record: FastaRecord = vec![dna("a", "ACGT"), dna("b", "AC"), dna("c", "ACGTAC")]
+            .into_iter()
+            .collect();
+        let long: FastaRecord = record.iter().filter(|s| s.len() >= 4).cloned().collect();
+        let ids: Vec<&str> = long.iter().map(FastaSeq::id).collect();
+        assert_eq!(ids, vec!["a", "c"]);
+
+        let mut merged = FastaRecord::default();
+        merged.push(dna("x", "A"));
+        merged.extend(&long);
+        merged.extend(record.into_iter().filter(|s| s.id() == "b"));
+        let ids: Vec<String> = merged.into_iter().map(|s| s.id().to_string()).collect();
+        assert_eq!(ids, vec!["x", "a", "c", "b"]);
+    }
+
+    #[test]
+    fn windows_drive_gc_profiles() {
+        let seq = dna("s1", "GGGGAAAACCCC");
+        let profile: Vec<(usize, f64)> = seq
+            .windows(4, 4)
+            .map(|w| (w.start, w.gc_content()))
+            .collect();
+        assert_eq!(profile, vec![(0, 1.0), (4, 0.0), (8, 1.0)]);
+    }
+
+    #[test]
+    fn splits_scaffolds_into_contigs() {
+        let scaffold = FastaSeq::builder()
+            .id("scf1")
+            .description("assembly v2")
+            .sequence("ACGTNNNNNNGGnCCNNNNNN")
+            .build()
+            .unwrap();
+        let contigs = scaffold.split_contigs(5);
+        assert_eq!(contigs.len(), 2);
+        assert_eq!((contigs[0].start, contigs[0].end), (0, 4));
+        assert_eq!(contigs[1].seq.id(), "scf1_ctg2");
+        assert_eq!(contigs[1].seq.sequence(), "GGnCC");
+        assert_eq!(contigs[1].seq.description(), "assembly v2");
+        assert_eq!(contigs[1].seq.alphabet(), Alphabet::DnaIupac);
+        assert_eq!(contigs[0].seq.alphabet(), Alphabet::Dna);
+        assert_eq!(contigs[1].source_id, "scf1");
+
+        let record = FastaRecord::new(vec![scaffold, dna("s2", "NNAC")]);
+        assert_eq!(record.split_contigs(2).len(), 3);
+    }
+
+    #[test]
+    fn compares_sequences_ignoring_case_and_ambiguity() {
+        let a = dna("a", "ACGTN");
+        assert!(a.equals_ignore_case(&dna("b", "acgtn")));
+        assert!(!a.equals_ignore_case(&dna("b", "acgtc")));
+        assert!(a.matches_iupac(&dna("b", "RCGTc")));
+        assert!(!a.matches_iupac(&dna("b", "YCGTc")));
+    }
+
+    #[test]
+    fn key_wrappers_choose_the_compared_field() {
+        let a = dna("a", "ACGT");
+        let b = dna("b", "ACGT");
+        let a2 = dna("a", "GG");
+        assert_eq!(BySequence(&a), BySequence(&b));
+        assert_ne!(ById(&a), ById(&b));
+        assert_eq!(ById(&a), ById(&a2));
+        assert!(BySequence(&a) < BySequence(&a2));
+        assert_ne!(a, b);
+
+        let unique: HashSet<BySequence> = [&a, &b, &a2].into_iter().map(BySequence).collect();
+        assert_eq!(unique.len(), 2);
+        let whole: HashSet<&FastaSeq> = [&a, &b, &a, &a2].into_iter().collect();
+        assert_eq!(whole.len(), 3);
+    }
+
+    #[test]
+    fn dedups_and_sorts_records() {
+        let mut record = FastaRecord::new(vec![
+            dna("c", "ACGT"),
+            dna("a", "GGCC"),
+            dna("b", "ACGT"),
+            dna("a", "TTTT"),
+        ]);
+        let mut by_id = record.clone();
+        assert_eq!(by_id.dedup_by_id(), 1);
+        assert_eq!(record.dedup_by_sequence(), 1);
+        let ids: Vec<&str> = record.iter().map(FastaSeq::id).collect();
+        assert_eq!(ids, vec!["c", "a", "a"]);
+
+        record.sort_by_id();
+        let seqs: Vec<&str> = record.iter().map(FastaSeq::sequence).collect();
+        assert_eq!(seqs, vec!["GGCC", "TTTT", "ACGT"]);
+        record.sort_by_sequence();
+        assert_eq!(record.seqs()[0].id(), "c");
+    }
+
+    #[test]
+    fn stats_follow_sequence_type() {
+        let stats = dna("s", "ACGTN").stats();
+        assert_eq!(stats.n_count, Some(1));
+        let protein = FastaSeq::builder()
+            .id("p")
+            .sequence("MKLE")
+            .build()
+            .unwrap();
+        assert_eq!(protein.stats().gc_percent, None);
+    }
+
+    #[test]
+    fn expands_degenerate_primer() {
+        let primer = dna("fwd", "ATGR");
+        let variants = primer.expand_degenerate(16).unwrap();
+        let seqs: Vec<&str> = variants.iter().map(FastaSeq::sequence).collect();
+        assert_eq!(seqs, vec!["ATGA", "ATGG"]);
+        assert_eq!(variants[1].id(), "fwd_v2");
+        assert_eq!(variants[1].alphabet(), Alphabet::Dna);
+        assert!(dna("p", "NNNN").expand_degenerate(16).is_err());
+    }
+
+    #[test]
+    fn reader_read_into_reuses_the_entry() {
+        let text = ">a one\nACGTACGT\nACGT\n>b\nMKLV\n";
+        let mut reader = FastaReader::new(text.as_bytes());
+        let mut seq = FastaSeq::default();
+        assert_eq!(reader.read_into(&mut seq), Ok(true));
+        assert_eq!(
+            (seq.id(), seq.description(), seq.sequence()),
+            ("a", "one", "ACGTACGTACGT")
+        );
+        let capacity = seq.sequence.capacity();
+        assert_eq!(reader.read_into(&mut seq), Ok(true));
+        assert_eq!((seq.id(), seq.description()), ("b", ""));
+        assert_eq!(seq.seq_type(), SeqType::Protein);
+        assert_eq!(seq.sequence.capacity(), capacity);
+        assert_eq!(reader.read_into(&mut seq), Ok(false));
+    }
+
+    #[test]
+    fn record_looks_up_by_id() {
+        let record = FastaRecord::new(vec![dna("a", "ACGT"), dna("b", "AC")]);
+        assert_eq!(record.get("b").map(FastaSeq::len), Some(2));
+        assert!(record.get("z").is_none());
+        assert_eq!((&record).into_iter().count(), 2);
+    }
+
+    fn contigs() -> FastaRecord {
+        FastaRecord::new(vec![
+            dna("ctg1", "ACGTACGTAC"),
+            dna("ctg2", "GGGCCC"),
+            dna("scaffold1", "ATATATATATAT"),
+        ])
+    }
+
+    #[test]
+    fn filters_by_length_gc_and_id() {
+        let ids = |record: FastaRecord| -> Vec<String> {
+            record.iter().map(|s| s.id().to_string()).collect()
+        };
+        let record = contigs();
+        assert_eq!(
+            ids(record.filter_by(&SeqFilter::new().min_len(10))),
+            vec!["ctg1", "scaffold1"]
+        );
+        assert_eq!(
+            ids(record.filter_by(&SeqFilter::new().min_gc(0.5).max_len(8))),
+            vec!["ctg2"]
+        );
+        let pattern = Regex::new("^ctg").unwrap();
+        assert_eq!(
+            ids(record.filter_by(&SeqFilter::new().id_matches(pattern).max_gc(0.5))),
+            vec!["ctg1"]
+        );
+        assert_eq!(record.filter_by(&SeqFilter::new()).len(), 3);
+    }
+
+    #[test]
+    fn maps_and_partitions_sequences() {
+        let masked = contigs().map_sequences(|s| s.replace("AT", "NN"));
+        let scaffold = masked.get("scaffold1").unwrap();
+        assert_eq!(scaffold.sequence(), "NNNNNNNNNNNN");
+        assert_eq!(scaffold.alphabet(), Alphabet::DnaIupac);
+
+        let (long, short) = contigs().partition(|s| s.len() >= 10);
+        assert_eq!(long.len(), 2);
+        assert_eq!(short.seqs()[0].id(), "ctg2");
+    }
+
+    #[test]
+    fn splits_into_groups() {
+        let record =
+            FastaRecord::from_string(">a\nAAAAAAAAAA\n>b\nCC\n>c\nGGGGGG\n>d\nTTTTT\n>e\nA\n")
+                .unwrap();
+        let layout = |groups: Vec<FastaRecord>| -> Vec<Vec<String>> {
+            groups
+                .iter()
+                .map(|g| g.iter().map(|s| s.id().to_string()).collect())
+                .collect()
+        };
+        assert_eq!(layout(record.split(SplitMode::PerRecord)).len(), 5);
+        assert_eq!(
+            layout(record.split(SplitMode::Groups(2))),
+            vec![vec!["a", "b"], vec!["c", "d"], vec!["e"]]
+        );
+        // Totals 10, 6 + 1 and 2 + 5: as even as whole entries allow.
+        assert_eq!(
+            layout(record.split(SplitMode::Balanced(3))),
+            vec![vec!["a"], vec!["c", "e"], vec!["b", "d"]]
+        );
+        assert_eq!(record.split(SplitMode::Balanced(10)).len(), 5);
+        assert!(FastaRecord::default()
+            .split(SplitMode::Balanced(2))
+            .is_empty());
+
+        let empty = FastaRecord::from_string(">a\n\n>b\n\n>c\n\n").unwrap();
+        let sizes: Vec<usize> = empty
+            .split(SplitMode::Balanced(3))
+            .iter()
+            .map(FastaRecord::len)
+            .collect();
+        assert_eq!(sizes, [1, 1, 1]);
+    }
+
+    #[test]
+    fn writes_split_files() {
+        let dir = std::env::temp_dir().join(format!("bio_oxide_split_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let record = FastaRecord::from_string(">chr1 x\nACGT\n>scaffold/2\nGG\n").unwrap();
+
+        let paths = record
+            .write_split(&dir, "genome", SplitMode::PerRecord)
+            .unwrap();
+        let names: Vec<_> = paths.iter().map(|p| p.file_name().unwrap()).collect();
+        assert_eq!(names, ["chr1.fasta", "scaffold_2.fasta"]);
+        assert_eq!(
+            std::fs::read_to_string(&paths[0]).unwrap(),
+            ">chr1 x\nACGT\n"
+        );
+
+        let paths = record
+            .write_split(&dir, "genome", SplitMode::Groups(5))
+            .unwrap();
+        assert_eq!(paths, [dir.join("genome.part_001.fasta")]);
+        assert_eq!(FastaRecord::from_file(&paths[0]).unwrap(), record);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn reader_parses_multiline_entries() {
+        let text = ">seq1 first entry\nACGT\nAC\n\n>seq2\r\nMKLV\r\n>empty\n";
+        let record = FastaRecord::from_reader(text.as_bytes()).unwrap();
+        assert_eq!(record.len(), 3);
+        let first = &record.seqs()[0];
+        assert_eq!(first.id(), "seq1");
+        assert_eq!(first.description(), "first entry");
+        assert_eq!(first.sequence(), "ACGTAC");
+        assert_eq!(record.seqs()[1].seq_type(), SeqType::Protein);
+        assert!(record.seqs()[2].is_empty());
+    }
+
+    #[test]
+    fn reader_rejects_data_before_header() {
+        let err = FastaRecord::from_reader("\nACGT\n>s\nA\n".as_bytes()).unwrap_err();
+        assert_eq!(err, FastaError::MissingHeader { line: 2 });
+        let err = FastaRecord::from_reader(">\nACGT\n".as_bytes()).unwrap_err();
+        assert_eq!(err, FastaError::MissingId);
+    }
+
+    #[test]
+    fn reader_reports_progress() {
+        use crate::progress::{to_channel, Progress};
+        use std::sync::mpsc;
+
+        let (tx, rx) = mpsc::channel();
+        let text = ">a\nACGT\n>b\nGG\n";
+        let reader = FastaReader::new(text.as_bytes()).with_progress(to_channel(tx));
+        assert_eq!(reader.count(), 2);
+        let reports: Vec<Progress> = rx.iter().collect();
+        let records: Vec<u64> = reports.iter().map(|p| p.records).collect();
+        assert_eq!(records, vec![1, 2, 2]);
+        let last = reports.last().unwrap();
+        assert!(last.finished);
+        assert_eq!(last.bytes_read, text.len() as u64);
+    }
+
+    #[test]
+    fn progress_finishes_only_at_end_after_bad_entry() {
+        use crate::progress::{to_channel, Progress};
+        use std::sync::mpsc;
+
+        let (tx, rx) = mpsc::channel();
+        let text = ">a\nACGT\n>b c\rd\nGG\n>c\nTT\n";
+        let reader = FastaReader::new(text.as_bytes()).with_progress(to_channel(tx));
+        let results: Vec<bool> = reader.map(|r| r.is_ok()).collect();
+        assert_eq!(results, vec![true, false, true]);
+        let reports: Vec<Progress> = rx.iter().collect();
+        let finished: Vec<(u64, bool)> = reports.iter().map(|p| (p.records, p.finished)).collect();
+        assert_eq!(finished, vec![(1, false), (2, false), (2, true)]);
+        assert_eq!(reports.last().unwrap().bytes_read, text.len() as u64);
+    }
+
+    #[test]
+    fn display_omits_empty_description() {
+        let fasta = FastaSeq::builder()
+            .id("p1")
+            .sequence("MK")
+            .alphabet(Alphabet::Protein)
+            .build()
+            .unwrap();
+        assert_eq!(fasta.to_string(), ">p1\nMK\n");
+    }
+}
@@ -0,0 +1,193 @@
+//! Offset index over FASTA files for listing and random access without
+//! loading sequences into memory.
+
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{BufRead, BufReader, Read, Seek, SeekFrom};
+use std::path::Path;
+
+use crate::fasta::{parse_header, FastaError, FastaReader, FastaSeq};
+use crate::progress::{ProgressFn, ProgressTracker, DEFAULT_INTERVAL};
+
+/// Location and size of one entry of an indexed FASTA file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IndexEntry {
+    pub id: String,
+    /// Number of sequence symbols, excluding line breaks.
+    pub length: u64,
+    /// Byte offset of the entry's `>` header line.
+    pub offset: u64,
+}
+
+/// Entries of a FASTA file in file order.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FastaIndex {
+    entries: Vec<IndexEntry>,
+    by_id: HashMap<String, usize>,
+}
+
+impl FastaIndex {
+    /// Scans a FASTA stream, recording each entry's ID, length and offset.
+    ///
+    /// Only one line is held in memory at a time, so the scan runs in
+    /// constant memory regardless of sequence sizes.
+    pub fn scan<R: BufRead>(reader: R) -> Result<FastaIndex, FastaError> {
+        Self::scan_impl(reader, None)
+    }
+
+    /// Like [`scan`](Self::scan), calling `callback` with the bytes read and
+    /// entries found so far every [`DEFAULT_INTERVAL`] bytes, after each
+    /// header, and once at the end.
+    pub fn scan_with_progress<R: BufRead>(
+        reader: R,
+        callback: ProgressFn,
+    ) -> Result<FastaIndex, FastaError> {
+        Self::scan_impl(
+            reader,
+            Some(ProgressTracker::new(callback, DEFAULT_INTERVAL)),
+        )
+    }
+
+    fn scan_impl<R: BufRead>(
+        mut reader: R,
+        mut progress: Option<ProgressTracker>,
+    ) -> Result<FastaIndex, FastaError> {
+        let mut entries: Vec<IndexEntry> = Vec::new();
+        let mut line = Vec::new();
+        let mut offset = 0u64;
+        let mut line_no = 0;
+        loop {
+            line.clear();
+            let read = reader.read_until(b'\n', &mut line)?;
+            if let Some(progress) = progress.as_mut() {
+                progress.add_bytes(read);
+            }
+            if read == 0 {
+                break;
+            }
+            line_no += 1;
+            if line.first() == Some(&b'>') {
+                let header = String::from_utf8_lossy(&line[1..]);
+                let (id, _) = parse_header(&header);
+                if id.is_empty() {
+                    return Err(FastaError::MissingId);
+                }
+                entries.push(IndexEntry {
+                    id: id.to_string(),
+                    length: 0,
+                    offset,
+                });
+                if let Some(progress) = progress.as_mut() {
+                    progress.add_record();
+                }
+            } else {
+                let symbols = line.iter().filter(|b| !b.is_ascii_whitespace()).count() as u64;
+                match entries.last_mut() {
+                    Some(entry) => entry.length += symbols,
+                    None if symbols > 0 => return Err(FastaError::MissingHeader { line: line_no }),
+                    None => {}
+                }
+            }
+            offset += read as u64;
+        }
+        if let Some(progress) = progress.as_mut() {
+            progress.finish();
+        }
+        // The first entry wins when IDs repeat, matching a linear search.
+        let mut by_id = HashMap::with_capacity(entries.len());
+        for (i, entry) in entries.iter().enumerate() {
+            by_id.entry(entry.id.clone()).or_insert(i);
+        }
+        Ok(FastaIndex { entries, by_id })
+    }
+
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<FastaIndex, FastaError> {
+        Self::scan(BufReader::new(File::open(path)?))
+    }
+
+    pub fn entries(&self) -> &[IndexEntry] {
+        &self.entries
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Entry with the given identifier, if any.
+    pub fn get(&self, id: &str) -> Option<&IndexEntry> {
+        self.by_id.get(id).map(|&i| &self.entries[i])
+    }
+
+    /// Reads the entry named `id` from the indexed source, seeking straight
+    /// to its header. Returns `Ok(None)` for IDs not in the index.
+    pub fn fetch<R: Read + Seek>(
+        &self,
+        reader: &mut R,
+        id: &str,
+    ) -> Result<Option<FastaSeq>, FastaError> {
+        let Some(entry) = self.get(id) else {
+            return Ok(None);
+        };
+        reader.seek(SeekFrom::Start(entry.offset))?;
+        FastaReader::new(BufReader::new(reader)).next().transpose()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    const FASTA: &str = ">chr1 first\nACGTACGT\nACG\n>chr2\nGGGG\n\n>chr3 last\nTT\n";
+
+    #[test]
+    fn scan_records_lengths_and_offsets() {
+        let index = FastaIndex::scan(FASTA.as_bytes()).unwrap();
+        let summary: Vec<(&str, u64, u64)> = index
+            .entries()
+            .iter()
+            .map(|e| (e.id.as_str(), e.length, e.offset))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![("chr1", 11, 0), ("chr2", 4, 25), ("chr3", 2, 37)]
+        );
+    }
+
+    #[test]
+    fn fetches_single_entries() {
+        let index = FastaIndex::scan(FASTA.as_bytes()).unwrap();
+        let mut source = Cursor::new(FASTA.as_bytes());
+        let chr2 = index.fetch(&mut source, "chr2").unwrap().unwrap();
+        assert_eq!(chr2.sequence(), "GGGG");
+        let chr1 = index.fetch(&mut source, "chr1").unwrap().unwrap();
+        assert_eq!(chr1.description(), "first");
+        assert_eq!(chr1.len(), 11);
+        assert!(index.fetch(&mut source, "chrX").unwrap().is_none());
+    }
+
+    #[test]
+    fn scan_reports_progress() {
+        use crate::progress::to_channel;
+        use std::sync::mpsc;
+
+        let (tx, rx) = mpsc::channel();
+        let index = FastaIndex::scan_with_progress(FASTA.as_bytes(), to_channel(tx)).unwrap();
+        let last = rx.iter().last().unwrap();
+        assert_eq!(last.records, index.len() as u64);
+        assert_eq!(last.bytes_read, FASTA.len() as u64);
+        assert!(last.finished);
+    }
+
+    #[test]
+    fn scan_rejects_headerless_data() {
+        assert_eq!(
+            FastaIndex::scan("ACGT\n".as_bytes()),
+            Err(FastaError::MissingHeader { line: 1 })
+        );
+    }
+}
@@ -0,0 +1,584 @@
+//! FASTQ reads, a streaming reader and conversion to and from FASTA.
+
+use std::fmt;
+use std::io::{self, BufRead, Write};
+use std::ops::Range;
+
+use crate::fasta::{parse_header, FastaError, FastaReader, FastaSeq};
+use crate::progress::{ProgressFn, ProgressTracker, DEFAULT_INTERVAL};
+
+/// Highest Phred score a quality character can encode.
+pub const MAX_PHRED: u8 = 93;
+
+/// Offset of Sanger / Illumina 1.8+ quality characters: `!` is Phred 0.
+pub const PHRED_OFFSET: u8 = 33;
+
+/// A single FASTQ read. Qualities are kept as the Phred+33 characters of
+/// the file, one per base.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct FastqSeq {
+    id: String,
+    description: String,
+    sequence: String,
+    quality: String,
+}
+
+/// Errors raised while constructing or parsing FASTQ data.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FastqError {
+    /// The read has no identifier.
+    MissingId,
+    /// The identifier contains whitespace.
+    InvalidId(String),
+    /// The description contains a line break.
+    InvalidDescription(String),
+    /// The sequence contains whitespace or a non-printable character.
+    InvalidSequence { position: usize, symbol: char },
+    /// A quality character is outside `!`..=`~`.
+    InvalidQuality { position: usize, symbol: char },
+    /// Sequence and quality strings differ in length.
+    LengthMismatch {
+        id: String,
+        sequence: usize,
+        quality: usize,
+    },
+    /// A record does not start with `@` at the given 1-based line.
+    MissingHeader { line: usize },
+    /// The input ends inside a record, after the given 1-based line.
+    Truncated { line: usize },
+    /// The read could not be converted to a FASTA entry.
+    Fasta(FastaError),
+    /// Reading the underlying input failed.
+    Io {
+        kind: io::ErrorKind,
+        message: String,
+    },
+}
+
+impl From<FastaError> for FastqError {
+    fn from(err: FastaError) -> Self {
+        FastqError::Fasta(err)
+    }
+}
+
+impl From<io::Error> for FastqError {
+    fn from(err: io::Error) -> Self {
+        FastqError::Io {
+            kind: err.kind(),
+            message: err.to_string(),
+        }
+    }
+}
+
+impl fmt::Display for FastqError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FastqError::MissingId => write!(f, "read has no identifier"),
+            FastqError::InvalidId(id) => write!(f, "identifier {:?} contains whitespace", id),
+            FastqError::InvalidDescription(description) => {
+                write!(f, "description {:?} contains a line break", description)
+            }
+            FastqError::InvalidSequence { position, symbol } => write!(
+                f,
+                "invalid symbol {:?} at sequence position {}",
+                symbol, position
+            ),
+            FastqError::InvalidQuality { position, symbol } => write!(
+                f,
+                "invalid quality character {:?} at position {}",
+                symbol, position
+            ),
+            FastqError::LengthMismatch {
+                id,
+                sequence,
+                quality,
+            } => write!(
+                f,
+                "read {} has {} bases but {} quality values",
+                id, sequence, quality
+            ),
+            FastqError::MissingHeader { line } => {
+                write!(f, "expected an '@' header at line {}", line)
+            }
+            FastqError::Truncated { line } => {
+                write!(f, "input ends inside a record after line {}", line)
+            }
+            FastqError::Fasta(err) => err.fmt(f),
+            FastqError::Io { message, .. } => write!(f, "I/O error: {}", message),
+        }
+    }
+}
+
+impl std::error::Error for FastqError {}
+
+/// Record checks shared by [`FastqSeq::new`] and the reader.
+fn check_fields(
+    id: &str,
+    description: &str,
+    sequence: &str,
+    quality: &str,
+) -> Result<(), FastqError> {
+    if id.is_empty() {
+        return Err(FastqError::MissingId);
+    }
+    if id.chars().any(char::is_whitespace) {
+        return Err(FastqError::InvalidId(id.to_string()));
+    }
+    if description.contains(['\n', '\r']) {
+        return Err(FastqError::InvalidDescription(description.to_string()));
+    }
+    if let Some((position, symbol)) = sequence.char_indices().find(|(_, c)| !c.is_ascii_graphic()) {
+        return Err(FastqError::InvalidSequence { position, symbol });
+    }
+    if let Some((position, symbol)) = quality.char_indices().find(|(_, c)| !c.is_ascii_graphic()) {
+        return Err(FastqError::InvalidQuality { position, symbol });
+    }
+    if sequence.len() != quality.len() {
+        return Err(FastqError::LengthMismatch {
+            id: id.to_string(),
+            sequence: sequence.len(),
+            quality: quality.len(),
+        });
+    }
+    Ok(())
+}
+
+impl Default for FastqSeq {
+    /// An empty placeholder with no identifier, meant to be filled by
+    /// [`FastqReader::read_into`].
+    fn default() -> Self {
+        FastqSeq {
+            id: String::new(),
+            description: String::new(),
+            sequence: String::new(),
+            quality: String::new(),
+        }
+    }
+}
+
+impl FastqSeq {
+    /// Validates and assembles a read. `quality` holds Phred+33
+    /// characters and must be as long as `sequence`.
+    pub fn new(
+        id: &str,
+        description: &str,
+        sequence: &str,
+        quality: &str,
+    ) -> Result<FastqSeq, FastqError> {
+        check_fields(id, description, sequence, quality)?;
+        Ok(FastqSeq {
+            id: id.to_string(),
+            description: description.to_string(),
+            sequence: sequence.to_string(),
+            quality: quality.to_string(),
+        })
+    }
+
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    pub fn description(&self) -> &str {
+        &self.description
+    }
+
+    pub fn sequence(&self) -> &str {
+        &self.sequence
+    }
+
+    /// Quality string as Phred+33 characters.
+    pub fn quality(&self) -> &str {
+        &self.quality
+    }
+
+    /// Read from a FASTA sequence with every base given quality `phred`,
+    /// capped at [`MAX_PHRED`]. Useful for tools that insist on FASTQ.
+    pub fn from_fasta(seq: &FastaSeq, phred: u8) -> FastqSeq {
+        let symbol = (phred.min(MAX_PHRED) + PHRED_OFFSET) as char;
+        FastqSeq {
+            id: seq.id().to_string(),
+            description: seq.description().to_string(),
+            sequence: seq.sequence().to_string(),
+            quality: std::iter::repeat_n(symbol, seq.len()).collect(),
+        }
+    }
+
+    /// The read without its qualities, keeping ID and description. The
+    /// sequence type is inferred as for parsed FASTA. Fails for reads
+    /// without an identifier, such as the [`Default`] placeholder.
+    pub fn to_fasta(&self) -> Result<FastaSeq, FastaError> {
+        FastaSeq::builder()
+            .id(&self.id)
+            .description(&self.description)
+            .sequence(&self.sequence)
+            .build()
+    }
+
+    /// The read with its bases replaced by `sequence` and the header and
+    /// qualities kept, for edits that preserve validity such as masking.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `sequence` is not as long as the read.
+    pub(crate) fn with_sequence(&self, sequence: String) -> FastqSeq {
+        assert_eq!(
+            sequence.len(),
+            self.len(),
+            "replacement changes the read length"
+        );
+        FastqSeq {
+            id: self.id.clone(),
+            description: self.description.clone(),
+            sequence,
+            quality: self.quality.clone(),
+        }
+    }
+
+    /// Phred quality score of each base.
+    pub fn phred_scores(&self) -> impl Iterator<Item = u8> + '_ {
+        self.quality.bytes().map(|q| q - PHRED_OFFSET)
+    }
+
+    /// Mean Phred score, or `None` for an empty read.
+    pub fn mean_quality(&self) -> Option<f64> {
+        if self.is_empty() {
+            return None;
+        }
+        let total: u64 = self.phred_scores().map(u64::from).sum();
+        Some(total as f64 / self.len() as f64)
+    }
+
+    /// Expected number of base-calling errors, the sum of each base's
+    /// error probability `10^(-Q/10)`.
+    pub fn expected_errors(&self) -> f64 {
+        self.phred_scores()
+            .map(|q| 10f64.powf(-(q as f64) / 10.0))
+            .sum()
+    }
+
+    /// The bases and qualities in `range`, keeping the header.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `range` is out of bounds.
+    pub fn slice(&self, range: Range<usize>) -> FastqSeq {
+        FastqSeq {
+            id: self.id.clone(),
+            description: self.description.clone(),
+            sequence: self.sequence[range.clone()].to_string(),
+            quality: self.quality[range].to_string(),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.sequence.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.sequence.is_empty()
+    }
+}
+
+impl fmt::Display for FastqSeq {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.description.is_empty() {
+            writeln!(f, "@{}", self.id)?;
+        } else {
+            writeln!(f, "@{} {}", self.id, self.description)?;
+        }
+        writeln!(f, "{}", self.sequence)?;
+        writeln!(f, "+")?;
+        writeln!(f, "{}", self.quality)
+    }
+}
+
+/// Streaming FASTQ parser yielding one [`FastqSeq`] at a time.
+///
+/// Sequence and quality may wrap over several lines; the quality part ends
+/// once it is as long as the sequence, so qualities starting with `@` are
+/// read correctly. Blank lines between records are skipped.
+pub struct FastqReader<R> {
+    reader: R,
+    line: Vec<u8>,
+    line_no: usize,
+    done: bool,
+    progress: Option<ProgressTracker>,
+}
+
+impl<R: BufRead> FastqReader<R> {
+    pub fn new(reader: R) -> Self {
+        FastqReader {
+            reader,
+            line: Vec::new(),
+            line_no: 0,
+            done: false,
+            progress: None,
+        }
+    }
+
+    /// Calls `callback` after every parsed read, every
+    /// [`DEFAULT_INTERVAL`] bytes within long reads, and once at the end.
+    pub fn with_progress(self, callback: ProgressFn) -> Self {
+        self.with_progress_interval(callback, DEFAULT_INTERVAL)
+    }
+
+    /// Like [`with_progress`](Self::with_progress) with a custom byte interval.
+    pub fn with_progress_interval(mut self, callback: ProgressFn, interval: u64) -> Self {
+        self.progress = Some(ProgressTracker::new(callback, interval));
+        self
+    }
+
+    /// Reads the next line into `self.line` without its line break;
+    /// `false` at end of input.
+    fn next_line(&mut self) -> Result<bool, FastqError> {
+        self.line.clear();
+        let read = self.reader.read_until(b'\n', &mut self.line)?;
+        if let Some(progress) = self.progress.as_mut() {
+            progress.add_bytes(read);
+        }
+        if read == 0 {
+            return Ok(false);
+        }
+        self.line_no += 1;
+        while matches!(self.line.last(), Some(b'\n' | b'\r')) {
+            self.line.pop();
+        }
+        Ok(true)
+    }
+
+    /// Parses the next read into `read`, reusing its string buffers, and
+    /// returns `Ok(false)` at the end of input. Reading a whole file into
+    /// one `FastqSeq` this way avoids allocating per read once the buffers
+    /// have grown to the longest read.
+    ///
+    /// On error `read` holds partial data and the reader stops.
+    pub fn read_into(&mut self, read: &mut FastqSeq) -> Result<bool, FastqError> {
+        if self.done {
+            return Ok(false);
+        }
+        let entry = self.read_raw_into(read);
+        if entry.is_err() {
+            self.done = true;
+        }
+        if let Some(progress) = self.progress.as_mut() {
+            match &entry {
+                Ok(true) => progress.add_record(),
+                _ => progress.finish(),
+            }
+        }
+        entry
+    }
+
+    fn read_raw_into(&mut self, read: &mut FastqSeq) -> Result<bool, FastqError> {
+        loop {
+            if !self.next_line()? {
+                self.done = true;
+                return Ok(false);
+            }
+            if !self.line.iter().all(u8::is_ascii_whitespace) {
+                break;
+            }
+        }
+        let Some(header) = self.line.strip_prefix(b"@") else {
+            return Err(FastqError::MissingHeader { line: self.line_no });
+        };
+        let header = String::from_utf8_lossy(header);
+        let (id, description) = parse_header(header.trim_end());
+        read.id.clear();
+        read.id.push_str(id);
+        read.description.clear();
+        read.description.push_str(description);
+
+        read.sequence.clear();
+        loop {
+            if !self.next_line()? {
+                return Err(FastqError::Truncated { line: self.line_no });
+            }
+            if self.line.starts_with(b"+") {
+                break;
+            }
+            read.sequence
+                .push_str(String::from_utf8_lossy(&self.line).trim());
+        }
+
+        read.quality.clear();
+        while read.quality.len() < read.sequence.len() {
+            if !self.next_line()? {
+                return Err(FastqError::Truncated { line: self.line_no });
+            }
+            read.quality
+                .push_str(String::from_utf8_lossy(&self.line).trim());
+        }
+
+        check_fields(&read.id, &read.description, &read.sequence, &read.quality)?;
+        Ok(true)
+    }
+}
+
+impl<R: BufRead> Iterator for FastqReader<R> {
+    type Item = Result<FastqSeq, FastqError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut read = FastqSeq::default();
+        match self.read_into(&mut read) {
+            Ok(true) => Some(Ok(read)),
+            Ok(false) => None,
+            Err(err) => Some(Err(err)),
+        }
+    }
+}
+
+/// Streams FASTQ reads to `out` as FASTA and returns the number written.
+pub fn fastq_to_fasta<R: BufRead, W: Write>(
+    reader: FastqReader<R>,
+    out: &mut W,
+) -> Result<u64, FastqError> {
+    let mut written = 0;
+    for read in reader {
+        write!(out, "{}", read?.to_fasta()?)?;
+        written += 1;
+    }
+    Ok(written)
+}
+
+/// Streams FASTA entries to `out` as FASTQ with constant quality `phred`
+/// and returns the number written.
+pub fn fasta_to_fastq<R: BufRead, W: Write>(
+    reader: FastaReader<R>,
+    phred: u8,
+    out: &mut W,
+) -> Result<u64, FastaError> {
+    let mut written = 0;
+    for seq in reader {
+        write!(out, "{}", FastqSeq::from_fasta(&seq?, phred))?;
+        written += 1;
+    }
+    Ok(written)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn read_all(text: &str) -> Result<Vec<FastqSeq>, FastqError> {
+        FastqReader::new(text.as_bytes()).collect()
+    }
+
+    #[test]
+    fn reads_records() {
+        let reads = read_all("@r1 sample=A\nACGT\n+\nII#I\n\n@r2\nGG\n+r2\n@@\n").unwrap();
+        assert_eq!(reads.len(), 2);
+        assert_eq!(reads[0].id(), "r1");
+        assert_eq!(reads[0].description(), "sample=A");
+        assert_eq!(
+            reads[0].phred_scores().collect::<Vec<_>>(),
+            vec![40, 40, 2, 40]
+        );
+        assert_eq!(reads[1].quality(), "@@");
+        assert_eq!(reads[1].mean_quality(), Some(31.0));
+        assert!((reads[0].expected_errors() - 0.6312).abs() < 1e-4);
+        assert_eq!(reads[0].to_string(), "@r1 sample=A\nACGT\n+\nII#I\n");
+    }
+
+    #[test]
+    fn slices_keep_qualities_aligned() {
+        let read = FastqSeq::new("r1", "x", "ACGTA", "ABCDE").unwrap();
+        let tail = read.slice(2..5);
+        assert_eq!((tail.sequence(), tail.quality()), ("GTA", "CDE"));
+        assert_eq!(tail.description(), "x");
+    }
+
+    #[test]
+    fn read_into_reuses_the_record() {
+        let mut reader =
+            FastqReader::new("@r1 a\nACGTACGT\n+\nIIIIIIII\n@r2\nGG\n+\n##\n".as_bytes());
+        let mut read = FastqSeq::default();
+        assert_eq!(reader.read_into(&mut read), Ok(true));
+        let capacity = read.sequence.capacity();
+        assert_eq!(reader.read_into(&mut read), Ok(true));
+        assert_eq!(read, FastqSeq::new("r2", "", "GG", "##").unwrap());
+        assert_eq!(read.sequence.capacity(), capacity);
+        assert_eq!(reader.read_into(&mut read), Ok(false));
+    }
+
+    #[test]
+    fn reads_wrapped_records() {
+        let reads = read_all("@r1\r\nACG\r\nTA\r\n+\r\nII\r\nIII\r\n").unwrap();
+        assert_eq!(reads[0].sequence(), "ACGTA");
+        assert_eq!(reads[0].quality(), "IIIII");
+    }
+
+    #[test]
+    fn reports_malformed_records() {
+        assert_eq!(
+            read_all("ACGT\n"),
+            Err(FastqError::MissingHeader { line: 1 })
+        );
+        assert_eq!(
+            read_all("@r1\nACGT\n+\nII"),
+            Err(FastqError::Truncated { line: 4 })
+        );
+        assert_eq!(
+            read_all("@r1\nACGT\n+\nIIIII\n"),
+            Err(FastqError::LengthMismatch {
+                id: "r1".to_string(),
+                sequence: 4,
+                quality: 5
+            })
+        );
+        assert_eq!(read_all("@\nA\n+\nI\n"), Err(FastqError::MissingId));
+        assert_eq!(
+            FastqSeq::new("r1", "a\nb", "ACGT", "IIII"),
+            Err(FastqError::InvalidDescription("a\nb".to_string()))
+        );
+        assert_eq!(
+            read_all("@r1 a\rb\nACGT\n+\nIIII\n"),
+            Err(FastqError::InvalidDescription("a\rb".to_string()))
+        );
+        let mut reader = FastqReader::new("@r1\nAC\n".as_bytes());
+        assert!(reader.next().unwrap().is_err());
+        assert!(reader.next().is_none());
+    }
+
+    #[test]
+    fn converts_between_fastq_and_fasta() {
+        let input = "@r1 lane=2\nACGTACGTAC\n+\nIIIIIIIIII\n@r2\nGG\n+\n##\n";
+        let mut fasta = Vec::new();
+        assert_eq!(
+            fastq_to_fasta(FastqReader::new(input.as_bytes()), &mut fasta),
+            Ok(2)
+        );
+        let fasta = String::from_utf8(fasta).unwrap();
+        assert_eq!(fasta, ">r1 lane=2\nACGTACGTAC\n>r2\nGG\n");
+
+        let mut fastq = Vec::new();
+        assert_eq!(
+            fasta_to_fastq(FastaReader::new(fasta.as_bytes()), 30, &mut fastq),
+            Ok(2)
+        );
+        assert_eq!(
+            String::from_utf8(fastq).unwrap(),
+            "@r1 lane=2\nACGTACGTAC\n+\n??????????\n@r2\nGG\n+\n??\n"
+        );
+
+        // A header FASTA would reject stops the stream instead of panicking.
+        let input = "@r1 a\rb\nACGT\n+\nIIII\n";
+        assert_eq!(
+            fastq_to_fasta(FastqReader::new(input.as_bytes()), &mut Vec::new()),
+            Err(FastqError::InvalidDescription("a\rb".to_string()))
+        );
+    }
+
+    #[test]
+    fn placeholder_read_has_no_fasta_form() {
+        assert_eq!(FastqSeq::default().to_fasta(), Err(FastaError::MissingId));
+        let read = FastqSeq::new("r1", "x", "ACGT", "IIII").unwrap();
+        assert_eq!(read.to_fasta().unwrap().to_string(), ">r1 x\nACGT\n");
+    }
+
+    #[test]
+    fn placeholder_quality_is_capped() {
+        let seq = FastaSeq::builder().id("s").sequence("AC").build().unwrap();
+        assert_eq!(FastqSeq::from_fasta(&seq, 200).quality(), "~~");
+    }
+}
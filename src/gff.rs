@@ -0,0 +1,324 @@
+//! GFF3 feature lines: parsing and writing.
+
+use std::fmt;
+use std::io::{self, BufRead, Write};
+
+use crate::region::{CoordError, CoordFormat, Region};
+use crate::seq::Strand;
+
+pub mod model;
+
+/// A single GFF3 feature. Coordinates are 1-based and inclusive, as in the
+/// file format itself.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GffFeature {
+    pub seqid: String,
+    pub source: String,
+    pub feature_type: String,
+    pub start: usize,
+    pub end: usize,
+    pub score: Option<f64>,
+    pub strand: Option<Strand>,
+    pub phase: Option<u8>,
+    pub attributes: Vec<(String, String)>,
+}
+
+impl GffFeature {
+    /// Value of the first attribute named `key`.
+    pub fn attribute(&self, key: &str) -> Option<&str> {
+        self.attributes
+            .iter()
+            .find(|(k, _)| k == key)
+            .map(|(_, v)| v.as_str())
+    }
+
+    /// The feature's span as a 0-based, half-open region, stranded if the
+    /// feature is.
+    pub fn region(&self) -> Result<Region, CoordError> {
+        let mut region = Region::from_format(CoordFormat::Gff, &self.seqid, self.start, self.end)?;
+        region.strand = self.strand;
+        Ok(region)
+    }
+}
+
+/// Errors raised while parsing GFF3.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GffError {
+    /// A feature line without nine tab-separated columns, at the given
+    /// 1-based line.
+    ColumnCount { line: usize, found: usize },
+    /// A column value that cannot be parsed.
+    InvalidField {
+        line: usize,
+        column: &'static str,
+        value: String,
+    },
+    /// Reading the underlying input failed.
+    Io {
+        kind: io::ErrorKind,
+        message: String,
+    },
+}
+
+impl From<io::Error> for GffError {
+    fn from(err: io::Error) -> Self {
+        GffError::Io {
+            kind: err.kind(),
+            message: err.to_string(),
+        }
+    }
+}
+
+impl fmt::Display for GffError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GffError::ColumnCount { line, found } => {
+                write!(f, "line {} has {} columns instead of 9", line, found)
+            }
+            GffError::InvalidField {
+                line,
+                column,
+                value,
+            } => write!(f, "invalid {} {:?} at line {}", column, value, line),
+            GffError::Io { message, .. } => write!(f, "I/O error: {}", message),
+        }
+    }
+}
+
+impl std::error::Error for GffError {}
+
+/// Decodes `%XX` escapes; malformed escapes are kept as they are.
+fn unescape(value: &str) -> String {
+    if !value.contains('%') {
+        return value.to_string();
+    }
+    let bytes = value.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let decoded = (bytes[i] == b'%')
+            .then(|| value.get(i + 1..i + 3))
+            .flatten()
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        match decoded {
+            Some(byte) => {
+                out.push(byte);
+                i += 3;
+            }
+            None => {
+                out.push(bytes[i]);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&out).into_owned()
+}
+
+fn parse_feature(text: &str, line: usize) -> Result<GffFeature, GffError> {
+    let columns: Vec<&str> = text.split('\t').collect();
+    if columns.len() != 9 {
+        return Err(GffError::ColumnCount {
+            line,
+            found: columns.len(),
+        });
+    }
+    let invalid = |column: &'static str, value: &str| GffError::InvalidField {
+        line,
+        column,
+        value: value.to_string(),
+    };
+    let position = |column: &'static str, value: &str| {
+        value
+            .parse::<usize>()
+            .ok()
+            .filter(|&p| p > 0)
+            .ok_or_else(|| invalid(column, value))
+    };
+    let start = position("start", columns[3])?;
+    let end = position("end", columns[4])?;
+    if end < start {
+        return Err(invalid("end", columns[4]));
+    }
+    let score = match columns[5] {
+        "." => None,
+        value => Some(value.parse().map_err(|_| invalid("score", value))?),
+    };
+    let strand = match columns[6] {
+        "+" => Some(Strand::Forward),
+        "-" => Some(Strand::Reverse),
+        "." | "?" => None,
+        value => return Err(invalid("strand", value)),
+    };
+    let phase = match columns[7] {
+        "." => None,
+        value => Some(
+            value
+                .parse::<u8>()
+                .ok()
+                .filter(|&p| p < 3)
+                .ok_or_else(|| invalid("phase", value))?,
+        ),
+    };
+    let mut attributes = Vec::new();
+    if columns[8] != "." {
+        for pair in columns[8]
+            .split(';')
+            .map(str::trim)
+            .filter(|p| !p.is_empty())
+        {
+            let (key, value) = pair
+                .split_once('=')
+                .ok_or_else(|| invalid("attribute", pair))?;
+            attributes.push((unescape(key), unescape(value)));
+        }
+    }
+    Ok(GffFeature {
+        seqid: unescape(columns[0]),
+        source: unescape(columns[1]),
+        feature_type: unescape(columns[2]),
+        start,
+        end,
+        score,
+        strand,
+        phase,
+        attributes,
+    })
+}
+
+/// Parses every feature line of a GFF3 file. Comments, directives and
+/// blank lines are skipped, and parsing stops at a `##FASTA` section.
+pub fn read_gff3<R: BufRead>(reader: R) -> Result<Vec<GffFeature>, GffError> {
+    let mut features = Vec::new();
+    for (index, line) in reader.lines().enumerate() {
+        let line = line?;
+        let text = line.trim_end_matches('\r');
+        if text.starts_with("##FASTA") {
+            break;
+        }
+        if text.trim().is_empty() || text.starts_with('#') {
+            continue;
+        }
+        features.push(parse_feature(text, index + 1)?);
+    }
+    Ok(features)
+}
+
+/// Percent-encodes the characters GFF3 reserves in columns and attributes.
+fn escape(value: &str) -> String {
+    let mut out = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '\t' | '\n' | '\r' | '%' | ';' | '=' | '&' | ',' => {
+                out.push_str(&format!("%{:02X}", c as u32));
+            }
+            c if c.is_control() => out.push_str(&format!("%{:02X}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+fn or_dot<T: fmt::Display>(value: Option<T>) -> String {
+    value.map_or_else(|| ".".to_string(), |v| v.to_string())
+}
+
+impl fmt::Display for GffFeature {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let attributes = if self.attributes.is_empty() {
+            ".".to_string()
+        } else {
+            self.attributes
+                .iter()
+                .map(|(k, v)| format!("{}={}", escape(k), escape(v)))
+                .collect::<Vec<_>>()
+                .join(";")
+        };
+        write!(
+            f,
+            "{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}\t{}",
+            escape(&self.seqid),
+            escape(&self.source),
+            escape(&self.feature_type),
+            self.start,
+            self.end,
+            or_dot(self.score),
+            or_dot(self.strand),
+            or_dot(self.phase),
+            attributes
+        )
+    }
+}
+
+/// Writes a GFF3 header followed by one line per feature.
+pub fn write_gff3<'a, W, I>(writer: &mut W, features: I) -> io::Result<()>
+where
+    W: Write,
+    I: IntoIterator<Item = &'a GffFeature>,
+{
+    writeln!(writer, "##gff-version 3")?;
+    for feature in features {
+        writeln!(writer, "{}", feature)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn formats_feature_line_with_escaping() {
+        let feature = GffFeature {
+            seqid: "chr1".to_string(),
+            source: "bio_oxide".to_string(),
+            feature_type: "CDS".to_string(),
+            start: 1,
+            end: 9,
+            score: None,
+            strand: Some(Strand::Reverse),
+            phase: Some(0),
+            attributes: vec![("ID".to_string(), "a;b=c".to_string())],
+        };
+        assert_eq!(
+            feature.to_string(),
+            "chr1\tbio_oxide\tCDS\t1\t9\t.\t-\t0\tID=a%3Bb%3Dc"
+        );
+    }
+
+    #[test]
+    fn parses_feature_lines() {
+        let text = "##gff-version 3\n# comment\n\nchr1\tsrc\tCDS\t10\t20\t0.5\t-\t2\tID=a%3Bb;Parent=t1,t2\nchr1\t.\tgene\t1\t30\t.\t.\t.\t.\n##FASTA\n>chr1\n";
+        let features = read_gff3(text.as_bytes()).unwrap();
+        assert_eq!(features.len(), 2);
+        let cds = &features[0];
+        assert_eq!((cds.start, cds.end, cds.score), (10, 20, Some(0.5)));
+        assert_eq!((cds.strand, cds.phase), (Some(Strand::Reverse), Some(2)));
+        assert_eq!(cds.attribute("ID"), Some("a;b"));
+        assert_eq!(cds.attribute("Parent"), Some("t1,t2"));
+        assert!(features[1].attributes.is_empty());
+        let region = cds.region().unwrap();
+        assert_eq!((region.start(), region.end()), (9, 20));
+        assert_eq!(region.strand, Some(Strand::Reverse));
+        assert_eq!(
+            read_gff3(cds.to_string().as_bytes()).unwrap()[0],
+            cds.clone()
+        );
+    }
+
+    #[test]
+    fn reports_malformed_lines() {
+        assert_eq!(
+            read_gff3("chr1\tsrc\tgene\t1\n".as_bytes()),
+            Err(GffError::ColumnCount { line: 1, found: 4 })
+        );
+        assert_eq!(
+            read_gff3("#x\nchr1\tsrc\tgene\t5\t2\t.\t+\t.\t.\n".as_bytes()),
+            Err(GffError::InvalidField {
+                line: 2,
+                column: "end",
+                value: "2".to_string()
+            })
+        );
+        assert!(read_gff3("chr1\tsrc\tgene\t1\t2\t.\t*\t.\t.\n".as_bytes()).is_err());
+    }
+}
@@ -0,0 +1,403 @@
+//! Gene models resolved from GFF3 `ID`/`Parent` attributes: genes,
+//! their transcripts, and each transcript's exons and CDS parts.
+
+use std::collections::HashMap;
+use std::fmt;
+
+use crate::fasta::{FastaError, FastaRecord, FastaSeq};
+use crate::gff::GffFeature;
+use crate::seq::{reverse_complement, Strand};
+
+/// Feature types treated as genes.
+const GENE_TYPES: &[&str] = &["gene", "pseudogene", "ncRNA_gene"];
+
+/// Feature types attached to transcripts as parts.
+const PART_TYPES: &[&str] = &["exon", "CDS"];
+
+/// Errors raised while building or using gene models.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ModelError {
+    /// A `Parent` attribute names an ID no feature has.
+    UnknownParent { feature: String, parent: String },
+    /// Two genes, or two transcripts of one gene, share an ID.
+    DuplicateId(String),
+    /// The genome has no sequence with this ID.
+    MissingSequence(String),
+    /// A feature extends past the end of its sequence.
+    OutOfBounds {
+        seqid: String,
+        end: usize,
+        len: usize,
+    },
+    /// A transcript has neither exons nor CDS parts to splice.
+    NoParts(String),
+    /// The spliced sequence could not be built.
+    Fasta(FastaError),
+}
+
+impl From<FastaError> for ModelError {
+    fn from(err: FastaError) -> Self {
+        ModelError::Fasta(err)
+    }
+}
+
+impl fmt::Display for ModelError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ModelError::UnknownParent { feature, parent } => {
+                write!(f, "feature {} has unknown parent {}", feature, parent)
+            }
+            ModelError::DuplicateId(id) => write!(f, "ID {} is used by several features", id),
+            ModelError::MissingSequence(seqid) => write!(f, "genome has no sequence {}", seqid),
+            ModelError::OutOfBounds { seqid, end, len } => write!(
+                f,
+                "feature ends at {} beyond {} ({} bases)",
+                end, seqid, len
+            ),
+            ModelError::NoParts(id) => write!(f, "transcript {} has no exons or CDS", id),
+            ModelError::Fasta(err) => err.fmt(f),
+        }
+    }
+}
+
+impl std::error::Error for ModelError {}
+
+/// A transcript (mRNA, ncRNA, ...) with its parts sorted by position.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Transcript {
+    pub id: String,
+    pub feature: GffFeature,
+    pub exons: Vec<GffFeature>,
+    pub cds: Vec<GffFeature>,
+}
+
+/// A gene and its transcripts, in file order.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Gene {
+    pub id: String,
+    pub feature: GffFeature,
+    pub transcripts: Vec<Transcript>,
+}
+
+/// The gene models of an annotation.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct GeneModels {
+    pub genes: Vec<Gene>,
+}
+
+fn parents(feature: &GffFeature) -> impl Iterator<Item = &str> {
+    feature
+        .attribute("Parent")
+        .into_iter()
+        .flat_map(|p| p.split(','))
+        .map(str::trim)
+        .filter(|p| !p.is_empty())
+}
+
+fn label(feature: &GffFeature) -> String {
+    feature.attribute("ID").map_or_else(
+        || format!("{}:{}-{}", feature.feature_type, feature.start, feature.end),
+        str::to_string,
+    )
+}
+
+impl GeneModels {
+    /// Resolves the hierarchy. Genes are `gene`, `pseudogene` and
+    /// `ncRNA_gene` features; transcripts are features other than `exon`
+    /// and `CDS` whose parent is a gene; `exon` and `CDS` features attach
+    /// to their parent transcripts. Parts whose parent is a gene, as in
+    /// prokaryotic `gene` -> `CDS` annotations, attach to an implicit
+    /// transcript sharing the gene's ID and feature. Features with several
+    /// parents are attached to each. Other features, such as UTRs, are
+    /// ignored, but every `Parent` must name a known ID.
+    pub fn from_features(features: &[GffFeature]) -> Result<Self, ModelError> {
+        let ids: HashMap<&str, &GffFeature> = features
+            .iter()
+            .filter_map(|f| f.attribute("ID").map(|id| (id, f)))
+            .collect();
+        for feature in features {
+            for parent in parents(feature) {
+                if !ids.contains_key(parent) {
+                    return Err(ModelError::UnknownParent {
+                        feature: label(feature),
+                        parent: parent.to_string(),
+                    });
+                }
+            }
+        }
+
+        let mut genes = Vec::new();
+        let mut gene_index = HashMap::new();
+        for feature in features {
+            if !GENE_TYPES.contains(&feature.feature_type.as_str()) {
+                continue;
+            }
+            let Some(id) = feature.attribute("ID") else {
+                continue;
+            };
+            if gene_index.insert(id, genes.len()).is_some() {
+                return Err(ModelError::DuplicateId(id.to_string()));
+            }
+            genes.push(Gene {
+                id: id.to_string(),
+                feature: feature.clone(),
+                transcripts: Vec::new(),
+            });
+        }
+
+        // (gene, transcript) positions of each transcript ID.
+        let mut transcript_index: HashMap<&str, Vec<(usize, usize)>> = HashMap::new();
+        for feature in features {
+            if PART_TYPES.contains(&feature.feature_type.as_str()) {
+                continue;
+            }
+            let Some(id) = feature.attribute("ID") else {
+                continue;
+            };
+            for parent in parents(feature) {
+                let Some(&g) = gene_index.get(parent) else {
+                    continue;
+                };
+                let slots = transcript_index.entry(id).or_default();
+                if slots.iter().any(|&(other, _)| other == g) {
+                    return Err(ModelError::DuplicateId(id.to_string()));
+                }
+                slots.push((g, genes[g].transcripts.len()));
+                genes[g].transcripts.push(Transcript {
+                    id: id.to_string(),
+                    feature: feature.clone(),
+                    exons: Vec::new(),
+                    cds: Vec::new(),
+                });
+            }
+        }
+
+        // Transcript position of each gene's implicit transcript.
+        let mut implicit: HashMap<usize, usize> = HashMap::new();
+        for feature in features {
+            if !PART_TYPES.contains(&feature.feature_type.as_str()) {
+                continue;
+            }
+            let is_exon = feature.feature_type == "exon";
+            for parent in parents(feature) {
+                let mut slots = transcript_index.get(parent).cloned().unwrap_or_default();
+                if let Some(&g) = gene_index.get(parent) {
+                    let t = *implicit.entry(g).or_insert_with(|| {
+                        let gene = &mut genes[g];
+                        gene.transcripts.push(Transcript {
+                            id: gene.id.clone(),
+                            feature: gene.feature.clone(),
+                            exons: Vec::new(),
+                            cds: Vec::new(),
+                        });
+                        gene.transcripts.len() - 1
+                    });
+                    slots.push((g, t));
+                }
+                for (g, t) in slots {
+                    let transcript = &mut genes[g].transcripts[t];
+                    let parts = if is_exon {
+                        &mut transcript.exons
+                    } else {
+                        &mut transcript.cds
+                    };
+                    parts.push(feature.clone());
+                }
+            }
+        }
+        for transcript in genes.iter_mut().flat_map(|g| g.transcripts.iter_mut()) {
+            transcript.exons.sort_by_key(|f| (f.start, f.end));
+            transcript.cds.sort_by_key(|f| (f.start, f.end));
+        }
+        Ok(GeneModels { genes })
+    }
+
+    pub fn gene(&self, id: &str) -> Option<&Gene> {
+        self.genes.iter().find(|g| g.id == id)
+    }
+
+    /// Every transcript of every gene.
+    pub fn transcripts(&self) -> impl Iterator<Item = &Transcript> {
+        self.genes.iter().flat_map(|g| g.transcripts.iter())
+    }
+}
+
+impl Transcript {
+    /// Concatenation of `parts` from the genome, reverse-complemented for
+    /// minus-strand transcripts.
+    fn splice(&self, parts: &[GffFeature], genome: &FastaRecord) -> Result<Vec<u8>, ModelError> {
+        let seqid = &self.feature.seqid;
+        let source = genome
+            .get(seqid)
+            .ok_or_else(|| ModelError::MissingSequence(seqid.clone()))?
+            .sequence()
+            .as_bytes();
+        let mut spliced = Vec::new();
+        for part in parts {
+            if part.end > source.len() {
+                return Err(ModelError::OutOfBounds {
+                    seqid: seqid.clone(),
+                    end: part.end,
+                    len: source.len(),
+                });
+            }
+            spliced.extend_from_slice(&source[part.start - 1..part.end]);
+        }
+        if self.feature.strand == Some(Strand::Reverse) {
+            spliced = reverse_complement(&spliced);
+        }
+        Ok(spliced)
+    }
+
+    fn to_fasta(&self, sequence: &[u8]) -> Result<FastaSeq, ModelError> {
+        Ok(FastaSeq::builder()
+            .id(&self.id)
+            .sequence(&String::from_utf8_lossy(sequence))
+            .build()?)
+    }
+
+    /// Spliced transcript: the exons joined in transcription order. A
+    /// transcript without exon features is spliced from its CDS parts.
+    pub fn spliced_sequence(&self, genome: &FastaRecord) -> Result<FastaSeq, ModelError> {
+        let parts = if self.exons.is_empty() {
+            &self.cds
+        } else {
+            &self.exons
+        };
+        if parts.is_empty() {
+            return Err(ModelError::NoParts(self.id.clone()));
+        }
+        self.to_fasta(&self.splice(parts, genome)?)
+    }
+
+    /// Coding sequence: the CDS parts joined in transcription order, with
+    /// the 5'-most part's phase trimmed so the result starts on a codon.
+    pub fn cds_sequence(&self, genome: &FastaRecord) -> Result<FastaSeq, ModelError> {
+        if self.cds.is_empty() {
+            return Err(ModelError::NoParts(self.id.clone()));
+        }
+        let first = if self.feature.strand == Some(Strand::Reverse) {
+            self.cds.last()
+        } else {
+            self.cds.first()
+        };
+        let phase = first.and_then(|f| f.phase).unwrap_or(0) as usize;
+        let cds = self.splice(&self.cds, genome)?;
+        self.to_fasta(&cds[phase.min(cds.len())..])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gff::read_gff3;
+
+    // Exons at 3-10 and 21-30, CDS at 6-10 and 21-27.
+    const GENOME: &str = ">chr1\nAAGGGATGAAGCCCCCCCCCTTCCGTAATGTTTTTTTTTT\n";
+
+    fn gff(strand: char, first_phase: char, last_phase: char) -> String {
+        [
+            format!("chr1\t.\tgene\t3\t30\t.\t{}\t.\tID=g1", strand),
+            format!("chr1\t.\tmRNA\t3\t30\t.\t{}\t.\tID=t1;Parent=g1", strand),
+            format!("chr1\t.\texon\t21\t30\t.\t{}\t.\tParent=t1", strand),
+            format!("chr1\t.\texon\t3\t10\t.\t{}\t.\tParent=t1", strand),
+            format!(
+                "chr1\t.\tCDS\t6\t10\t.\t{}\t{}\tParent=t1",
+                strand, first_phase
+            ),
+            format!(
+                "chr1\t.\tCDS\t21\t27\t.\t{}\t{}\tParent=t1",
+                strand, last_phase
+            ),
+            format!("chr1\t.\tfive_prime_UTR\t3\t5\t.\t{}\t.\tParent=t1", strand),
+        ]
+        .join("\n")
+    }
+
+    fn models(text: &str) -> Result<GeneModels, ModelError> {
+        GeneModels::from_features(&read_gff3(text.as_bytes()).unwrap())
+    }
+
+    #[test]
+    fn resolves_hierarchy() {
+        let models = models(&gff('+', '0', '2')).unwrap();
+        let gene = models.gene("g1").unwrap();
+        assert_eq!(gene.transcripts.len(), 1);
+        let t = &gene.transcripts[0];
+        assert_eq!(t.id, "t1");
+        let exons: Vec<(usize, usize)> = t.exons.iter().map(|e| (e.start, e.end)).collect();
+        assert_eq!(exons, vec![(3, 10), (21, 30)]);
+        assert_eq!(t.cds.len(), 2);
+    }
+
+    #[test]
+    fn extracts_spliced_and_coding_sequences() {
+        let genome = FastaRecord::from_string(GENOME).unwrap();
+        let models = models(&gff('+', '0', '2')).unwrap();
+        let t = models.transcripts().next().unwrap();
+        assert_eq!(
+            t.spliced_sequence(&genome).unwrap().sequence(),
+            "GGGATGAATTCCGTAATG"
+        );
+        assert_eq!(t.cds_sequence(&genome).unwrap().sequence(), "ATGAATTCCGTA");
+    }
+
+    #[test]
+    fn minus_strand_is_reverse_complemented_and_phased() {
+        let genome = FastaRecord::from_string(GENOME).unwrap();
+        let models = models(&gff('-', '0', '1')).unwrap();
+        let t = models.transcripts().next().unwrap();
+        assert_eq!(
+            t.spliced_sequence(&genome).unwrap().sequence(),
+            "CATTACGGAATTCATCCC"
+        );
+        // The 3'-most genomic CDS comes first in transcript order.
+        assert_eq!(t.cds_sequence(&genome).unwrap().sequence(), "ACGGAATTCAT");
+    }
+
+    #[test]
+    fn attaches_gene_parented_cds_to_an_implicit_transcript() {
+        // NCBI prokaryotic layout: the CDS hangs directly off the gene and
+        // repeats its ID on each segment.
+        let text = "\
+chr1\t.\tgene\t6\t27\t.\t+\t.\tID=gene-a
+chr1\t.\tCDS\t6\t10\t.\t+\t0\tID=cds-a;Parent=gene-a
+chr1\t.\tCDS\t21\t27\t.\t+\t2\tID=cds-a;Parent=gene-a
+";
+        let genome = FastaRecord::from_string(GENOME).unwrap();
+        let models = models(text).unwrap();
+        let gene = models.gene("gene-a").unwrap();
+        assert_eq!(gene.transcripts.len(), 1);
+        let t = &gene.transcripts[0];
+        assert_eq!((t.id.as_str(), t.cds.len()), ("gene-a", 2));
+        assert_eq!(t.cds_sequence(&genome).unwrap().sequence(), "ATGAATTCCGTA");
+        assert_eq!(
+            t.spliced_sequence(&genome).unwrap().sequence(),
+            "ATGAATTCCGTA"
+        );
+    }
+
+    #[test]
+    fn reports_broken_models() {
+        let dangling = gff('+', '0', '2').replace("Parent=g1", "Parent=g2");
+        assert_eq!(
+            models(&dangling),
+            Err(ModelError::UnknownParent {
+                feature: "t1".to_string(),
+                parent: "g2".to_string()
+            })
+        );
+        let models = models(&gff('+', '0', '2')).unwrap();
+        let t = models.transcripts().next().unwrap();
+        let other = FastaRecord::from_string(">chr2\nACGT\n").unwrap();
+        assert_eq!(
+            t.spliced_sequence(&other),
+            Err(ModelError::MissingSequence("chr1".to_string()))
+        );
+        let short = FastaRecord::from_string(">chr1\nACGT\n").unwrap();
+        assert!(matches!(
+            t.cds_sequence(&short),
+            Err(ModelError::OutOfBounds { len: 4, .. })
+        ));
+    }
+}
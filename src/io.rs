@@ -0,0 +1,429 @@
+//! Format-independent sequence records, reading, writing and streaming
+//! conversion between FASTA, FASTQ, GenBank and EMBL.
+//!
+//! GenBank and EMBL are handled at the sequence level: identifier,
+//! definition line and sequence. Feature tables are skipped on reading and
+//! left empty on writing.
+
+use std::fmt;
+use std::io::{self, BufRead, Write};
+use std::str::FromStr;
+
+use crate::fasta::{FastaError, FastaReader, FastaSeq, LINE_WIDTH};
+use crate::fastq::{FastqError, FastqReader, FastqSeq};
+
+pub mod embl;
+pub mod genbank;
+
+/// The parts of a sequence record every format can carry.
+pub trait Record {
+    fn id(&self) -> &str;
+    fn description(&self) -> &str;
+    fn sequence(&self) -> &str;
+    /// Phred+33 quality characters, one per base, for formats with
+    /// qualities.
+    fn quality(&self) -> Option<&str> {
+        None
+    }
+}
+
+impl Record for FastaSeq {
+    fn id(&self) -> &str {
+        FastaSeq::id(self)
+    }
+
+    fn description(&self) -> &str {
+        FastaSeq::description(self)
+    }
+
+    fn sequence(&self) -> &str {
+        FastaSeq::sequence(self)
+    }
+}
+
+impl Record for FastqSeq {
+    fn id(&self) -> &str {
+        FastqSeq::id(self)
+    }
+
+    fn description(&self) -> &str {
+        FastqSeq::description(self)
+    }
+
+    fn sequence(&self) -> &str {
+        FastqSeq::sequence(self)
+    }
+
+    fn quality(&self) -> Option<&str> {
+        Some(FastqSeq::quality(self))
+    }
+}
+
+/// An owned record of any format.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct SeqRecord {
+    pub id: String,
+    pub description: String,
+    pub sequence: String,
+    pub quality: Option<String>,
+}
+
+impl SeqRecord {
+    pub fn from_record<T: Record + ?Sized>(record: &T) -> Self {
+        SeqRecord {
+            id: record.id().to_string(),
+            description: record.description().to_string(),
+            sequence: record.sequence().to_string(),
+            quality: record.quality().map(str::to_string),
+        }
+    }
+}
+
+impl Record for SeqRecord {
+    fn id(&self) -> &str {
+        &self.id
+    }
+
+    fn description(&self) -> &str {
+        &self.description
+    }
+
+    fn sequence(&self) -> &str {
+        &self.sequence
+    }
+
+    fn quality(&self) -> Option<&str> {
+        self.quality.as_deref()
+    }
+}
+
+/// Supported sequence file formats.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SeqFormat {
+    Fasta,
+    Fastq,
+    GenBank,
+    Embl,
+}
+
+impl SeqFormat {
+    pub fn name(&self) -> &'static str {
+        match self {
+            SeqFormat::Fasta => "fasta",
+            SeqFormat::Fastq => "fastq",
+            SeqFormat::GenBank => "genbank",
+            SeqFormat::Embl => "embl",
+        }
+    }
+}
+
+impl fmt::Display for SeqFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// Parses a format name or a common file extension, ignoring case:
+/// `fasta`/`fa`/`fna`/`faa`, `fastq`/`fq`, `genbank`/`gb`/`gbk`, `embl`.
+impl FromStr for SeqFormat {
+    type Err = IoError;
+
+    fn from_str(name: &str) -> Result<Self, IoError> {
+        match name.to_ascii_lowercase().as_str() {
+            "fasta" | "fa" | "fna" | "faa" => Ok(SeqFormat::Fasta),
+            "fastq" | "fq" => Ok(SeqFormat::Fastq),
+            "genbank" | "gb" | "gbk" => Ok(SeqFormat::GenBank),
+            "embl" => Ok(SeqFormat::Embl),
+            _ => Err(IoError::UnknownFormat(name.to_string())),
+        }
+    }
+}
+
+/// Errors raised while reading, writing or converting records.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum IoError {
+    Fasta(FastaError),
+    Fastq(FastqError),
+    /// A GenBank or EMBL record does not start with its header line at the
+    /// given 1-based line.
+    MissingHeader {
+        format: SeqFormat,
+        line: usize,
+    },
+    /// The input ends inside a GenBank or EMBL record, after the given
+    /// 1-based line.
+    Truncated {
+        format: SeqFormat,
+        line: usize,
+    },
+    /// FASTQ output was requested for a record without qualities.
+    MissingQuality(String),
+    UnknownFormat(String),
+    /// Reading or writing the underlying stream failed.
+    Io {
+        kind: io::ErrorKind,
+        message: String,
+    },
+}
+
+impl From<io::Error> for IoError {
+    fn from(err: io::Error) -> Self {
+        IoError::Io {
+            kind: err.kind(),
+            message: err.to_string(),
+        }
+    }
+}
+
+impl From<FastaError> for IoError {
+    fn from(err: FastaError) -> Self {
+        IoError::Fasta(err)
+    }
+}
+
+impl From<FastqError> for IoError {
+    fn from(err: FastqError) -> Self {
+        IoError::Fastq(err)
+    }
+}
+
+impl fmt::Display for IoError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            IoError::Fasta(err) => err.fmt(f),
+            IoError::Fastq(err) => err.fmt(f),
+            IoError::MissingHeader { format, line } => {
+                write!(
+                    f,
+                    "line {}: expected the start of a {} record",
+                    line, format
+                )
+            }
+            IoError::Truncated { format, line } => {
+                write!(
+                    f,
+                    "{} input ends inside a record after line {}",
+                    format, line
+                )
+            }
+            IoError::MissingQuality(id) => write!(f, "record {} has no qualities", id),
+            IoError::UnknownFormat(name) => write!(f, "unknown sequence format {:?}", name),
+            IoError::Io { message, .. } => write!(f, "I/O error: {}", message),
+        }
+    }
+}
+
+impl std::error::Error for IoError {}
+
+/// Streaming reader over any supported format, yielding [`SeqRecord`]s.
+pub struct SeqReader<R> {
+    inner: Inner<R>,
+}
+
+enum Inner<R> {
+    Fasta(FastaReader<R>),
+    Fastq(FastqReader<R>),
+    GenBank(genbank::GenBankReader<R>),
+    Embl(embl::EmblReader<R>),
+}
+
+impl<R: BufRead> SeqReader<R> {
+    pub fn new(reader: R, format: SeqFormat) -> Self {
+        let inner = match format {
+            SeqFormat::Fasta => Inner::Fasta(FastaReader::new(reader)),
+            SeqFormat::Fastq => Inner::Fastq(FastqReader::new(reader)),
+            SeqFormat::GenBank => Inner::GenBank(genbank::GenBankReader::new(reader)),
+            SeqFormat::Embl => Inner::Embl(embl::EmblReader::new(reader)),
+        };
+        SeqReader { inner }
+    }
+}
+
+impl<R: BufRead> Iterator for SeqReader<R> {
+    type Item = Result<SeqRecord, IoError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        Some(match &mut self.inner {
+            Inner::Fasta(reader) => reader
+                .next()?
+                .map(|seq| SeqRecord::from_record(&seq))
+                .map_err(IoError::from),
+            Inner::Fastq(reader) => reader
+                .next()?
+                .map(|read| SeqRecord::from_record(&read))
+                .map_err(IoError::from),
+            Inner::GenBank(reader) => reader.next()?,
+            Inner::Embl(reader) => reader.next()?,
+        })
+    }
+}
+
+/// Writes one record in `format`. FASTA sequences are wrapped at
+/// [`LINE_WIDTH`]; FASTQ output needs a record with qualities.
+pub fn write_record<W, T>(out: &mut W, record: &T, format: SeqFormat) -> Result<(), IoError>
+where
+    W: Write,
+    T: Record + ?Sized,
+{
+    match format {
+        SeqFormat::Fasta => {
+            write_header(out, '>', record)?;
+            for line in record.sequence().as_bytes().chunks(LINE_WIDTH) {
+                out.write_all(line)?;
+                out.write_all(b"\n")?;
+            }
+        }
+        SeqFormat::Fastq => {
+            let quality = record
+                .quality()
+                .ok_or_else(|| IoError::MissingQuality(record.id().to_string()))?;
+            write_header(out, '@', record)?;
+            writeln!(out, "{}\n+\n{}", record.sequence(), quality)?;
+        }
+        SeqFormat::GenBank => genbank::write_genbank(out, record)?,
+        SeqFormat::Embl => embl::write_embl(out, record)?,
+    }
+    Ok(())
+}
+
+fn write_header<W: Write, T: Record + ?Sized>(
+    out: &mut W,
+    marker: char,
+    record: &T,
+) -> io::Result<()> {
+    if record.description().is_empty() {
+        writeln!(out, "{}{}", marker, record.id())
+    } else {
+        writeln!(out, "{}{} {}", marker, record.id(), record.description())
+    }
+}
+
+/// Streams every record of `reader` from one format to another and
+/// returns the number converted. Converting to FASTQ fails on the first
+/// record without qualities.
+pub fn convert<R: BufRead, W: Write>(
+    reader: R,
+    writer: &mut W,
+    from: SeqFormat,
+    to: SeqFormat,
+) -> Result<u64, IoError> {
+    let mut converted = 0;
+    for record in SeqReader::new(reader, from) {
+        write_record(writer, &record?, to)?;
+        converted += 1;
+    }
+    Ok(converted)
+}
+
+/// Sequence as lower-case groups of ten, sixty bases per line: the layout
+/// shared by the GenBank `ORIGIN` and EMBL `SQ` blocks. Yields each line
+/// with its 0-based start and end.
+fn sequence_lines(sequence: &str) -> impl Iterator<Item = (String, usize, usize)> + '_ {
+    sequence.as_bytes().chunks(60).enumerate().map(|(i, line)| {
+        let groups: Vec<String> = line
+            .chunks(10)
+            .map(|g| String::from_utf8_lossy(g).to_ascii_lowercase())
+            .collect();
+        (groups.join(" "), i * 60, i * 60 + line.len())
+    })
+}
+
+/// Splits `text` into lines of at most `width` characters at spaces; a
+/// longer word gets a line of its own.
+fn wrap_words(text: &str, width: usize) -> Vec<String> {
+    let mut lines: Vec<String> = Vec::new();
+    for word in text.split_whitespace() {
+        match lines.last_mut() {
+            Some(line) if line.len() + 1 + word.len() <= width => {
+                line.push(' ');
+                line.push_str(word);
+            }
+            _ => lines.push(word.to_string()),
+        }
+    }
+    lines
+}
+
+/// Line-by-line reader for the flat-file formats, tracking the 1-based
+/// line number for error reports.
+struct Lines<R> {
+    reader: R,
+    line: String,
+    number: usize,
+}
+
+impl<R: BufRead> Lines<R> {
+    fn new(reader: R) -> Self {
+        Lines {
+            reader,
+            line: String::new(),
+            number: 0,
+        }
+    }
+
+    /// The next line without its line ending, or `None` at the end.
+    fn next_line(&mut self) -> Result<Option<&str>, IoError> {
+        self.line.clear();
+        if self.reader.read_line(&mut self.line)? == 0 {
+            return Ok(None);
+        }
+        self.number += 1;
+        Ok(Some(self.line.trim_end_matches(['\n', '\r'])))
+    }
+}
+
+/// Upper-cased letters of a sequence block line, skipping positions and
+/// spacing.
+fn push_sequence(sequence: &mut String, line: &str) {
+    sequence.extend(
+        line.chars()
+            .filter(|c| c.is_ascii_alphabetic() || *c == '*' || *c == '-')
+            .map(|c| c.to_ascii_uppercase()),
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FASTQ: &str = "@r1 first read\nACGTACGTAC\n+\nIIIIIHHHHH\n@r2\nGGTT\n+\n!!!!\n";
+
+    fn converted(text: &str, from: SeqFormat, to: SeqFormat) -> Result<String, IoError> {
+        let mut out = Vec::new();
+        convert(text.as_bytes(), &mut out, from, to)?;
+        Ok(String::from_utf8(out).unwrap())
+    }
+
+    #[test]
+    fn parses_format_names() {
+        assert_eq!("GBK".parse::<SeqFormat>(), Ok(SeqFormat::GenBank));
+        assert_eq!("fq".parse::<SeqFormat>(), Ok(SeqFormat::Fastq));
+        assert_eq!(
+            "sam".parse::<SeqFormat>(),
+            Err(IoError::UnknownFormat("sam".to_string()))
+        );
+    }
+
+    #[test]
+    fn converts_through_every_format() {
+        let mut text = FASTQ.to_string();
+        let mut from = SeqFormat::Fastq;
+        for to in [SeqFormat::GenBank, SeqFormat::Embl, SeqFormat::Fasta] {
+            text = converted(&text, from, to).unwrap();
+            from = to;
+        }
+        assert_eq!(text, ">r1 first read\nACGTACGTAC\n>r2\nGGTT\n");
+    }
+
+    #[test]
+    fn fastq_needs_qualities() {
+        assert_eq!(
+            converted(FASTQ, SeqFormat::Fastq, SeqFormat::Fastq).unwrap(),
+            FASTQ
+        );
+        assert_eq!(
+            converted(">s1\nACGT\n", SeqFormat::Fasta, SeqFormat::Fastq),
+            Err(IoError::MissingQuality("s1".to_string()))
+        );
+    }
+}
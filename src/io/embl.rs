@@ -0,0 +1,178 @@
+//! EMBL flat files at the sequence level.
+
+use std::io::{BufRead, Write};
+
+use crate::alphabet::infer_type_and_alphabet;
+use crate::io::{
+    push_sequence, sequence_lines, wrap_words, IoError, Lines, Record, SeqFormat, SeqRecord,
+};
+
+/// Streaming EMBL reader. The identifier is the first `ID` field and the
+/// description the joined `DE` lines.
+pub struct EmblReader<R> {
+    lines: Lines<R>,
+}
+
+impl<R: BufRead> EmblReader<R> {
+    pub fn new(reader: R) -> Self {
+        EmblReader {
+            lines: Lines::new(reader),
+        }
+    }
+
+    fn read_record(&mut self) -> Result<Option<SeqRecord>, IoError> {
+        let id = loop {
+            let Some(line) = self.lines.next_line()? else {
+                return Ok(None);
+            };
+            if line.trim().is_empty() {
+                continue;
+            }
+            match line.strip_prefix("ID ") {
+                Some(rest) => break rest.split(';').next().unwrap_or("").trim().to_string(),
+                None => {
+                    return Err(IoError::MissingHeader {
+                        format: SeqFormat::Embl,
+                        line: self.lines.number,
+                    })
+                }
+            }
+        };
+        let mut record = SeqRecord {
+            id,
+            ..SeqRecord::default()
+        };
+        let mut in_sequence = false;
+        loop {
+            let Some(line) = self.lines.next_line()? else {
+                return Err(IoError::Truncated {
+                    format: SeqFormat::Embl,
+                    line: self.lines.number,
+                });
+            };
+            if line.starts_with("//") {
+                break;
+            }
+            if in_sequence {
+                push_sequence(&mut record.sequence, line);
+            } else if let Some(text) = line.strip_prefix("DE ") {
+                if !record.description.is_empty() {
+                    record.description.push(' ');
+                }
+                record.description.push_str(text.trim());
+            } else {
+                in_sequence = line.starts_with("SQ ");
+            }
+        }
+        Ok(Some(record))
+    }
+}
+
+impl<R: BufRead> Iterator for EmblReader<R> {
+    type Item = Result<SeqRecord, IoError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.read_record().transpose()
+    }
+}
+
+/// Writes a record as an EMBL entry without a feature table.
+pub fn write_embl<W: Write, T: Record + ?Sized>(out: &mut W, record: &T) -> Result<(), IoError> {
+    let sequence = record.sequence();
+    let seq_type = infer_type_and_alphabet(sequence).0;
+    writeln!(
+        out,
+        "ID   {}; SV 1; linear; {}; STD; UNC; {} BP.\nXX",
+        record.id(),
+        seq_type,
+        sequence.len()
+    )?;
+    writeln!(out, "AC   {};\nXX", record.id())?;
+    let description = wrap_words(record.description(), 75);
+    for line in &description {
+        writeln!(out, "DE   {}", line)?;
+    }
+    if !description.is_empty() {
+        writeln!(out, "XX")?;
+    }
+    let mut counts = [0usize; 4];
+    for b in sequence.bytes() {
+        match b.to_ascii_uppercase() {
+            b'A' => counts[0] += 1,
+            b'C' => counts[1] += 1,
+            b'G' => counts[2] += 1,
+            b'T' => counts[3] += 1,
+            _ => {}
+        }
+    }
+    writeln!(
+        out,
+        "SQ   Sequence {} BP; {} A; {} C; {} G; {} T; {} other;",
+        sequence.len(),
+        counts[0],
+        counts[1],
+        counts[2],
+        counts[3],
+        sequence.len() - counts.iter().sum::<usize>()
+    )?;
+    for (groups, _, end) in sequence_lines(sequence) {
+        writeln!(out, "     {:<65}{:>10}", groups, end)?;
+    }
+    writeln!(out, "//")?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ENTRY: &str = "\
+ID   X56734; SV 1; linear; mRNA; STD; PLN; 1859 BP.
+XX
+AC   X56734; S46826;
+XX
+DE   Trifolium repens mRNA for non-cyanogenic beta-glucosidase
+XX
+FH   Key             Location/Qualifiers
+FT   source          1..1859
+SQ   Sequence 1859 BP; 609 A; 314 C; 355 G; 581 T; 0 other;
+     aaacaaacca aatatggatt ttattgtagc catatttgct ctgtttgtta ttagctcatt        60
+     cacaattgct                                                               70
+//
+";
+
+    #[test]
+    fn reads_entries() {
+        let records: Vec<SeqRecord> = EmblReader::new(ENTRY.as_bytes())
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].id, "X56734");
+        assert_eq!(
+            records[0].description,
+            "Trifolium repens mRNA for non-cyanogenic beta-glucosidase"
+        );
+        assert_eq!(records[0].sequence.len(), 70);
+        assert!(records[0].sequence.ends_with("CACAATTGCT"));
+    }
+
+    #[test]
+    fn round_trips_records() {
+        let record = SeqRecord {
+            id: "seq1".to_string(),
+            description: "word ".repeat(20).trim_end().to_string(),
+            sequence: "ACGTN".repeat(13),
+            quality: None,
+        };
+        let mut out = Vec::new();
+        write_embl(&mut out, &record).unwrap();
+        let text = String::from_utf8(out).unwrap();
+        assert!(text.starts_with("ID   seq1; SV 1; linear; DNA; STD; UNC; 65 BP.\n"));
+        assert!(text.contains("SQ   Sequence 65 BP; 13 A; 13 C; 13 G; 13 T; 13 other;\n"));
+        assert!(text.contains(&format!("\n     acgtn{}65\n//\n", " ".repeat(68))));
+        assert_eq!(text.lines().filter(|l| l.starts_with("DE")).count(), 2);
+        assert!(text.lines().all(|l| l.len() <= 80));
+        let back = EmblReader::new(text.as_bytes()).next().unwrap().unwrap();
+        assert_eq!(back, record);
+    }
+}
@@ -0,0 +1,190 @@
+//! GenBank flat files at the sequence level.
+
+use std::io::{BufRead, Write};
+
+use crate::alphabet::{infer_type_and_alphabet, SeqType};
+use crate::io::{
+    push_sequence, sequence_lines, wrap_words, IoError, Lines, Record, SeqFormat, SeqRecord,
+};
+
+/// Streaming GenBank reader. The identifier is the `LOCUS` name and the
+/// description the `DEFINITION` without its final period.
+pub struct GenBankReader<R> {
+    lines: Lines<R>,
+}
+
+impl<R: BufRead> GenBankReader<R> {
+    pub fn new(reader: R) -> Self {
+        GenBankReader {
+            lines: Lines::new(reader),
+        }
+    }
+
+    fn read_record(&mut self) -> Result<Option<SeqRecord>, IoError> {
+        let id = loop {
+            let Some(line) = self.lines.next_line()? else {
+                return Ok(None);
+            };
+            if line.trim().is_empty() {
+                continue;
+            }
+            match line.strip_prefix("LOCUS") {
+                Some(rest) => match rest.split_whitespace().next() {
+                    Some(name) => break name.to_string(),
+                    None => break String::new(),
+                },
+                None => {
+                    return Err(IoError::MissingHeader {
+                        format: SeqFormat::GenBank,
+                        line: self.lines.number,
+                    })
+                }
+            }
+        };
+        let mut record = SeqRecord {
+            id,
+            ..SeqRecord::default()
+        };
+        let mut in_definition = false;
+        let mut in_sequence = false;
+        loop {
+            let Some(line) = self.lines.next_line()? else {
+                return Err(IoError::Truncated {
+                    format: SeqFormat::GenBank,
+                    line: self.lines.number,
+                });
+            };
+            if line.starts_with("//") {
+                break;
+            }
+            if in_sequence {
+                push_sequence(&mut record.sequence, line);
+            } else if let Some(text) = line.strip_prefix("DEFINITION") {
+                record.description = text.trim().to_string();
+                in_definition = true;
+            } else if in_definition && line.starts_with(' ') {
+                record.description.push(' ');
+                record.description.push_str(line.trim());
+            } else {
+                in_definition = false;
+                in_sequence = line.starts_with("ORIGIN");
+            }
+        }
+        if let Some(description) = record.description.strip_suffix('.') {
+            record.description.truncate(description.len());
+        }
+        Ok(Some(record))
+    }
+}
+
+impl<R: BufRead> Iterator for GenBankReader<R> {
+    type Item = Result<SeqRecord, IoError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.read_record().transpose()
+    }
+}
+
+/// Writes a record as a GenBank entry with an empty feature table.
+pub fn write_genbank<W: Write, T: Record + ?Sized>(out: &mut W, record: &T) -> Result<(), IoError> {
+    let sequence = record.sequence();
+    let seq_type = infer_type_and_alphabet(sequence).0;
+    let (units, molecule) = match seq_type {
+        SeqType::Protein => ("aa", ""),
+        SeqType::Dna => ("bp", "DNA"),
+        SeqType::Rna => ("bp", "RNA"),
+    };
+    writeln!(
+        out,
+        "LOCUS       {:<16} {:>11} {}    {:<6}  linear   UNK 01-JAN-1980",
+        record.id(),
+        sequence.len(),
+        units,
+        molecule
+    )?;
+    let definition = format!("{}.", record.description());
+    for (i, line) in wrap_words(&definition, 67).iter().enumerate() {
+        let key = if i == 0 { "DEFINITION" } else { "" };
+        writeln!(out, "{:<12}{}", key, line)?;
+    }
+    writeln!(out, "ACCESSION   {}", record.id())?;
+    writeln!(out, "FEATURES             Location/Qualifiers")?;
+    writeln!(out, "ORIGIN")?;
+    for (groups, start, _) in sequence_lines(sequence) {
+        writeln!(out, "{:>9} {}", start + 1, groups)?;
+    }
+    writeln!(out, "//")?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ENTRY: &str = "\
+LOCUS       SCU49845     5028 bp    DNA             PLN       21-JUN-1999
+DEFINITION  Saccharomyces cerevisiae TCP1-beta gene, partial cds, and Axl2p
+            (AXL2) and Rev7p (REV7) genes, complete cds.
+ACCESSION   U49845
+FEATURES             Location/Qualifiers
+     source          1..5028
+                     /organism=\"Saccharomyces cerevisiae\"
+ORIGIN
+        1 gatcctccat atacaacggt atctccacct caggtttaga tctcaacaac ggaaccattg
+       61 ccgacatgag
+//
+";
+
+    #[test]
+    fn reads_entries() {
+        let text = format!("{}\n{}", ENTRY, ENTRY.replace("SCU49845", "SECOND"));
+        let records: Vec<SeqRecord> = GenBankReader::new(text.as_bytes())
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[0].id, "SCU49845");
+        assert_eq!(
+            records[0].description,
+            "Saccharomyces cerevisiae TCP1-beta gene, partial cds, and Axl2p \
+             (AXL2) and Rev7p (REV7) genes, complete cds"
+        );
+        assert_eq!(records[0].sequence.len(), 70);
+        assert!(records[0].sequence.starts_with("GATCCTCCATATAC"));
+        assert_eq!(records[1].id, "SECOND");
+    }
+
+    #[test]
+    fn round_trips_records() {
+        let record = SeqRecord {
+            id: "seq1".to_string(),
+            description: "a test sequence".to_string(),
+            sequence: "ACGT".repeat(20),
+            quality: None,
+        };
+        let mut out = Vec::new();
+        write_genbank(&mut out, &record).unwrap();
+        let text = String::from_utf8(out).unwrap();
+        assert!(text.contains("DEFINITION  a test sequence.\n"));
+        assert!(text.contains("\n       61 acgtacgtac gtacgtacgt\n//\n"));
+        let back = GenBankReader::new(text.as_bytes()).next().unwrap().unwrap();
+        assert_eq!(back, record);
+    }
+
+    #[test]
+    fn reports_malformed_input() {
+        assert_eq!(
+            GenBankReader::new("\nID   x\n".as_bytes()).next(),
+            Some(Err(IoError::MissingHeader {
+                format: SeqFormat::GenBank,
+                line: 2
+            }))
+        );
+        assert_eq!(
+            GenBankReader::new("LOCUS x\nORIGIN\n 1 acgt\n".as_bytes()).next(),
+            Some(Err(IoError::Truncated {
+                format: SeqFormat::GenBank,
+                line: 3
+            }))
+        );
+    }
+}
@@ -0,0 +1,239 @@
+//! IUPAC nucleotide ambiguity codes and ambiguity-aware comparison.
+
+use std::fmt;
+
+const A: u8 = 1;
+const C: u8 = 2;
+const G: u8 = 4;
+const T: u8 = 8;
+
+/// Bit set of the concrete bases (A=1, C=2, G=4, T/U=8) a nucleotide
+/// symbol stands for; 0 for anything that is not an IUPAC code.
+pub fn base_mask(symbol: u8) -> u8 {
+    match symbol.to_ascii_uppercase() {
+        b'A' => A,
+        b'C' => C,
+        b'G' => G,
+        b'T' | b'U' => T,
+        b'R' => A | G,
+        b'Y' => C | T,
+        b'S' => C | G,
+        b'W' => A | T,
+        b'K' => G | T,
+        b'M' => A | C,
+        b'B' => C | G | T,
+        b'D' => A | G | T,
+        b'H' => A | C | T,
+        b'V' => A | C | G,
+        b'N' => A | C | G | T,
+        _ => 0,
+    }
+}
+
+/// Whether two symbols can stand for the same base, e.g. `R` and `A`, or
+/// `N` and anything. Identical symbols, gaps included, always match.
+pub fn symbols_match(a: u8, b: u8) -> bool {
+    a.eq_ignore_ascii_case(&b) || base_mask(a) & base_mask(b) != 0
+}
+
+/// Whether `pattern` covers `base`: every base `base` may stand for is
+/// allowed by `pattern`. `R` covers `A` and `G`, but `A` does not cover `R`.
+pub fn covers(pattern: u8, base: u8) -> bool {
+    if pattern.eq_ignore_ascii_case(&base) {
+        return true;
+    }
+    let (p, b) = (base_mask(pattern), base_mask(base));
+    b != 0 && p & b == b
+}
+
+/// Case-insensitive sequence equality.
+pub fn equals_ignore_case(a: &[u8], b: &[u8]) -> bool {
+    a.eq_ignore_ascii_case(b)
+}
+
+/// Whether two equal-length sequences are compatible at every position
+/// under [`symbols_match`].
+pub fn sequences_match(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).all(|(&x, &y)| symbols_match(x, y))
+}
+
+/// Number of positions where two equal-length sequences are incompatible,
+/// or `None` if their lengths differ.
+pub fn mismatches(a: &[u8], b: &[u8]) -> Option<usize> {
+    (a.len() == b.len()).then(|| {
+        a.iter()
+            .zip(b)
+            .filter(|(&x, &y)| !symbols_match(x, y))
+            .count()
+    })
+}
+
+/// Errors from [`expand`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ExpansionError {
+    /// The symbol at `position` is not an IUPAC nucleotide code.
+    InvalidSymbol { position: usize, symbol: char },
+    /// The sequence stands for more variants than the allowed maximum;
+    /// `count` is `None` if it does not even fit in a `u128`.
+    TooManyVariants { count: Option<u128>, max: usize },
+}
+
+impl fmt::Display for ExpansionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ExpansionError::InvalidSymbol { position, symbol } => write!(
+                f,
+                "symbol {:?} at position {} is not an IUPAC nucleotide code",
+                symbol, position
+            ),
+            ExpansionError::TooManyVariants {
+                count: Some(count),
+                max,
+            } => write!(f, "{} variants exceed the limit of {}", count, max),
+            ExpansionError::TooManyVariants { count: None, max } => {
+                write!(f, "variant count overflows, limit is {}", max)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ExpansionError {}
+
+/// Number of concrete sequences a degenerate sequence stands for, or `None`
+/// on overflow. Symbols that are not IUPAC codes count as one variant.
+pub fn variant_count(seq: &[u8]) -> Option<u128> {
+    seq.iter().try_fold(1u128, |acc, &b| {
+        acc.checked_mul(base_mask(b).count_ones().max(1) as u128)
+    })
+}
+
+/// All concrete sequences a degenerate sequence stands for, e.g. `AR` gives
+/// `AA` and `AG`. Output is upper case, in `ACGT` order per position, and
+/// uses `U` instead of `T` when the input contains `U`.
+///
+/// Fails without allocating the variants if there would be more than
+/// `max_variants` of them.
+pub fn expand(seq: &[u8], max_variants: usize) -> Result<Vec<Vec<u8>>, ExpansionError> {
+    if let Some((position, &symbol)) = seq.iter().enumerate().find(|(_, &b)| base_mask(b) == 0) {
+        return Err(ExpansionError::InvalidSymbol {
+            position,
+            symbol: symbol as char,
+        });
+    }
+    let count = variant_count(seq);
+    if count.is_none_or(|n| n > max_variants as u128) {
+        return Err(ExpansionError::TooManyVariants {
+            count,
+            max: max_variants,
+        });
+    }
+    let thymine = if seq.iter().any(|b| b.eq_ignore_ascii_case(&b'U')) {
+        b'U'
+    } else {
+        b'T'
+    };
+    let choices: Vec<Vec<u8>> = seq
+        .iter()
+        .map(|&b| {
+            let mask = base_mask(b);
+            [(A, b'A'), (C, b'C'), (G, b'G'), (T, thymine)]
+                .iter()
+                .filter(|(bit, _)| mask & bit != 0)
+                .map(|&(_, base)| base)
+                .collect()
+        })
+        .collect();
+
+    let mut variants = vec![Vec::with_capacity(seq.len())];
+    for options in &choices {
+        variants = variants
+            .into_iter()
+            .flat_map(|prefix| {
+                options.iter().map(move |&base| {
+                    let mut next = prefix.clone();
+                    next.push(base);
+                    next
+                })
+            })
+            .collect();
+    }
+    Ok(variants)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ambiguity_codes_match_their_bases() {
+        assert!(symbols_match(b'R', b'a'));
+        assert!(symbols_match(b'g', b'R'));
+        assert!(!symbols_match(b'R', b'C'));
+        assert!(symbols_match(b'N', b'T'));
+        assert!(symbols_match(b'Y', b'K'));
+        assert!(symbols_match(b'U', b'T'));
+        assert!(symbols_match(b'-', b'-'));
+        assert!(!symbols_match(b'-', b'A'));
+    }
+
+    #[test]
+    fn covers_is_directional() {
+        assert!(covers(b'R', b'A'));
+        assert!(!covers(b'A', b'R'));
+        assert!(covers(b'N', b'Y'));
+        assert!(!covers(b'Y', b'N'));
+        assert!(!covers(b'N', b'-'));
+    }
+
+    #[test]
+    fn expands_degenerate_sequences() {
+        let variants = expand(b"aRY", 10).unwrap();
+        assert_eq!(
+            variants,
+            vec![
+                b"AAC".to_vec(),
+                b"AAT".to_vec(),
+                b"AGC".to_vec(),
+                b"AGT".to_vec()
+            ]
+        );
+        assert_eq!(expand(b"NU", 10).unwrap().len(), 4);
+        assert!(expand(b"NU", 10).unwrap().iter().all(|v| v[1] == b'U'));
+        assert_eq!(expand(b"", 1).unwrap(), vec![Vec::<u8>::new()]);
+    }
+
+    #[test]
+    fn expansion_guards_against_explosion() {
+        assert_eq!(variant_count(b"NNN"), Some(64));
+        assert_eq!(
+            expand(b"NNN", 63),
+            Err(ExpansionError::TooManyVariants {
+                count: Some(64),
+                max: 63
+            })
+        );
+        let huge = vec![b'N'; 100];
+        assert_eq!(variant_count(&huge), None);
+        assert!(matches!(
+            expand(&huge, usize::MAX),
+            Err(ExpansionError::TooManyVariants { count: None, .. })
+        ));
+        assert_eq!(
+            expand(b"AC-T", 10),
+            Err(ExpansionError::InvalidSymbol {
+                position: 2,
+                symbol: '-'
+            })
+        );
+    }
+
+    #[test]
+    fn compares_whole_sequences() {
+        assert!(equals_ignore_case(b"ACGT", b"acgt"));
+        assert!(!equals_ignore_case(b"ACGT", b"ACGA"));
+        assert!(sequences_match(b"ACGTNR", b"acgtCG"));
+        assert!(!sequences_match(b"ACGT", b"ACG"));
+        assert_eq!(mismatches(b"ACGTRR", b"ACGAAC"), Some(2));
+        assert_eq!(mismatches(b"A", b"AC"), None);
+    }
+}
@@ -0,0 +1,71 @@
+//! Minimal JSON output helpers for the report types.
+
+/// Quotes and escapes `value` as a JSON string.
+pub(crate) fn string(value: &str) -> String {
+    let mut out = String::with_capacity(value.len() + 2);
+    out.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+/// Formats a finite number with at most four decimals, trailing zeros
+/// trimmed; non-finite values become `null`.
+pub(crate) fn number(value: f64) -> String {
+    if !value.is_finite() {
+        return "null".to_string();
+    }
+    let text = format!("{:.4}", value);
+    let text = text.trim_end_matches('0').trim_end_matches('.');
+    if text == "-0" {
+        "0".to_string()
+    } else {
+        text.to_string()
+    }
+}
+
+/// Joins already-serialized values into a JSON array.
+pub(crate) fn array<I, S>(items: I) -> String
+where
+    I: IntoIterator<Item = S>,
+    S: AsRef<str>,
+{
+    let items: Vec<S> = items.into_iter().collect();
+    let parts: Vec<&str> = items.iter().map(AsRef::as_ref).collect();
+    format!("[{}]", parts.join(","))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn escapes_strings() {
+        assert_eq!(string("a\"b\\c\n"), "\"a\\\"b\\\\c\\n\"");
+        assert_eq!(string("\u{1}"), "\"\\u0001\"");
+    }
+
+    #[test]
+    fn formats_numbers() {
+        assert_eq!(number(60.0), "60");
+        assert_eq!(number(0.123456), "0.1235");
+        assert_eq!(number(-0.00001), "0");
+        assert_eq!(number(f64::NAN), "null");
+    }
+
+    #[test]
+    fn joins_arrays() {
+        assert_eq!(array(["1", "2"]), "[1,2]");
+        assert_eq!(array(Vec::<String>::new()), "[]");
+    }
+}
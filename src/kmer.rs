@@ -0,0 +1,247 @@
+//! Oligonucleotide counts and over/under-representation statistics.
+//!
+//! The expected count of a k-mer `w` comes from the maximal-order Markov
+//! model: `E(w) = N(w⁻) N(⁻w) / N(⁻w⁻)`, where `w⁻` drops the last base,
+//! `⁻w` the first and `⁻w⁻` both. For dinucleotides this is Karlin's
+//! relative abundance `f(XY) / f(X) f(Y)`. The z-score uses the model's
+//! asymptotic variance `E (N(⁻w⁻) - N(w⁻)) (N(⁻w⁻) - N(⁻w)) / N(⁻w⁻)²`.
+
+use std::collections::HashMap;
+
+use crate::seq::reverse_complement;
+
+/// Largest supported k; counts of all 4^k words are kept.
+pub const MAX_K: usize = 10;
+
+fn base_code(base: u8) -> Option<usize> {
+    match base.to_ascii_uppercase() {
+        b'A' => Some(0),
+        b'C' => Some(1),
+        b'G' => Some(2),
+        b'T' | b'U' => Some(3),
+        _ => None,
+    }
+}
+
+/// Counts of every word of length `len` over unambiguous bases.
+fn count_words(seq: &[u8], len: usize, counts: &mut [u64]) {
+    if len == 0 {
+        counts[0] += seq.iter().filter(|&&b| base_code(b).is_some()).count() as u64;
+        return;
+    }
+    let mask = (1usize << (2 * len)) - 1;
+    let (mut code, mut valid) = (0usize, 0usize);
+    for &b in seq {
+        match base_code(b) {
+            Some(c) => {
+                code = ((code << 2) | c) & mask;
+                valid += 1;
+                if valid >= len {
+                    counts[code] += 1;
+                }
+            }
+            None => valid = 0,
+        }
+    }
+}
+
+/// K-mer counts of one or more sequences, for instance the contigs of a
+/// genome. Words never span sequence boundaries or ambiguous bases.
+#[derive(Debug, Clone, PartialEq)]
+pub struct KmerCounts {
+    k: usize,
+    /// Counts of words of length k, k - 1 and k - 2.
+    counts: [Vec<u64>; 3],
+}
+
+impl KmerCounts {
+    /// # Panics
+    ///
+    /// Panics unless `2 <= k <= MAX_K`.
+    pub fn new(k: usize) -> Self {
+        assert!(
+            (2..=MAX_K).contains(&k),
+            "k must be between 2 and {}",
+            MAX_K
+        );
+        KmerCounts {
+            k,
+            counts: [
+                vec![0; 1 << (2 * k)],
+                vec![0; 1 << (2 * (k - 1))],
+                vec![0; 1 << (2 * (k - 2))],
+            ],
+        }
+    }
+
+    pub fn k(&self) -> usize {
+        self.k
+    }
+
+    pub fn add(&mut self, seq: &[u8]) {
+        for (i, counts) in self.counts.iter_mut().enumerate() {
+            count_words(seq, self.k - i, counts);
+        }
+    }
+
+    /// Adds `seq` and its reverse complement, giving strand-symmetric
+    /// counts as used for genomic signatures.
+    pub fn add_both_strands(&mut self, seq: &[u8]) {
+        self.add(seq);
+        self.add(&reverse_complement(seq));
+    }
+
+    /// Count of `kmer`, or `None` if it is not an unambiguous word of
+    /// length k.
+    pub fn count(&self, kmer: &[u8]) -> Option<u64> {
+        if kmer.len() != self.k {
+            return None;
+        }
+        let code = kmer
+            .iter()
+            .try_fold(0usize, |code, &b| Some((code << 2) | base_code(b)?))?;
+        Some(self.counts[0][code])
+    }
+
+    /// Total number of k-mers counted.
+    pub fn total(&self) -> u64 {
+        self.counts[0].iter().sum()
+    }
+
+    /// Bias statistics of every k-mer, in lexicographic `ACGT` order.
+    pub fn bias(&self) -> Vec<OligoBias> {
+        let k = self.k;
+        let suffix_mask = (1usize << (2 * (k - 1))) - 1;
+        let middle_mask = (1usize << (2 * (k - 2))) - 1;
+        (0..self.counts[0].len())
+            .map(|code| {
+                let observed = self.counts[0][code];
+                let prefix = self.counts[1][code >> 2] as f64;
+                let suffix = self.counts[1][code & suffix_mask] as f64;
+                let middle = self.counts[2][(code >> 2) & middle_mask] as f64;
+                let expected = if middle > 0.0 {
+                    prefix * suffix / middle
+                } else {
+                    0.0
+                };
+                let variance = if middle > 0.0 {
+                    expected * (middle - prefix) * (middle - suffix) / (middle * middle)
+                } else {
+                    0.0
+                };
+                OligoBias {
+                    kmer: decode(code, k),
+                    observed,
+                    expected,
+                    ratio: (expected > 0.0).then(|| observed as f64 / expected),
+                    z_score: (variance > 0.0)
+                        .then(|| (observed as f64 - expected) / variance.sqrt()),
+                }
+            })
+            .collect()
+    }
+}
+
+fn decode(code: usize, k: usize) -> String {
+    (0..k)
+        .rev()
+        .map(|i| b"ACGT"[(code >> (2 * i)) & 3] as char)
+        .collect()
+}
+
+/// Observed against expected count of one k-mer.
+#[derive(Debug, Clone, PartialEq)]
+pub struct OligoBias {
+    pub kmer: String,
+    pub observed: u64,
+    pub expected: f64,
+    /// Observed/expected ratio; `None` when nothing is expected.
+    pub ratio: Option<f64>,
+    /// `None` when the model gives no variance.
+    pub z_score: Option<f64>,
+}
+
+/// Bias statistics of all k-mers over the given sequences, counting both
+/// strands.
+pub fn oligo_bias<'a, I>(seqs: I, k: usize) -> Vec<OligoBias>
+where
+    I: IntoIterator<Item = &'a [u8]>,
+{
+    let mut counts = KmerCounts::new(k);
+    for seq in seqs {
+        counts.add_both_strands(seq);
+    }
+    counts.bias()
+}
+
+/// Mean absolute difference of the observed/expected ratios of k-mers
+/// scored in both profiles; Karlin's δ* for dinucleotides. A contig far
+/// from the rest of its assembly is a contamination candidate.
+pub fn signature_distance(a: &[OligoBias], b: &[OligoBias]) -> Option<f64> {
+    let b: HashMap<&str, f64> = b
+        .iter()
+        .filter_map(|o| o.ratio.map(|r| (o.kmer.as_str(), r)))
+        .collect();
+    let diffs: Vec<f64> = a
+        .iter()
+        .filter_map(|o| Some((o.ratio? - b.get(o.kmer.as_str())?).abs()))
+        .collect();
+    (!diffs.is_empty()).then(|| diffs.iter().sum::<f64>() / diffs.len() as f64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn counts_skip_ambiguous_bases() {
+        let mut counts = KmerCounts::new(2);
+        counts.add(b"ACGNacgu");
+        assert_eq!(counts.count(b"AC"), Some(2));
+        assert_eq!(counts.count(b"GT"), Some(1));
+        assert_eq!(counts.count(b"GN"), None);
+        assert_eq!(counts.total(), 5);
+    }
+
+    #[test]
+    fn dinucleotide_ratio_is_relative_abundance() {
+        // CG depleted, as in vertebrate genomes.
+        let mut counts = KmerCounts::new(2);
+        counts.add(b"ACAGTCATGCTTGACAAGTTCAGGACTTGACCAGTCATG");
+        let bias = counts.bias();
+        assert_eq!(bias.len(), 16);
+        assert_eq!(bias[0].kmer, "AA");
+        let cg = bias.iter().find(|o| o.kmer == "CG").unwrap();
+        assert_eq!(cg.observed, 0);
+        assert!(cg.ratio.unwrap() == 0.0 && cg.z_score.unwrap() < 0.0);
+
+        let (c, g) = (counts.counts[1][1] as f64, counts.counts[1][2] as f64);
+        let n = counts.counts[2][0] as f64;
+        assert!((cg.expected - c * g / n).abs() < 1e-12);
+    }
+
+    #[test]
+    fn periodic_sequence_has_no_bias_in_its_words() {
+        let bias = oligo_bias([b"ACGTACGTACGTACGTACGT".as_slice()], 3);
+        let acg = bias.iter().find(|o| o.kmer == "ACG").unwrap();
+        assert_eq!(acg.ratio, Some(1.0));
+        let aaa = bias.iter().find(|o| o.kmer == "AAA").unwrap();
+        assert_eq!((aaa.observed, aaa.ratio), (0, None));
+    }
+
+    #[test]
+    fn signature_distance_separates_compositions() {
+        let at_rich = b"AATTTAAATATTAGATTACATTTAAATTTATTAAGTAAT".as_slice();
+        let gc_rich = b"GCGCCGGCGTCGCCGAGCGGCCGCGACGGCGCCCGGCGC".as_slice();
+        let a = oligo_bias([at_rich], 2);
+        assert_eq!(signature_distance(&a, &a), Some(0.0));
+        let b = oligo_bias([gc_rich], 2);
+        assert!(signature_distance(&a, &b).unwrap() > 0.1);
+    }
+
+    #[test]
+    #[should_panic(expected = "k must be between 2 and 10")]
+    fn rejects_unsupported_k() {
+        KmerCounts::new(1);
+    }
+}
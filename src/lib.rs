@@ -1,14 +1,30 @@
-pub fn add(left: usize, right: usize) -> usize {
-    left + right
-}
+//! Bioinformatics primitives: sequence containers, translation and
+//! feature extraction.
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-
-    #[test]
-    fn it_works() {
-        let result = add(2, 2);
-        assert_eq!(result, 4);
-    }
-}
+pub mod align;
+pub mod alphabet;
+pub mod codon;
+pub mod demux;
+pub mod fasta;
+pub mod fasta_index;
+pub mod fastq;
+pub mod gff;
+pub mod io;
+pub mod iupac;
+mod json;
+pub mod kmer;
+pub mod motif;
+pub mod msa;
+pub mod orf;
+pub mod paired;
+pub mod pattern;
+pub mod pcr;
+pub mod primer;
+pub mod progress;
+pub mod qc;
+pub mod read_ops;
+pub mod region;
+pub mod restriction;
+pub mod seq;
+pub mod similarity;
+pub mod umi;
@@ -0,0 +1,347 @@
+//! Nucleotide position weight matrices and their exchange formats.
+
+use std::fmt;
+use std::io::{self, Write};
+
+const BASES: [u8; 4] = *b"ACGT";
+
+/// Errors raised when building a [`Pwm`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MotifError {
+    /// No columns, or no sites.
+    Empty,
+    /// A negative or non-finite value in the column at `position`.
+    InvalidCount { position: usize },
+    /// A column whose counts sum to 0.
+    EmptyColumn { position: usize },
+    /// A site whose length differs from the first site's.
+    SiteLength {
+        index: usize,
+        expected: usize,
+        found: usize,
+    },
+}
+
+impl fmt::Display for MotifError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MotifError::Empty => write!(f, "motif has no columns"),
+            MotifError::InvalidCount { position } => {
+                write!(f, "invalid count in column {}", position + 1)
+            }
+            MotifError::EmptyColumn { position } => {
+                write!(f, "column {} has no counts", position + 1)
+            }
+            MotifError::SiteLength {
+                index,
+                expected,
+                found,
+            } => write!(
+                f,
+                "site {} has length {}, expected {}",
+                index + 1,
+                found,
+                expected
+            ),
+        }
+    }
+}
+
+impl std::error::Error for MotifError {}
+
+/// A position weight matrix over `ACGT`, kept as per-column counts so it
+/// can be written to count-based formats without loss.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Pwm {
+    id: String,
+    name: Option<String>,
+    counts: Vec<[f64; 4]>,
+}
+
+impl Pwm {
+    /// Matrix from per-column `A`, `C`, `G`, `T` counts.
+    pub fn from_counts(id: &str, counts: Vec<[f64; 4]>) -> Result<Pwm, MotifError> {
+        if counts.is_empty() {
+            return Err(MotifError::Empty);
+        }
+        for (position, column) in counts.iter().enumerate() {
+            if column.iter().any(|c| !c.is_finite() || *c < 0.0) {
+                return Err(MotifError::InvalidCount { position });
+            }
+            if column.iter().sum::<f64>() == 0.0 {
+                return Err(MotifError::EmptyColumn { position });
+            }
+        }
+        Ok(Pwm {
+            id: id.to_string(),
+            name: None,
+            counts,
+        })
+    }
+
+    /// Matrix from per-column probabilities, scaled to `nsites` counts.
+    pub fn from_probabilities(
+        id: &str,
+        probabilities: &[[f64; 4]],
+        nsites: f64,
+    ) -> Result<Pwm, MotifError> {
+        let counts = probabilities
+            .iter()
+            .map(|column| column.map(|p| p * nsites))
+            .collect();
+        Pwm::from_counts(id, counts)
+    }
+
+    /// Matrix counted from aligned, equal-length binding sites. Symbols
+    /// other than `ACGTU` are not counted.
+    pub fn from_sites(id: &str, sites: &[&[u8]]) -> Result<Pwm, MotifError> {
+        let expected = sites.first().ok_or(MotifError::Empty)?.len();
+        let mut counts = vec![[0.0; 4]; expected];
+        for (index, site) in sites.iter().enumerate() {
+            if site.len() != expected {
+                return Err(MotifError::SiteLength {
+                    index,
+                    expected,
+                    found: site.len(),
+                });
+            }
+            for (column, &b) in counts.iter_mut().zip(site.iter()) {
+                let slot = match b.to_ascii_uppercase() {
+                    b'A' => 0,
+                    b'C' => 1,
+                    b'G' => 2,
+                    b'T' | b'U' => 3,
+                    _ => continue,
+                };
+                column[slot] += 1.0;
+            }
+        }
+        Pwm::from_counts(id, counts)
+    }
+
+    pub fn with_name(mut self, name: &str) -> Self {
+        self.name = Some(name.to_string());
+        self
+    }
+
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    pub fn name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+
+    pub fn counts(&self) -> &[[f64; 4]] {
+        &self.counts
+    }
+
+    pub fn len(&self) -> usize {
+        self.counts.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.counts.is_empty()
+    }
+
+    /// Number of sites, taken from the largest column total.
+    pub fn nsites(&self) -> f64 {
+        self.counts
+            .iter()
+            .map(|c| c.iter().sum::<f64>())
+            .fold(0.0, f64::max)
+    }
+
+    /// Per-column base probabilities.
+    pub fn probabilities(&self) -> Vec<[f64; 4]> {
+        self.counts
+            .iter()
+            .map(|column| {
+                let total: f64 = column.iter().sum();
+                column.map(|c| c / total)
+            })
+            .collect()
+    }
+
+    /// IUPAC consensus by Cavener's rules: a base above 50% and twice the
+    /// next, else a two-base code for a pair above 75%, else `N`.
+    pub fn consensus(&self) -> String {
+        self.probabilities()
+            .iter()
+            .map(|column| {
+                let mut order = [0, 1, 2, 3];
+                order.sort_by(|&a, &b| column[b].total_cmp(&column[a]));
+                let (first, second) = (column[order[0]], column[order[1]]);
+                if first > 0.5 && first >= 2.0 * second {
+                    BASES[order[0]] as char
+                } else if first + second > 0.75 {
+                    let mut pair = [order[0], order[1]];
+                    pair.sort_unstable();
+                    match pair {
+                        [0, 1] => 'M',
+                        [0, 2] => 'R',
+                        [0, 3] => 'W',
+                        [1, 2] => 'S',
+                        [1, 3] => 'Y',
+                        _ => 'K',
+                    }
+                } else {
+                    'N'
+                }
+            })
+            .collect()
+    }
+}
+
+/// Integral counts without decimals, others with up to three.
+fn format_count(count: f64) -> String {
+    if count.fract() == 0.0 {
+        format!("{:.0}", count)
+    } else {
+        let text = format!("{:.3}", count);
+        text.trim_end_matches('0').trim_end_matches('.').to_string()
+    }
+}
+
+/// Writes motifs as a MEME minimal-format file with uniform background
+/// frequencies, readable by the MEME suite tools (FIMO, Tomtom, ...).
+pub fn write_meme<W: Write>(out: &mut W, motifs: &[Pwm]) -> io::Result<()> {
+    writeln!(out, "MEME version 4\n")?;
+    writeln!(out, "ALPHABET= ACGT\n")?;
+    writeln!(out, "strands: + -\n")?;
+    writeln!(out, "Background letter frequencies")?;
+    writeln!(out, "A 0.25 C 0.25 G 0.25 T 0.25")?;
+    for motif in motifs {
+        write!(out, "\nMOTIF {}", motif.id)?;
+        if let Some(name) = &motif.name {
+            write!(out, " {}", name)?;
+        }
+        writeln!(
+            out,
+            "\nletter-probability matrix: alength= 4 w= {} nsites= {} E= 0",
+            motif.len(),
+            format_count(motif.nsites().round())
+        )?;
+        for column in motif.probabilities() {
+            let row: Vec<String> = column.iter().map(|p| format!("{:.6}", p)).collect();
+            writeln!(out, " {}", row.join("  "))?;
+        }
+    }
+    Ok(())
+}
+
+/// Writes motifs in the JASPAR count-matrix format, one row per base.
+pub fn write_jaspar<W: Write>(out: &mut W, motifs: &[Pwm]) -> io::Result<()> {
+    for motif in motifs {
+        writeln!(out, ">{}\t{}", motif.id, motif.name().unwrap_or(&motif.id))?;
+        for (slot, base) in BASES.iter().enumerate() {
+            let row: Vec<String> = motif
+                .counts
+                .iter()
+                .map(|c| format!("{:>6}", format_count(c[slot])))
+                .collect();
+            writeln!(out, "{}  [{} ]", *base as char, row.join(""))?;
+        }
+    }
+    Ok(())
+}
+
+/// Writes motifs as TRANSFAC matrix entries, with the consensus base of
+/// each position in the last column.
+pub fn write_transfac<W: Write>(out: &mut W, motifs: &[Pwm]) -> io::Result<()> {
+    for motif in motifs {
+        writeln!(out, "AC  {}\nXX", motif.id)?;
+        writeln!(out, "ID  {}\nXX", motif.name().unwrap_or(&motif.id))?;
+        writeln!(out, "P0      A      C      G      T")?;
+        for (i, (column, consensus)) in motif
+            .counts
+            .iter()
+            .zip(motif.consensus().chars())
+            .enumerate()
+        {
+            write!(out, "{:02}", i + 1)?;
+            for count in column {
+                write!(out, " {:>6}", format_count(*count))?;
+            }
+            writeln!(out, "      {}", consensus)?;
+        }
+        writeln!(out, "XX\n//")?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn arnt() -> Pwm {
+        let sites: [&[u8]; 4] = [b"CACGTG", b"CACGTG", b"AACGTG", b"CAGGTG"];
+        Pwm::from_sites("MA0004.1", &sites)
+            .unwrap()
+            .with_name("Arnt")
+    }
+
+    fn written(write: fn(&mut Vec<u8>, &[Pwm]) -> io::Result<()>, motifs: &[Pwm]) -> String {
+        let mut out = Vec::new();
+        write(&mut out, motifs).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn builds_from_sites_and_counts() {
+        let pwm = arnt();
+        assert_eq!(pwm.len(), 6);
+        assert_eq!(pwm.counts()[0], [1.0, 3.0, 0.0, 0.0]);
+        assert_eq!(pwm.nsites(), 4.0);
+        assert_eq!(pwm.consensus(), "CACGTG");
+        assert_eq!(
+            Pwm::from_sites("x", &[b"ACG".as_slice(), b"AC".as_slice()]),
+            Err(MotifError::SiteLength {
+                index: 1,
+                expected: 3,
+                found: 2
+            })
+        );
+        assert_eq!(
+            Pwm::from_counts("x", vec![[1.0, 0.0, 0.0, 0.0], [0.0; 4]]),
+            Err(MotifError::EmptyColumn { position: 1 })
+        );
+        let scaled = Pwm::from_probabilities("x", &[[0.25, 0.25, 0.5, 0.0]], 10.0).unwrap();
+        assert_eq!(scaled.counts()[0], [2.5, 2.5, 5.0, 0.0]);
+        assert_eq!(scaled.consensus(), "N");
+    }
+
+    #[test]
+    fn writes_meme() {
+        let text = written(write_meme, &[arnt()]);
+        assert!(text.starts_with("MEME version 4\n\nALPHABET= ACGT\n"));
+        assert!(text.contains(
+            "\nMOTIF MA0004.1 Arnt\nletter-probability matrix: alength= 4 w= 6 nsites= 4 E= 0\n \
+             0.250000  0.750000  0.000000  0.000000\n"
+        ));
+        assert_eq!(text.lines().count(), 17);
+    }
+
+    #[test]
+    fn writes_jaspar() {
+        let text = written(write_jaspar, &[arnt()]);
+        assert_eq!(
+            text,
+            ">MA0004.1\tArnt\n\
+             A  [     1     4     0     0     0     0 ]\n\
+             C  [     3     0     3     0     0     0 ]\n\
+             G  [     0     0     1     4     0     4 ]\n\
+             T  [     0     0     0     0     4     0 ]\n"
+        );
+    }
+
+    #[test]
+    fn writes_transfac() {
+        let pwm = Pwm::from_counts("M1", vec![[2.5, 0.0, 1.5, 0.0], [0.0, 0.0, 0.0, 4.0]]).unwrap();
+        let text = written(write_transfac, &[pwm.clone(), pwm]);
+        let entry = "AC  M1\nXX\nID  M1\nXX\nP0      A      C      G      T\n\
+                     01    2.5      0    1.5      0      R\n\
+                     02      0      0      0      4      T\nXX\n//\n";
+        assert_eq!(text, entry.repeat(2));
+    }
+}
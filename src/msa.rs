@@ -0,0 +1,185 @@
+//! Multiple sequence alignments: equal-length rows of [`FastaSeq`]s.
+
+use std::collections::HashSet;
+use std::fmt;
+use std::io::BufRead;
+
+use crate::fasta::{FastaError, FastaRecord, FastaSeq};
+
+pub mod concat;
+pub mod conservation;
+pub mod trim;
+
+/// Symbols treated as alignment gaps.
+pub fn is_gap(symbol: u8) -> bool {
+    matches!(symbol, b'-' | b'.')
+}
+
+/// Errors raised while building or combining alignments.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MsaError {
+    /// A row whose length differs from the first row's.
+    RaggedRows {
+        id: String,
+        expected: usize,
+        found: usize,
+    },
+    /// Two rows, or two concatenated partitions, share a name.
+    DuplicateId(String),
+    /// A partition of a concatenation has no columns.
+    EmptyPartition(String),
+    Fasta(FastaError),
+}
+
+impl From<FastaError> for MsaError {
+    fn from(err: FastaError) -> Self {
+        MsaError::Fasta(err)
+    }
+}
+
+impl fmt::Display for MsaError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MsaError::RaggedRows {
+                id,
+                expected,
+                found,
+            } => write!(f, "row {} has {} columns, expected {}", id, found, expected),
+            MsaError::DuplicateId(id) => write!(f, "name {} appears more than once", id),
+            MsaError::EmptyPartition(name) => write!(f, "partition {} has no columns", name),
+            MsaError::Fasta(err) => err.fmt(f),
+        }
+    }
+}
+
+impl std::error::Error for MsaError {}
+
+/// An alignment of uniquely named rows of equal length.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Alignment {
+    rows: Vec<FastaSeq>,
+}
+
+impl Alignment {
+    pub fn new(rows: Vec<FastaSeq>) -> Result<Alignment, MsaError> {
+        let mut ids = HashSet::new();
+        for row in &rows {
+            if row.len() != rows[0].len() {
+                return Err(MsaError::RaggedRows {
+                    id: row.id().to_string(),
+                    expected: rows[0].len(),
+                    found: row.len(),
+                });
+            }
+            if !ids.insert(row.id()) {
+                return Err(MsaError::DuplicateId(row.id().to_string()));
+            }
+        }
+        Ok(Alignment { rows })
+    }
+
+    /// Parses an aligned FASTA stream.
+    pub fn from_reader<R: BufRead>(reader: R) -> Result<Alignment, MsaError> {
+        Alignment::new(FastaRecord::from_reader(reader)?.seqs().to_vec())
+    }
+
+    pub fn rows(&self) -> &[FastaSeq] {
+        &self.rows
+    }
+
+    pub fn num_rows(&self) -> usize {
+        self.rows.len()
+    }
+
+    /// Number of columns.
+    pub fn width(&self) -> usize {
+        self.rows.first().map_or(0, FastaSeq::len)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rows.is_empty()
+    }
+
+    pub fn get(&self, id: &str) -> Option<&FastaSeq> {
+        self.rows.iter().find(|row| row.id() == id)
+    }
+
+    /// Symbols of column `index`, one per row.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is not below [`width`](Self::width).
+    pub fn column(&self, index: usize) -> Vec<u8> {
+        self.rows
+            .iter()
+            .map(|row| row.sequence().as_bytes()[index])
+            .collect()
+    }
+
+    /// Alignment of the given columns, in the given order. Rows keep
+    /// their identifiers, descriptions and sequence types.
+    ///
+    /// # Panics
+    ///
+    /// Panics if any index is not below [`width`](Self::width).
+    pub fn select_columns(&self, columns: &[usize]) -> Result<Alignment, MsaError> {
+        let rows = self
+            .rows
+            .iter()
+            .map(|row| {
+                let bytes = row.sequence().as_bytes();
+                let sequence: String = columns.iter().map(|&i| bytes[i] as char).collect();
+                FastaSeq::builder()
+                    .id(row.id())
+                    .description(row.description())
+                    .sequence(&sequence)
+                    .seq_type(row.seq_type())
+                    .alphabet(row.alphabet())
+                    .build()
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(Alignment { rows })
+    }
+
+    pub fn to_record(&self) -> FastaRecord {
+        FastaRecord::new(self.rows.clone())
+    }
+}
+
+/// Writes the alignment as aligned FASTA.
+impl fmt::Display for Alignment {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.rows.iter().try_for_each(|row| write!(f, "{}", row))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn validates_rows() {
+        let aln = Alignment::from_reader(">a\nAC-T\n>b\nA-GT\n".as_bytes()).unwrap();
+        assert_eq!((aln.num_rows(), aln.width()), (2, 4));
+        assert_eq!(aln.column(1), b"C-");
+        assert_eq!(aln.get("b").unwrap().sequence(), "A-GT");
+        assert_eq!(aln.to_string(), ">a\nAC-T\n>b\nA-GT\n");
+        assert_eq!(
+            aln.select_columns(&[3, 0]).unwrap().to_string(),
+            ">a\nTA\n>b\nTA\n"
+        );
+        assert_eq!(
+            Alignment::from_reader(">a\nACGT\n>b\nAC\n".as_bytes()),
+            Err(MsaError::RaggedRows {
+                id: "b".to_string(),
+                expected: 4,
+                found: 2
+            })
+        );
+        assert_eq!(
+            Alignment::from_reader(">a\nAC\n>a\nAC\n".as_bytes()),
+            Err(MsaError::DuplicateId("a".to_string()))
+        );
+        assert_eq!(Alignment::default().width(), 0);
+    }
+}
@@ -0,0 +1,163 @@
+//! Concatenation of gene alignments into a supermatrix for phylogenomics.
+
+use std::collections::{HashMap, HashSet};
+
+use crate::alphabet::SeqType;
+use crate::fasta::FastaSeq;
+use crate::msa::{Alignment, MsaError};
+
+/// The columns one gene occupies in a supermatrix, 0-based and
+/// half-open.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Partition {
+    pub name: String,
+    pub start: usize,
+    pub end: usize,
+    pub seq_type: SeqType,
+}
+
+/// Concatenated alignment and the partition of each source gene.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Supermatrix {
+    pub alignment: Alignment,
+    pub partitions: Vec<Partition>,
+}
+
+/// Joins named gene alignments column-wise, matching rows by identifier.
+///
+/// Taxa are ordered by first appearance; a taxon missing from a gene gets
+/// a row of gaps there.
+pub fn concatenate(genes: &[(&str, &Alignment)]) -> Result<Supermatrix, MsaError> {
+    let mut names = HashSet::new();
+    let mut taxa: Vec<&str> = Vec::new();
+    let mut seen = HashSet::new();
+    for &(name, alignment) in genes {
+        if !names.insert(name) {
+            return Err(MsaError::DuplicateId(name.to_string()));
+        }
+        if alignment.width() == 0 {
+            return Err(MsaError::EmptyPartition(name.to_string()));
+        }
+        for row in alignment.rows() {
+            if seen.insert(row.id()) {
+                taxa.push(row.id());
+            }
+        }
+    }
+
+    let mut sequences: HashMap<&str, String> = taxa.iter().map(|&t| (t, String::new())).collect();
+    let mut partitions = Vec::with_capacity(genes.len());
+    let mut start = 0;
+    for &(name, alignment) in genes {
+        let width = alignment.width();
+        for &taxon in &taxa {
+            let sequence = sequences.entry(taxon).or_default();
+            match alignment.get(taxon) {
+                Some(row) => sequence.push_str(row.sequence()),
+                None => sequence.extend(std::iter::repeat_n('-', width)),
+            }
+        }
+        partitions.push(Partition {
+            name: name.to_string(),
+            start,
+            end: start + width,
+            seq_type: alignment.rows()[0].seq_type(),
+        });
+        start += width;
+    }
+
+    let rows = taxa
+        .iter()
+        .map(|&taxon| {
+            FastaSeq::builder()
+                .id(taxon)
+                .sequence(&sequences[taxon])
+                .build()
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(Supermatrix {
+        alignment: Alignment::new(rows)?,
+        partitions,
+    })
+}
+
+impl Supermatrix {
+    /// RAxML partition file, one `MODEL, name = start-end` line per gene
+    /// in 1-based inclusive columns. Nucleotide genes get `DNA` and
+    /// protein genes `WAG`; edit the models as needed.
+    pub fn to_raxml(&self) -> String {
+        self.partitions
+            .iter()
+            .map(|p| {
+                let model = match p.seq_type {
+                    SeqType::Protein => "WAG",
+                    SeqType::Dna | SeqType::Rna => "DNA",
+                };
+                format!("{}, {} = {}-{}\n", model, p.name, p.start + 1, p.end)
+            })
+            .collect()
+    }
+
+    /// NEXUS `sets` block with one `charset` per gene.
+    pub fn to_nexus(&self) -> String {
+        let mut text = String::from("#NEXUS\nbegin sets;\n");
+        for p in &self.partitions {
+            text.push_str(&format!(
+                "    charset {} = {}-{};\n",
+                p.name,
+                p.start + 1,
+                p.end
+            ));
+        }
+        text.push_str("end;\n");
+        text
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn alignment(text: &str) -> Alignment {
+        Alignment::from_reader(text.as_bytes()).unwrap()
+    }
+
+    #[test]
+    fn concatenates_and_fills_missing_taxa() {
+        let gene1 = alignment(">human\nACGT\n>mouse\nAC-T\n");
+        let gene2 = alignment(">mouse\nMEF\n>fly\nMQF\n");
+        let matrix = concatenate(&[("cox1", &gene1), ("rbcl", &gene2)]).unwrap();
+        assert_eq!(
+            matrix.alignment.to_string(),
+            ">human\nACGT---\n>mouse\nAC-TMEF\n>fly\n----MQF\n"
+        );
+        assert_eq!(
+            matrix.partitions[1],
+            Partition {
+                name: "rbcl".to_string(),
+                start: 4,
+                end: 7,
+                seq_type: SeqType::Protein
+            }
+        );
+        assert_eq!(matrix.to_raxml(), "DNA, cox1 = 1-4\nWAG, rbcl = 5-7\n");
+        assert_eq!(
+            matrix.to_nexus(),
+            "#NEXUS\nbegin sets;\n    charset cox1 = 1-4;\n    charset rbcl = 5-7;\nend;\n"
+        );
+    }
+
+    #[test]
+    fn rejects_bad_partitions() {
+        let gene = alignment(">a\nACGT\n");
+        assert_eq!(
+            concatenate(&[("g", &gene), ("g", &gene)]),
+            Err(MsaError::DuplicateId("g".to_string()))
+        );
+        assert_eq!(
+            concatenate(&[("g", &gene), ("empty", &Alignment::default())]),
+            Err(MsaError::EmptyPartition("empty".to_string()))
+        );
+        assert!(concatenate(&[]).unwrap().alignment.is_empty());
+    }
+}
@@ -0,0 +1,149 @@
+//! Per-column and windowed conservation of an alignment.
+//!
+//! Low-entropy or high-scoring stretches of a profile mark conserved
+//! blocks, such as primer sites or domain cores; sharp changes mark their
+//! boundaries.
+
+use crate::align::matrix::SubstitutionMatrix;
+use crate::msa::{is_gap, Alignment};
+use crate::seq::shannon_entropy;
+
+/// Mean of a profile over one window, 0-based and half-open.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ProfileWindow {
+    pub start: usize,
+    pub end: usize,
+    pub mean: f64,
+}
+
+impl Alignment {
+    /// Fraction of rows with a gap in column `index`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is not below [`width`](Self::width).
+    pub fn column_gap_fraction(&self, index: usize) -> f64 {
+        let column = self.column(index);
+        if column.is_empty() {
+            return 0.0;
+        }
+        column.iter().filter(|&&b| is_gap(b)).count() as f64 / column.len() as f64
+    }
+
+    /// Shannon entropy, in bits, of the residues in column `index`. Gaps
+    /// are ignored, so check [`column_gap_fraction`](Self::column_gap_fraction)
+    /// as well.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is not below [`width`](Self::width).
+    pub fn column_entropy(&self, index: usize) -> f64 {
+        let residues: Vec<u8> = self
+            .column(index)
+            .into_iter()
+            .filter(|&b| !is_gap(b))
+            .collect();
+        shannon_entropy(&residues)
+    }
+
+    /// Sum of `matrix` scores over all pairs of rows with residues in
+    /// column `index`; pairs involving a gap are skipped.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is not below [`width`](Self::width).
+    pub fn column_sum_of_pairs(&self, index: usize, matrix: &SubstitutionMatrix) -> i64 {
+        let residues: Vec<u8> = self
+            .column(index)
+            .into_iter()
+            .filter(|&b| !is_gap(b))
+            .collect();
+        residues
+            .iter()
+            .enumerate()
+            .flat_map(|(i, &a)| residues[i + 1..].iter().map(move |&b| (a, b)))
+            .map(|(a, b)| i64::from(matrix.score(a, b)))
+            .sum()
+    }
+
+    /// [`column_entropy`](Self::column_entropy) of every column.
+    pub fn entropy_profile(&self) -> Vec<f64> {
+        (0..self.width()).map(|i| self.column_entropy(i)).collect()
+    }
+
+    /// Sum-of-pairs score of every column divided by the number of row
+    /// pairs, so gapped pairs pull a column towards 0.
+    pub fn sum_of_pairs_profile(&self, matrix: &SubstitutionMatrix) -> Vec<f64> {
+        let rows = self.num_rows();
+        let pairs = (rows * rows.saturating_sub(1) / 2).max(1) as f64;
+        (0..self.width())
+            .map(|i| self.column_sum_of_pairs(i, matrix) as f64 / pairs)
+            .collect()
+    }
+}
+
+/// Means of `profile` over windows of `size` columns starting every
+/// `step` columns. A trailing window shorter than `size` is not produced.
+///
+/// # Panics
+///
+/// Panics if `size` or `step` is 0.
+pub fn sliding_mean(profile: &[f64], size: usize, step: usize) -> Vec<ProfileWindow> {
+    assert!(size > 0, "window size must be non-zero");
+    assert!(step > 0, "window step must be non-zero");
+    (0..profile.len().saturating_sub(size - 1))
+        .step_by(step)
+        .map(|start| ProfileWindow {
+            start,
+            end: start + size,
+            mean: profile[start..start + size].iter().sum::<f64>() / size as f64,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn alignment() -> Alignment {
+        Alignment::from_reader(">a\nLEF-\n>b\nLQY-\n>c\nLEWE\n".as_bytes()).unwrap()
+    }
+
+    #[test]
+    fn scores_columns() {
+        let aln = alignment();
+        let entropy = aln.entropy_profile();
+        assert_eq!(entropy[0], 0.0);
+        assert!((entropy[1] - 0.9183).abs() < 1e-4);
+        assert!((entropy[2] - 3f64.log2()).abs() < 1e-9);
+        assert_eq!(entropy[3], 0.0);
+        assert!((aln.column_gap_fraction(3) - 2.0 / 3.0).abs() < 1e-9);
+
+        let blosum = SubstitutionMatrix::blosum62();
+        assert_eq!(aln.column_sum_of_pairs(1, &blosum), 9);
+        assert_eq!(aln.sum_of_pairs_profile(&blosum), vec![4.0, 3.0, 2.0, 0.0]);
+        assert!(Alignment::default().entropy_profile().is_empty());
+    }
+
+    #[test]
+    fn averages_windows() {
+        let windows = sliding_mean(&[1.0, 3.0, 2.0, 0.0, 4.0], 2, 2);
+        assert_eq!(
+            windows,
+            vec![
+                ProfileWindow {
+                    start: 0,
+                    end: 2,
+                    mean: 2.0
+                },
+                ProfileWindow {
+                    start: 2,
+                    end: 4,
+                    mean: 1.0
+                },
+            ]
+        );
+        assert_eq!(sliding_mean(&[1.0, 2.0, 3.0], 3, 1).len(), 1);
+        assert!(sliding_mean(&[1.0], 2, 1).is_empty());
+    }
+}
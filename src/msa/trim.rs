@@ -0,0 +1,118 @@
+//! Removal of gappy, ambiguous and highly variable alignment columns.
+
+use crate::alphabet::SeqType;
+use crate::msa::{is_gap, Alignment, MsaError};
+use crate::seq::shannon_entropy;
+
+/// Column filters for [`trim`]. A column is kept only if it passes every
+/// filter that is set.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TrimOptions {
+    /// Largest fraction of rows that may be gaps in a kept column.
+    pub max_gap_fraction: f64,
+    /// Count unknown symbols (`N` in nucleotides, `X` in proteins, `?`
+    /// anywhere) as gaps.
+    pub ambiguous_as_gaps: bool,
+    /// Largest Shannon entropy, in bits, of the residues of a kept
+    /// column; `None` disables the filter. Saturated columns of unrelated
+    /// symbols carry little phylogenetic signal.
+    pub max_entropy: Option<f64>,
+}
+
+impl Default for TrimOptions {
+    fn default() -> Self {
+        TrimOptions {
+            max_gap_fraction: 0.5,
+            ambiguous_as_gaps: true,
+            max_entropy: None,
+        }
+    }
+}
+
+/// A trimmed alignment and, for each of its columns, the index of that
+/// column in the original alignment.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Trimmed {
+    pub alignment: Alignment,
+    pub columns: Vec<usize>,
+}
+
+fn is_unknown(symbol: u8, seq_type: SeqType) -> bool {
+    match symbol.to_ascii_uppercase() {
+        b'?' => true,
+        b'N' => seq_type != SeqType::Protein,
+        b'X' => seq_type == SeqType::Protein,
+        _ => false,
+    }
+}
+
+/// Filters the columns of `alignment`, keeping their order.
+pub fn trim(alignment: &Alignment, options: &TrimOptions) -> Result<Trimmed, MsaError> {
+    let rows = alignment.num_rows();
+    let types: Vec<SeqType> = alignment.rows().iter().map(|r| r.seq_type()).collect();
+    let columns: Vec<usize> = (0..alignment.width())
+        .filter(|&index| {
+            let column = alignment.column(index);
+            let residues: Vec<u8> = column
+                .iter()
+                .zip(&types)
+                .filter(|&(&b, &seq_type)| {
+                    !(is_gap(b) || options.ambiguous_as_gaps && is_unknown(b, seq_type))
+                })
+                .map(|(&b, _)| b)
+                .collect();
+            let gaps = rows - residues.len();
+            gaps as f64 <= options.max_gap_fraction * rows as f64
+                && options
+                    .max_entropy
+                    .is_none_or(|max| shannon_entropy(&residues) <= max)
+        })
+        .collect();
+    Ok(Trimmed {
+        alignment: alignment.select_columns(&columns)?,
+        columns,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn alignment(text: &str) -> Alignment {
+        Alignment::from_reader(text.as_bytes()).unwrap()
+    }
+
+    #[test]
+    fn removes_gappy_and_ambiguous_columns() {
+        let aln = alignment(">a\nA-CGTA\n>b\nA-CN-A\n>c\nAT-N-A\n>d\nATCG-A\n");
+        let trimmed = trim(&aln, &TrimOptions::default()).unwrap();
+        assert_eq!(trimmed.columns, vec![0, 1, 2, 3, 5]);
+        assert_eq!(trimmed.alignment.get("c").unwrap().sequence(), "AT-NA");
+
+        let strict = TrimOptions {
+            max_gap_fraction: 0.25,
+            ..TrimOptions::default()
+        };
+        assert_eq!(trim(&aln, &strict).unwrap().columns, vec![0, 2, 5]);
+
+        let keep_n = TrimOptions {
+            max_gap_fraction: 0.0,
+            ambiguous_as_gaps: false,
+            max_entropy: None,
+        };
+        assert_eq!(trim(&aln, &keep_n).unwrap().columns, vec![0, 3, 5]);
+    }
+
+    #[test]
+    fn removes_variable_columns() {
+        let aln = alignment(">a\nMACL\n>b\nMACW\n>c\nMGDY\n>d\nMGEF\n");
+        let options = TrimOptions {
+            max_entropy: Some(1.0),
+            ..TrimOptions::default()
+        };
+        let trimmed = trim(&aln, &options).unwrap();
+        assert_eq!(trimmed.columns, vec![0, 1]);
+        assert_eq!(trimmed.alignment.get("d").unwrap().sequence(), "MG");
+        assert_eq!(trimmed.alignment.rows()[0].seq_type(), SeqType::Protein);
+    }
+}
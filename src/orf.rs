@@ -0,0 +1,273 @@
+//! Open reading frame discovery and translation to protein.
+
+use std::io::{self, Write};
+
+use crate::alphabet::{infer_alphabet, SeqType};
+use crate::codon::{translate, GeneticCode};
+use crate::fasta::{FastaRecord, FastaSeq};
+use crate::gff::{self, GffFeature};
+use crate::seq::{reverse_complement, Strand};
+
+/// Where an ORF was found in its source sequence.
+///
+/// `start` and `end` are 0-based, half-open forward-strand coordinates of the
+/// ORF including its stop codon, whichever strand it lies on.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct OrfProvenance {
+    pub source_id: String,
+    pub start: usize,
+    pub end: usize,
+    pub strand: Strand,
+    /// Reading frame (0, 1 or 2) counted from the 5' end of `strand`.
+    pub frame: u8,
+}
+
+/// A translated ORF: the protein plus where it came from.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Orf {
+    pub protein: FastaSeq,
+    pub provenance: OrfProvenance,
+}
+
+impl Orf {
+    /// The ORF as a GFF3 `CDS` feature on its source sequence.
+    pub fn to_gff_feature(&self) -> GffFeature {
+        let prov = &self.provenance;
+        GffFeature {
+            seqid: prov.source_id.clone(),
+            source: "bio_oxide".to_string(),
+            feature_type: "CDS".to_string(),
+            start: prov.start + 1,
+            end: prov.end,
+            score: None,
+            strand: Some(prov.strand),
+            phase: Some(0),
+            attributes: vec![("ID".to_string(), self.protein.id().to_string())],
+        }
+    }
+}
+
+/// Parameters for [`find_orfs`].
+#[derive(Debug, Clone)]
+pub struct OrfOptions {
+    /// Minimum protein length in residues, not counting the stop.
+    pub min_protein_len: usize,
+    pub genetic_code: GeneticCode,
+    /// Also search the reverse complement.
+    pub both_strands: bool,
+}
+
+impl Default for OrfOptions {
+    fn default() -> Self {
+        OrfOptions {
+            min_protein_len: 30,
+            genetic_code: GeneticCode::STANDARD,
+            both_strands: true,
+        }
+    }
+}
+
+/// Finds start-to-stop ORFs in a nucleotide sequence.
+///
+/// In each frame the first start codon after a stop opens an ORF, so nested
+/// in-frame starts are not reported separately. ORFs running off the end of
+/// the sequence are skipped. Proteins are named `<source id>_orf<n>`,
+/// numbered in order of their forward-strand start. Protein input yields no
+/// ORFs.
+pub fn find_orfs(seq: &FastaSeq, options: &OrfOptions) -> Vec<Orf> {
+    if seq.seq_type() == SeqType::Protein {
+        return Vec::new();
+    }
+    let forward = seq.sequence().as_bytes();
+    let mut hits = scan_strand(forward, Strand::Forward, options);
+    if options.both_strands {
+        let reverse = reverse_complement(forward);
+        hits.extend(scan_strand(&reverse, Strand::Reverse, options));
+    }
+    hits.sort_by_key(|hit| (hit.start, hit.end, hit.strand == Strand::Reverse));
+
+    hits.into_iter()
+        .enumerate()
+        .map(|(i, hit)| {
+            let id = format!("{}_orf{}", seq.id(), i + 1);
+            Orf {
+                // The source ID was validated when it was built, so the
+                // derived ID is valid as well.
+                protein: FastaSeq::builder()
+                    .id(&id)
+                    .sequence(&hit.protein)
+                    .alphabet(infer_alphabet(&hit.protein, SeqType::Protein))
+                    .build()
+                    .expect("ORF protein fields are valid"),
+                provenance: OrfProvenance {
+                    source_id: seq.id().to_string(),
+                    start: hit.start,
+                    end: hit.end,
+                    strand: hit.strand,
+                    frame: hit.frame,
+                },
+            }
+        })
+        .collect()
+}
+
+/// Runs [`find_orfs`] over every sequence of a record set.
+pub fn find_orfs_in_record(record: &FastaRecord, options: &OrfOptions) -> Vec<Orf> {
+    record
+        .seqs()
+        .iter()
+        .flat_map(|seq| find_orfs(seq, options))
+        .collect()
+}
+
+struct OrfHit {
+    start: usize,
+    end: usize,
+    strand: Strand,
+    frame: u8,
+    protein: String,
+}
+
+fn scan_strand(seq: &[u8], strand: Strand, options: &OrfOptions) -> Vec<OrfHit> {
+    let code = &options.genetic_code;
+    let len = seq.len();
+    let mut hits = Vec::new();
+    for frame in 0..3 {
+        let mut open: Option<usize> = None;
+        let mut pos = frame;
+        while pos + 3 <= len {
+            let codon = &seq[pos..pos + 3];
+            if code.is_stop(codon) {
+                if let Some(start) = open.take() {
+                    let residues = (pos - start) / 3;
+                    if residues >= options.min_protein_len {
+                        let mut protein = translate(&seq[start..pos], code);
+                        // Alternative start codons still initiate with Met.
+                        protein[0] = b'M';
+                        let (start, end) = match strand {
+                            Strand::Forward => (start, pos + 3),
+                            Strand::Reverse => (len - pos - 3, len - start),
+                        };
+                        hits.push(OrfHit {
+                            start,
+                            end,
+                            strand,
+                            frame: frame as u8,
+                            protein: String::from_utf8_lossy(&protein).into_owned(),
+                        });
+                    }
+                }
+            } else if open.is_none() && code.is_start(codon) {
+                open = Some(pos);
+            }
+            pos += 3;
+        }
+    }
+    hits
+}
+
+/// Writes the ORFs as GFF3 `CDS` features on their source sequences.
+pub fn write_gff<W: Write>(writer: &mut W, orfs: &[Orf]) -> io::Result<()> {
+    let features: Vec<GffFeature> = orfs.iter().map(Orf::to_gff_feature).collect();
+    gff::write_gff3(writer, &features)
+}
+
+/// Writes the translated proteins as FASTA.
+pub fn write_protein_fasta<W: Write>(writer: &mut W, orfs: &[Orf]) -> io::Result<()> {
+    for orf in orfs {
+        write!(writer, "{}", orf.protein)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::alphabet::Alphabet;
+
+    fn dna(id: &str, seq: &str) -> FastaSeq {
+        FastaSeq::builder()
+            .id(id)
+            .sequence(seq)
+            .alphabet(Alphabet::Dna)
+            .build()
+            .unwrap()
+    }
+
+    fn opts(min_protein_len: usize) -> OrfOptions {
+        OrfOptions {
+            min_protein_len,
+            ..OrfOptions::default()
+        }
+    }
+
+    #[test]
+    fn finds_forward_orf_with_provenance() {
+        let orfs = find_orfs(&dna("contig1", "CCATGAAATTTTAGCC"), &opts(2));
+        assert_eq!(orfs.len(), 1);
+        let orf = &orfs[0];
+        assert_eq!(orf.protein.id(), "contig1_orf1");
+        assert_eq!(orf.protein.sequence(), "MKF");
+        assert_eq!(
+            orf.provenance,
+            OrfProvenance {
+                source_id: "contig1".to_string(),
+                start: 2,
+                end: 14,
+                strand: Strand::Forward,
+                frame: 2,
+            }
+        );
+    }
+
+    #[test]
+    fn maps_reverse_strand_coordinates() {
+        // Reverse complement of CCATGAAATTTTAGCC.
+        let orfs = find_orfs(&dna("contig1", "GGCTAAAATTTCATGG"), &opts(2));
+        assert_eq!(orfs.len(), 1);
+        let prov = &orfs[0].provenance;
+        assert_eq!((prov.start, prov.end), (2, 14));
+        assert_eq!(prov.strand, Strand::Reverse);
+        assert_eq!(orfs[0].protein.sequence(), "MKF");
+    }
+
+    #[test]
+    fn ambiguous_codons_widen_the_protein_alphabet() {
+        let orfs = find_orfs(&dna("s", "ATGAANTTTTAG"), &opts(2));
+        let protein = &orfs[0].protein;
+        assert_eq!(protein.sequence(), "MXF");
+        assert_eq!(protein.alphabet(), Alphabet::ProteinExtended);
+        assert!(FastaSeq::builder()
+            .id(protein.id())
+            .sequence(protein.sequence())
+            .alphabet(protein.alphabet())
+            .strict(true)
+            .build()
+            .is_ok());
+        let orfs = find_orfs(&dna("s", "ATGAAATTTTAG"), &opts(2));
+        assert_eq!(orfs[0].protein.alphabet(), Alphabet::Protein);
+    }
+
+    #[test]
+    fn respects_minimum_length_and_open_ends() {
+        assert!(find_orfs(&dna("s", "ATGAAATAG"), &opts(3)).is_empty());
+        assert!(find_orfs(&dna("s", "ATGAAAAAAAAA"), &opts(1)).is_empty());
+    }
+
+    #[test]
+    fn writes_gff_and_fasta() {
+        let orfs = find_orfs(&dna("contig1", "CCATGAAATTTTAGCC"), &opts(2));
+        let mut gff_out = Vec::new();
+        write_gff(&mut gff_out, &orfs).unwrap();
+        assert_eq!(
+            String::from_utf8(gff_out).unwrap(),
+            "##gff-version 3\ncontig1\tbio_oxide\tCDS\t3\t14\t.\t+\t0\tID=contig1_orf1\n"
+        );
+        let mut fasta_out = Vec::new();
+        write_protein_fasta(&mut fasta_out, &orfs).unwrap();
+        assert_eq!(
+            String::from_utf8(fasta_out).unwrap(),
+            ">contig1_orf1\nMKF\n"
+        );
+    }
+}
@@ -0,0 +1,298 @@
+//! Paired-end FASTQ: pairing R1/R2 streams, interleaving and
+//! de-interleaving.
+
+use std::fmt;
+use std::io::{self, BufRead, Write};
+
+use crate::fastq::{FastqError, FastqReader, FastqSeq};
+
+/// Errors raised while pairing reads.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PairError {
+    /// Reading or writing FASTQ failed.
+    Fastq(FastqError),
+    /// The reads of the pair at 0-based index `pair` are not mates, or are
+    /// in the wrong order.
+    MateMismatch {
+        pair: u64,
+        first: String,
+        second: String,
+    },
+    /// One input ran out of reads before the other; `id` is the first
+    /// read without a mate.
+    UnpairedRead { pair: u64, id: String },
+}
+
+impl From<FastqError> for PairError {
+    fn from(err: FastqError) -> Self {
+        PairError::Fastq(err)
+    }
+}
+
+impl From<io::Error> for PairError {
+    fn from(err: io::Error) -> Self {
+        PairError::Fastq(err.into())
+    }
+}
+
+impl fmt::Display for PairError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PairError::Fastq(err) => err.fmt(f),
+            PairError::MateMismatch {
+                pair,
+                first,
+                second,
+            } => write!(
+                f,
+                "reads {} and {} of pair {} are not mates in R1/R2 order",
+                first, second, pair
+            ),
+            PairError::UnpairedRead { pair, id } => {
+                write!(f, "read {} at pair {} has no mate", id, pair)
+            }
+        }
+    }
+}
+
+impl std::error::Error for PairError {}
+
+/// Read name without a trailing `/1` or `/2` mate suffix.
+pub fn mate_name(id: &str) -> &str {
+    id.strip_suffix("/1")
+        .or_else(|| id.strip_suffix("/2"))
+        .unwrap_or(id)
+}
+
+/// Mate number from a `/1` or `/2` suffix or, for Illumina 1.8+ headers,
+/// the `1:N:0:...` description; `None` if neither says.
+pub fn mate_number(read: &FastqSeq) -> Option<u8> {
+    let id = read.id();
+    if id.ends_with("/1") {
+        return Some(1);
+    }
+    if id.ends_with("/2") {
+        return Some(2);
+    }
+    match read.description().as_bytes() {
+        [n @ (b'1' | b'2'), b':', ..] => Some(n - b'0'),
+        _ => None,
+    }
+}
+
+/// Checks that `first` and `second` are mates, in that order.
+fn check_mates(pair: u64, first: &FastqSeq, second: &FastqSeq) -> Result<(), PairError> {
+    let same_name = mate_name(first.id()) == mate_name(second.id());
+    let in_order = !matches!(
+        (mate_number(first), mate_number(second)),
+        (Some(a), Some(b)) if (a, b) != (1, 2)
+    );
+    if same_name && in_order {
+        Ok(())
+    } else {
+        Err(PairError::MateMismatch {
+            pair,
+            first: first.id().to_string(),
+            second: second.id().to_string(),
+        })
+    }
+}
+
+/// Pairs from separate R1 and R2 readers, checked to be mates.
+pub struct PairedReads<R1, R2> {
+    r1: FastqReader<R1>,
+    r2: FastqReader<R2>,
+    pairs: u64,
+    done: bool,
+}
+
+impl<R1: BufRead, R2: BufRead> PairedReads<R1, R2> {
+    pub fn new(r1: FastqReader<R1>, r2: FastqReader<R2>) -> Self {
+        PairedReads {
+            r1,
+            r2,
+            pairs: 0,
+            done: false,
+        }
+    }
+
+    fn next_pair(&mut self) -> Result<Option<(FastqSeq, FastqSeq)>, PairError> {
+        let pair = self.pairs;
+        match (self.r1.next().transpose()?, self.r2.next().transpose()?) {
+            (None, None) => Ok(None),
+            (Some(read), None) | (None, Some(read)) => Err(PairError::UnpairedRead {
+                pair,
+                id: read.id().to_string(),
+            }),
+            (Some(first), Some(second)) => {
+                check_mates(pair, &first, &second)?;
+                self.pairs += 1;
+                Ok(Some((first, second)))
+            }
+        }
+    }
+}
+
+impl<R1: BufRead, R2: BufRead> Iterator for PairedReads<R1, R2> {
+    type Item = Result<(FastqSeq, FastqSeq), PairError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        let pair = self.next_pair();
+        if !matches!(pair, Ok(Some(_))) {
+            self.done = true;
+        }
+        pair.transpose()
+    }
+}
+
+/// Pairs from an interleaved reader, where each R1 read is directly
+/// followed by its R2 mate.
+pub struct InterleavedPairs<R> {
+    reader: FastqReader<R>,
+    pairs: u64,
+    done: bool,
+}
+
+impl<R: BufRead> InterleavedPairs<R> {
+    pub fn new(reader: FastqReader<R>) -> Self {
+        InterleavedPairs {
+            reader,
+            pairs: 0,
+            done: false,
+        }
+    }
+
+    fn next_pair(&mut self) -> Result<Option<(FastqSeq, FastqSeq)>, PairError> {
+        let pair = self.pairs;
+        let Some(first) = self.reader.next().transpose()? else {
+            return Ok(None);
+        };
+        let Some(second) = self.reader.next().transpose()? else {
+            return Err(PairError::UnpairedRead {
+                pair,
+                id: first.id().to_string(),
+            });
+        };
+        check_mates(pair, &first, &second)?;
+        self.pairs += 1;
+        Ok(Some((first, second)))
+    }
+}
+
+impl<R: BufRead> Iterator for InterleavedPairs<R> {
+    type Item = Result<(FastqSeq, FastqSeq), PairError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        let pair = self.next_pair();
+        if !matches!(pair, Ok(Some(_))) {
+            self.done = true;
+        }
+        pair.transpose()
+    }
+}
+
+/// Writes R1/R2 mates alternately to `out` and returns the number of
+/// pairs. Stops at the first mismatched pair; pairs before it have been
+/// written.
+pub fn interleave<R1: BufRead, R2: BufRead, W: Write>(
+    r1: FastqReader<R1>,
+    r2: FastqReader<R2>,
+    out: &mut W,
+) -> Result<u64, PairError> {
+    let mut pairs = 0;
+    for pair in PairedReads::new(r1, r2) {
+        let (first, second) = pair?;
+        write!(out, "{}{}", first, second)?;
+        pairs += 1;
+    }
+    Ok(pairs)
+}
+
+/// Splits an interleaved stream into R1 and R2 outputs and returns the
+/// number of pairs.
+pub fn deinterleave<R: BufRead, W1: Write, W2: Write>(
+    reader: FastqReader<R>,
+    r1: &mut W1,
+    r2: &mut W2,
+) -> Result<u64, PairError> {
+    let mut pairs = 0;
+    for pair in InterleavedPairs::new(reader) {
+        let (first, second) = pair?;
+        write!(r1, "{}", first)?;
+        write!(r2, "{}", second)?;
+        pairs += 1;
+    }
+    Ok(pairs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const R1: &str = "@p1/1\nACGT\n+\nIIII\n@p2/1\nGG\n+\nII\n";
+    const R2: &str = "@p1/2\nTTTT\n+\n####\n@p2/2\nCC\n+\n##\n";
+
+    fn reader(text: &str) -> FastqReader<&[u8]> {
+        FastqReader::new(text.as_bytes())
+    }
+
+    #[test]
+    fn interleaves_and_splits_round_trip() {
+        let mut interleaved = Vec::new();
+        assert_eq!(interleave(reader(R1), reader(R2), &mut interleaved), Ok(2));
+        let text = String::from_utf8(interleaved).unwrap();
+        assert!(text.starts_with("@p1/1\nACGT\n+\nIIII\n@p1/2\nTTTT\n"));
+
+        let (mut r1, mut r2) = (Vec::new(), Vec::new());
+        assert_eq!(deinterleave(reader(&text), &mut r1, &mut r2), Ok(2));
+        assert_eq!(String::from_utf8(r1).unwrap(), R1);
+        assert_eq!(String::from_utf8(r2).unwrap(), R2);
+    }
+
+    #[test]
+    fn reads_illumina_mate_descriptions() {
+        let r1 = "@frag 1:N:0:ACGT\nA\n+\nI\n";
+        let r2 = "@frag 2:N:0:ACGT\nC\n+\nI\n";
+        let pairs: Vec<_> = PairedReads::new(reader(r1), reader(r2)).collect();
+        assert_eq!(pairs.len(), 1);
+        assert!(pairs[0].is_ok());
+        assert!(matches!(
+            PairedReads::new(reader(r2), reader(r1)).next(),
+            Some(Err(PairError::MateMismatch { pair: 0, .. }))
+        ));
+    }
+
+    #[test]
+    fn rejects_mismatched_and_unpaired_reads() {
+        let swapped = "@p1/1\nA\n+\nI\n@p2/2\nC\n+\nI\n";
+        assert_eq!(
+            deinterleave(reader(swapped), &mut Vec::new(), &mut Vec::new()),
+            Err(PairError::MateMismatch {
+                pair: 0,
+                first: "p1/1".to_string(),
+                second: "p2/2".to_string()
+            })
+        );
+        let mut out = Vec::new();
+        assert_eq!(
+            interleave(reader(R1), reader("@p1/2\nTTTT\n+\n####\n"), &mut out),
+            Err(PairError::UnpairedRead {
+                pair: 1,
+                id: "p2/1".to_string()
+            })
+        );
+        assert_eq!(
+            deinterleave(reader("@p1/1\nA\n+\nI\n"), &mut Vec::new(), &mut Vec::new()),
+            Err(PairError::UnpairedRead {
+                pair: 0,
+                id: "p1/1".to_string()
+            })
+        );
+    }
+}
@@ -0,0 +1,536 @@
+//! Sequence pattern search: IUPAC nucleotide patterns and PROSITE
+//! protein patterns.
+
+use std::fmt;
+use std::ops::Range;
+
+use crate::iupac;
+use crate::seq::{reverse_complement, Strand};
+
+pub mod exact;
+pub mod multi;
+
+/// A pattern occurrence. `start` and `end` are 0-based, half-open
+/// forward-strand coordinates; a `Reverse` match is where the pattern's
+/// reverse complement occurs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PatternMatch {
+    pub start: usize,
+    pub end: usize,
+    pub strand: Strand,
+}
+
+impl PatternMatch {
+    pub fn len(&self) -> usize {
+        self.end - self.start
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.start == self.end
+    }
+}
+
+/// All occurrences of an IUPAC ambiguity-code pattern in `target` on both
+/// strands, sorted by position with forward matches first.
+///
+/// A pattern symbol matches any target base it covers (see
+/// [`iupac::covers`]), so `GTYRAC` finds `GTCGAC` but `GTCGAC` does not
+/// find `GTYRAC`. Overlapping matches are all reported. When the pattern
+/// is its own reverse complement, like most restriction sites, each match
+/// is reported once, on the forward strand.
+pub fn find_iupac(pattern: &[u8], target: &[u8]) -> Vec<PatternMatch> {
+    let len = pattern.len();
+    if len == 0 || len > target.len() {
+        return Vec::new();
+    }
+    let reverse = reverse_complement(pattern);
+    let palindromic = reverse.eq_ignore_ascii_case(pattern);
+    let matches_at =
+        |pat: &[u8], window: &[u8]| pat.iter().zip(window).all(|(&p, &b)| iupac::covers(p, b));
+
+    let mut matches = Vec::new();
+    for (start, window) in target.windows(len).enumerate() {
+        let end = start + len;
+        if matches_at(pattern, window) {
+            matches.push(PatternMatch {
+                start,
+                end,
+                strand: Strand::Forward,
+            });
+        }
+        if !palindromic && matches_at(&reverse, window) {
+            matches.push(PatternMatch {
+                start,
+                end,
+                strand: Strand::Reverse,
+            });
+        }
+    }
+    matches
+}
+
+/// Errors from [`PrositePattern::parse`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PrositeError {
+    Empty,
+    /// An element, given with its 0-based index, could not be parsed.
+    InvalidElement {
+        index: usize,
+        element: String,
+    },
+    /// `<` or `>` outside the first or last element.
+    MisplacedAnchor {
+        index: usize,
+    },
+}
+
+impl fmt::Display for PrositeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PrositeError::Empty => write!(f, "empty PROSITE pattern"),
+            PrositeError::InvalidElement { index, element } => {
+                write!(
+                    f,
+                    "invalid pattern element {:?} at index {}",
+                    element, index
+                )
+            }
+            PrositeError::MisplacedAnchor { index } => {
+                write!(
+                    f,
+                    "terminal anchor in element {} is not at a pattern end",
+                    index
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for PrositeError {}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Residues {
+    Any,
+    OneOf(Vec<u8>),
+    NoneOf(Vec<u8>),
+}
+
+impl Residues {
+    fn matches(&self, residue: u8) -> bool {
+        let residue = residue.to_ascii_uppercase();
+        match self {
+            Residues::Any => true,
+            Residues::OneOf(set) => set.contains(&residue),
+            Residues::NoneOf(set) => !set.contains(&residue),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Element {
+    residues: Residues,
+    min: usize,
+    max: usize,
+    /// `[G>]`: the element may also match the end of the sequence.
+    or_c_term: bool,
+}
+
+/// A compiled PROSITE pattern such as `C-x(2,4)-C-x(3)-[LIVMFYWC]`.
+///
+/// Supported syntax: residue letters, `x` for any residue, `[...]` and
+/// `{...}` for allowed and forbidden residues, repeats `(n)` and `(n,m)`,
+/// `<` and `>` anchors at the N- and C-terminus (also as `[...>]` in the
+/// last element) and an optional trailing period.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PrositePattern {
+    elements: Vec<Element>,
+    n_term: bool,
+    c_term: bool,
+}
+
+impl PrositePattern {
+    pub fn parse(pattern: &str) -> Result<Self, PrositeError> {
+        let pattern = pattern.trim();
+        let pattern = pattern.strip_suffix('.').unwrap_or(pattern);
+        if pattern.is_empty() {
+            return Err(PrositeError::Empty);
+        }
+        let raw: Vec<&str> = pattern.split('-').map(str::trim).collect();
+        let last = raw.len() - 1;
+        let mut n_term = false;
+        let mut c_term = false;
+        let mut elements = Vec::with_capacity(raw.len());
+        for (index, text) in raw.iter().enumerate() {
+            let invalid = || PrositeError::InvalidElement {
+                index,
+                element: text.to_string(),
+            };
+            let mut body = *text;
+            if let Some(rest) = body.strip_prefix('<') {
+                if index != 0 {
+                    return Err(PrositeError::MisplacedAnchor { index });
+                }
+                n_term = true;
+                body = rest;
+            }
+            if let Some(rest) = body.strip_suffix('>') {
+                if index != last {
+                    return Err(PrositeError::MisplacedAnchor { index });
+                }
+                c_term = true;
+                body = rest;
+            }
+
+            let (class, repeat) = match body.find('(') {
+                Some(open) => {
+                    let repeat = body[open..]
+                        .strip_prefix('(')
+                        .and_then(|r| r.strip_suffix(')'))
+                        .ok_or_else(invalid)?;
+                    (&body[..open], Some(repeat))
+                }
+                None => (body, None),
+            };
+            let (min, max) = match repeat {
+                None => (1, 1),
+                Some(repeat) => {
+                    let (lo, hi) = repeat.split_once(',').unwrap_or((repeat, repeat));
+                    let lo = lo.trim().parse().map_err(|_| invalid())?;
+                    let hi = hi.trim().parse().map_err(|_| invalid())?;
+                    if lo > hi || hi == 0 {
+                        return Err(invalid());
+                    }
+                    (lo, hi)
+                }
+            };
+
+            let mut or_c_term = false;
+            let residues = if class.eq_ignore_ascii_case("x") {
+                Residues::Any
+            } else if let Some(set) = class.strip_prefix('[').and_then(|c| c.strip_suffix(']')) {
+                let set = match set.strip_suffix('>') {
+                    Some(set) if index == last => {
+                        or_c_term = true;
+                        set
+                    }
+                    Some(_) => return Err(PrositeError::MisplacedAnchor { index }),
+                    None => set,
+                };
+                Residues::OneOf(residue_set(set).ok_or_else(invalid)?)
+            } else if let Some(set) = class.strip_prefix('{').and_then(|c| c.strip_suffix('}')) {
+                Residues::NoneOf(residue_set(set).ok_or_else(invalid)?)
+            } else {
+                Residues::OneOf(
+                    residue_set(class)
+                        .filter(|s| s.len() == 1)
+                        .ok_or_else(invalid)?,
+                )
+            };
+            if or_c_term && (min, max) != (1, 1) {
+                return Err(invalid());
+            }
+            elements.push(Element {
+                residues,
+                min,
+                max,
+                or_c_term,
+            });
+        }
+        Ok(PrositePattern {
+            elements,
+            n_term,
+            c_term,
+        })
+    }
+
+    /// Non-overlapping matches in `protein`, scanning left to right and
+    /// taking the longest match at each start, as a regular expression
+    /// would. Residues are compared case-insensitively.
+    pub fn find(&self, protein: &[u8]) -> Vec<Range<usize>> {
+        let mut matches = Vec::new();
+        let mut start = 0;
+        while start <= protein.len() {
+            if self.n_term && start > 0 {
+                break;
+            }
+            match self.match_at(protein, 0, start) {
+                Some(end) if end > start => {
+                    matches.push(start..end);
+                    start = end;
+                }
+                _ => start += 1,
+            }
+        }
+        matches
+    }
+
+    /// Whether the pattern occurs anywhere in `protein`.
+    pub fn is_match(&self, protein: &[u8]) -> bool {
+        !self.find(protein).is_empty()
+    }
+
+    /// End of the longest match of `elements[element..]` starting at `pos`.
+    fn match_at(&self, protein: &[u8], element: usize, pos: usize) -> Option<usize> {
+        let Some(el) = self.elements.get(element) else {
+            return (!self.c_term || pos == protein.len()).then_some(pos);
+        };
+        if el.or_c_term && pos == protein.len() {
+            return Some(pos);
+        }
+        let run = protein[pos..]
+            .iter()
+            .take(el.max)
+            .take_while(|&&r| el.residues.matches(r))
+            .count();
+        (el.min..=run)
+            .rev()
+            .find_map(|count| self.match_at(protein, element + 1, pos + count))
+    }
+}
+
+/// Upper-cased residue letters of a class body, or `None` if it is empty
+/// or holds anything but letters.
+fn residue_set(letters: &str) -> Option<Vec<u8>> {
+    let set: Vec<u8> = letters.bytes().map(|b| b.to_ascii_uppercase()).collect();
+    (!set.is_empty() && set.iter().all(u8::is_ascii_alphabetic)).then_some(set)
+}
+
+/// Longest pattern [`find_approx`] accepts, one bit per pattern position.
+pub const MAX_APPROX_PATTERN_LEN: usize = 64;
+
+/// An approximate pattern occurrence in 0-based, half-open coordinates,
+/// with the edit distance between the pattern and `target[start..end]`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ApproxMatch {
+    pub start: usize,
+    pub end: usize,
+    pub distance: usize,
+}
+
+/// Occurrences of `pattern` in `target` with at most `max_errors`
+/// substitutions, insertions or deletions, using Myers' bit-parallel
+/// algorithm. Pattern symbols may be IUPAC codes, matched as in
+/// [`find_iupac`]; only the forward strand is searched.
+///
+/// An approximate hit usually ends at several neighbouring positions (the
+/// exact match, plus one base shorter or longer at a cost); each run of
+/// consecutive end positions is reported once, at its lowest distance.
+///
+/// # Panics
+///
+/// Panics if `pattern` is longer than [`MAX_APPROX_PATTERN_LEN`].
+pub fn find_approx(pattern: &[u8], target: &[u8], max_errors: usize) -> Vec<ApproxMatch> {
+    let m = pattern.len();
+    assert!(
+        m <= MAX_APPROX_PATTERN_LEN,
+        "approximate patterns are limited to {} symbols",
+        MAX_APPROX_PATTERN_LEN
+    );
+    if m == 0 {
+        return Vec::new();
+    }
+    let mut peq = [0u64; 256];
+    for (symbol, bits) in peq.iter_mut().enumerate() {
+        for (i, &p) in pattern.iter().enumerate() {
+            if iupac::covers(p, symbol as u8) {
+                *bits |= 1 << i;
+            }
+        }
+    }
+
+    let high = 1u64 << (m - 1);
+    let mut pv = !0u64;
+    let mut mv = 0u64;
+    let mut score = m;
+    // Best (end, distance) of the current run of consecutive hits.
+    let mut run: Option<(usize, usize)> = None;
+    let mut matches = Vec::new();
+    for (j, &symbol) in target.iter().enumerate() {
+        let eq = peq[symbol as usize];
+        let xv = eq | mv;
+        let xh = ((eq & pv).wrapping_add(pv) ^ pv) | eq;
+        let mut ph = mv | !(xh | pv);
+        let mut mh = pv & xh;
+        if ph & high != 0 {
+            score += 1;
+        } else if mh & high != 0 {
+            score -= 1;
+        }
+        ph <<= 1;
+        mh <<= 1;
+        pv = mh | !(xv | ph);
+        mv = ph & xv;
+
+        let end = j + 1;
+        if score <= max_errors {
+            run = match run {
+                Some((best_end, best)) if best <= score => Some((best_end, best)),
+                _ => Some((end, score)),
+            };
+        } else if let Some((best_end, distance)) = run.take() {
+            matches.push(locate(pattern, target, best_end, distance, max_errors));
+        }
+    }
+    if let Some((best_end, distance)) = run {
+        matches.push(locate(pattern, target, best_end, distance, max_errors));
+    }
+    matches
+}
+
+/// Recovers the start of a hit ending at `end` by aligning the reversed
+/// pattern against the reversed target, anchored at `end`. The shortest
+/// span reaching `distance` wins.
+fn locate(
+    pattern: &[u8],
+    target: &[u8],
+    end: usize,
+    distance: usize,
+    max_errors: usize,
+) -> ApproxMatch {
+    let lo = end.saturating_sub(pattern.len() + max_errors);
+    let window: Vec<u8> = target[lo..end].iter().rev().copied().collect();
+    // prev[j]: distance of the reversed pattern prefix against window[..j].
+    let mut prev: Vec<usize> = (0..=window.len()).collect();
+    for (i, &p) in pattern.iter().rev().enumerate() {
+        let mut row = vec![i + 1; window.len() + 1];
+        for (j, &b) in window.iter().enumerate() {
+            let cost = usize::from(!iupac::covers(p, b));
+            row[j + 1] = (prev[j] + cost).min(prev[j + 1] + 1).min(row[j] + 1);
+        }
+        prev = row;
+    }
+    let span = prev
+        .iter()
+        .position(|&d| d == distance)
+        .unwrap_or(pattern.len().min(window.len()));
+    ApproxMatch {
+        start: end - span,
+        end,
+        distance,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn spans(matches: &[PatternMatch]) -> Vec<(usize, usize, Strand)> {
+        matches.iter().map(|m| (m.start, m.end, m.strand)).collect()
+    }
+
+    #[test]
+    fn finds_degenerate_matches_on_both_strands() {
+        // GGTNNC forward at 0; its reverse complement GNNACC at 10.
+        let target = b"GGTaacTTTTGcgACCTT";
+        assert_eq!(
+            spans(&find_iupac(b"GGTNNC", target)),
+            vec![(0, 6, Strand::Forward), (10, 16, Strand::Reverse)]
+        );
+    }
+
+    #[test]
+    fn palindromes_are_reported_once() {
+        // HincII, GTYRAC, is its own reverse complement.
+        let target = b"AAGTCGACAAGTTAACAA";
+        assert_eq!(
+            spans(&find_iupac(b"GTYRAC", target)),
+            vec![(2, 8, Strand::Forward), (10, 16, Strand::Forward)]
+        );
+    }
+
+    #[test]
+    fn ambiguous_targets_need_covering_patterns() {
+        assert!(find_iupac(b"GAATTC", b"GAANTC").is_empty());
+        assert_eq!(find_iupac(b"GAWTTC", b"GAWTTC").len(), 1);
+        assert_eq!(find_iupac(b"AA", b"AAAA").len(), 3);
+        assert!(find_iupac(b"", b"ACGT").is_empty());
+        assert!(find_iupac(b"ACGTA", b"ACGT").is_empty());
+    }
+
+    #[test]
+    fn matches_prosite_zinc_finger_like_pattern() {
+        let pattern = PrositePattern::parse("C-x(2,4)-C-x(3)-[LIVMFYWC].").unwrap();
+        // C AA C GGG L matches with two spacers; C KKKK C AAA W with four.
+        let protein = b"MCAACGGGLPPPCKKKKCAAAWS";
+        assert_eq!(pattern.find(protein), vec![1..9, 12..22]);
+        assert!(!pattern.is_match(b"MCAACGGGP"));
+    }
+
+    #[test]
+    fn prosite_classes_and_exclusions() {
+        let pattern = PrositePattern::parse("N-{P}-[ST]-{P}").unwrap();
+        assert_eq!(pattern.find(b"aNGSAnpsa"), vec![1..5]);
+        assert!(PrositePattern::parse("x(2)-G").unwrap().is_match(b"AAG"));
+    }
+
+    #[test]
+    fn prosite_anchors() {
+        let n_term = PrositePattern::parse("<M-x-K").unwrap();
+        assert_eq!(n_term.find(b"MAKMAK"), vec![0..3]);
+        let c_term = PrositePattern::parse("K-x-L>").unwrap();
+        assert_eq!(c_term.find(b"KALKAL"), vec![3..6]);
+        let or_end = PrositePattern::parse("K-[GA>]").unwrap();
+        assert_eq!(or_end.find(b"KGPK"), vec![0..2, 3..4]);
+    }
+
+    #[test]
+    fn prosite_backtracks_over_repeats() {
+        // The greedy x(1,3) must give back residues for the final G.
+        let pattern = PrositePattern::parse("A-x(1,3)-G").unwrap();
+        assert_eq!(pattern.find(b"AGGGG"), vec![0..5]);
+        assert_eq!(pattern.find(b"AGG"), vec![0..3]);
+    }
+
+    #[test]
+    fn rejects_malformed_prosite_patterns() {
+        assert_eq!(PrositePattern::parse(" . "), Err(PrositeError::Empty));
+        assert_eq!(
+            PrositePattern::parse("C-x(4,2)"),
+            Err(PrositeError::InvalidElement {
+                index: 1,
+                element: "x(4,2)".to_string()
+            })
+        );
+        assert!(PrositePattern::parse("C-[LIV-G").is_err());
+        assert!(PrositePattern::parse("CC-G").is_err());
+        assert_eq!(
+            PrositePattern::parse("C-<G"),
+            Err(PrositeError::MisplacedAnchor { index: 1 })
+        );
+    }
+
+    fn approx(pattern: &[u8], target: &[u8], k: usize) -> Vec<(usize, usize, usize)> {
+        find_approx(pattern, target, k)
+            .iter()
+            .map(|m| (m.start, m.end, m.distance))
+            .collect()
+    }
+
+    #[test]
+    fn approx_finds_exact_and_mismatched_hits() {
+        let target = b"TTTTACGTACGTTTTTTTACGAACGTTTT";
+        assert_eq!(
+            approx(b"ACGTACGT", target, 1),
+            vec![(4, 12, 0), (18, 26, 1)]
+        );
+        assert_eq!(approx(b"ACGTACGT", target, 0), vec![(4, 12, 0)]);
+    }
+
+    #[test]
+    fn approx_handles_indels() {
+        // One base deleted from, then one inserted into, the pattern.
+        assert_eq!(approx(b"GATTACA", b"CCGATACACC", 1), vec![(2, 8, 1)]);
+        assert_eq!(approx(b"GATTACA", b"CCGATTTACACC", 1), vec![(2, 10, 1)]);
+    }
+
+    #[test]
+    fn approx_supports_iupac_and_long_patterns() {
+        assert_eq!(approx(b"ACNNGT", b"TTACTTGTT", 0), vec![(2, 8, 0)]);
+        let pattern = [b'A'; 64];
+        let mut target = vec![b'C'; 10];
+        target.extend_from_slice(&pattern);
+        target[40] = b'G';
+        assert_eq!(approx(&pattern, &target, 1), vec![(10, 74, 1)]);
+        assert!(approx(b"", b"ACGT", 1).is_empty());
+    }
+}
@@ -0,0 +1,231 @@
+//! Aho–Corasick automaton for finding many exact patterns in one pass.
+
+use std::collections::VecDeque;
+
+/// An occurrence of pattern number `pattern` (its index in the list the
+/// automaton was built from) at 0-based, half-open `start..end`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct MultiMatch {
+    pub pattern: usize,
+    pub start: usize,
+    pub end: usize,
+}
+
+/// A compiled set of exact patterns, e.g. thousands of barcodes or
+/// adapters, searched simultaneously.
+///
+/// Matching is ASCII case-insensitive and reports every occurrence of
+/// every pattern, overlapping ones included. Empty patterns never match.
+/// Transitions are a dense table over the distinct symbols of the
+/// patterns, so each target byte costs one lookup.
+#[derive(Debug, Clone)]
+pub struct AhoCorasick {
+    /// Byte to symbol class; class 0 is every byte absent from the patterns.
+    classes: [u8; 256],
+    class_count: usize,
+    /// `transitions[state * class_count + class]`, the full automaton with
+    /// failure links already folded in.
+    transitions: Vec<u32>,
+    /// Patterns ending at each state, including via failure links.
+    outputs: Vec<Vec<u32>>,
+    pattern_lens: Vec<usize>,
+}
+
+impl AhoCorasick {
+    pub fn new<I, P>(patterns: I) -> Self
+    where
+        I: IntoIterator<Item = P>,
+        P: AsRef<[u8]>,
+    {
+        let patterns: Vec<Vec<u8>> = patterns
+            .into_iter()
+            .map(|p| p.as_ref().to_ascii_uppercase())
+            .collect();
+
+        let mut classes = [0u8; 256];
+        let mut class_count = 1;
+        for &b in patterns.iter().flatten() {
+            if classes[b as usize] == 0 {
+                classes[b as usize] = class_count as u8;
+                classes[b.to_ascii_lowercase() as usize] = class_count as u8;
+                class_count += 1;
+            }
+        }
+
+        // Trie, with 0 marking a missing edge (the root is never a child).
+        let mut transitions = vec![0u32; class_count];
+        let mut outputs = vec![Vec::new()];
+        for (id, pattern) in patterns.iter().enumerate() {
+            if pattern.is_empty() {
+                continue;
+            }
+            let mut state = 0;
+            for &b in pattern {
+                let slot = state * class_count + classes[b as usize] as usize;
+                if transitions[slot] == 0 {
+                    transitions[slot] = outputs.len() as u32;
+                    transitions.extend(std::iter::repeat_n(0, class_count));
+                    outputs.push(Vec::new());
+                }
+                state = transitions[slot] as usize;
+            }
+            outputs[state].push(id as u32);
+        }
+
+        // Breadth-first, turn missing edges into failure transitions and
+        // inherit the outputs of each state's failure state.
+        let mut fail = vec![0usize; outputs.len()];
+        let mut queue: VecDeque<usize> = (0..class_count)
+            .map(|c| transitions[c] as usize)
+            .filter(|&s| s != 0)
+            .collect();
+        while let Some(state) = queue.pop_front() {
+            for c in 0..class_count {
+                let slot = state * class_count + c;
+                let fallback = transitions[fail[state] * class_count + c];
+                if transitions[slot] == 0 {
+                    transitions[slot] = fallback;
+                } else {
+                    let child = transitions[slot] as usize;
+                    fail[child] = fallback as usize;
+                    let inherited = outputs[fallback as usize].clone();
+                    outputs[child].extend(inherited);
+                    queue.push_back(child);
+                }
+            }
+        }
+
+        AhoCorasick {
+            classes,
+            class_count,
+            transitions,
+            outputs,
+            pattern_lens: patterns.iter().map(Vec::len).collect(),
+        }
+    }
+
+    /// Number of patterns, empty ones included.
+    pub fn pattern_count(&self) -> usize {
+        self.pattern_lens.len()
+    }
+
+    /// All matches in `target`, ordered by end position.
+    pub fn find_all(&self, target: &[u8]) -> Vec<MultiMatch> {
+        let mut stream = self.stream();
+        stream.feed(target)
+    }
+
+    /// A searcher for a target arriving in chunks, such as a sequence read
+    /// line by line; matches spanning chunk boundaries are found.
+    pub fn stream(&self) -> StreamSearcher<'_> {
+        StreamSearcher {
+            automaton: self,
+            state: 0,
+            offset: 0,
+        }
+    }
+
+    fn step(&self, state: usize, byte: u8) -> usize {
+        self.transitions[state * self.class_count + self.classes[byte as usize] as usize] as usize
+    }
+}
+
+/// Incremental search state returned by [`AhoCorasick::stream`].
+#[derive(Debug, Clone)]
+pub struct StreamSearcher<'a> {
+    automaton: &'a AhoCorasick,
+    state: usize,
+    offset: usize,
+}
+
+impl StreamSearcher<'_> {
+    /// Searches the next chunk. Match coordinates count from the start of
+    /// the first chunk fed since creation or the last [`reset`](Self::reset).
+    pub fn feed(&mut self, chunk: &[u8]) -> Vec<MultiMatch> {
+        let ac = self.automaton;
+        let mut matches = Vec::new();
+        for (i, &b) in chunk.iter().enumerate() {
+            self.state = ac.step(self.state, b);
+            let end = self.offset + i + 1;
+            for &id in &ac.outputs[self.state] {
+                let pattern = id as usize;
+                matches.push(MultiMatch {
+                    pattern,
+                    start: end - ac.pattern_lens[pattern],
+                    end,
+                });
+            }
+        }
+        self.offset += chunk.len();
+        matches
+    }
+
+    /// Total number of bytes fed so far.
+    pub fn position(&self) -> usize {
+        self.offset
+    }
+
+    /// Starts over, e.g. at the next sequence.
+    pub fn reset(&mut self) {
+        self.state = 0;
+        self.offset = 0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn found(matches: &[MultiMatch]) -> Vec<(usize, usize, usize)> {
+        matches
+            .iter()
+            .map(|m| (m.pattern, m.start, m.end))
+            .collect()
+    }
+
+    #[test]
+    fn finds_overlapping_patterns() {
+        let ac = AhoCorasick::new(["he", "she", "his", "hers"]);
+        assert_eq!(
+            found(&ac.find_all(b"ushers")),
+            vec![(1, 1, 4), (0, 2, 4), (3, 2, 6)]
+        );
+    }
+
+    #[test]
+    fn matches_barcodes_case_insensitively() {
+        let ac = AhoCorasick::new(["ACGT", "", "GTAC", "CGTA"]);
+        assert_eq!(ac.pattern_count(), 4);
+        assert_eq!(
+            found(&ac.find_all(b"ttacgtacnn")),
+            vec![(0, 2, 6), (3, 3, 7), (2, 4, 8)]
+        );
+        assert!(ac.find_all(b"NNNN").is_empty());
+    }
+
+    #[test]
+    fn stream_matches_span_chunks() {
+        let ac = AhoCorasick::new(["GATTACA"]);
+        let mut stream = ac.stream();
+        assert!(stream.feed(b"CCGAT").is_empty());
+        assert_eq!(found(&stream.feed(b"TACAGG")), vec![(0, 2, 9)]);
+        assert_eq!(stream.position(), 11);
+        stream.reset();
+        assert!(stream.feed(b"ACA").is_empty());
+    }
+
+    #[test]
+    fn handles_many_patterns() {
+        let bases = [b'A', b'C', b'G', b'T'];
+        let patterns: Vec<Vec<u8>> = (0..4096usize)
+            .map(|n| (0..6).map(|i| bases[(n >> (2 * i)) & 3]).collect())
+            .collect();
+        let ac = AhoCorasick::new(&patterns);
+        let target = b"ACGTACGTAC";
+        let matches = ac.find_all(target);
+        assert_eq!(matches.len(), target.len() - 5);
+        for m in matches {
+            assert_eq!(patterns[m.pattern], &target[m.start..m.end]);
+        }
+    }
+}
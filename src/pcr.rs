@@ -0,0 +1,301 @@
+//! In-silico PCR: primer binding sites and predicted amplicons.
+
+use crate::fasta::FastaRecord;
+use crate::iupac;
+use crate::seq::{reverse_complement, Strand};
+
+/// A named forward/reverse primer pair, both written 5' to 3'.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PcrPrimers {
+    pub name: String,
+    pub forward: String,
+    pub reverse: String,
+}
+
+impl PcrPrimers {
+    pub fn new(name: &str, forward: &str, reverse: &str) -> Self {
+        PcrPrimers {
+            name: name.to_string(),
+            forward: forward.to_string(),
+            reverse: reverse.to_string(),
+        }
+    }
+}
+
+/// Matching and product size limits for [`in_silico_pcr`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PcrOptions {
+    /// Mismatches tolerated over the whole primer.
+    pub max_mismatches: usize,
+    /// Number of 3'-terminal primer bases that must match exactly.
+    pub anchor_len: usize,
+    pub min_product_size: usize,
+    pub max_product_size: usize,
+}
+
+impl Default for PcrOptions {
+    fn default() -> Self {
+        PcrOptions {
+            max_mismatches: 2,
+            anchor_len: 3,
+            min_product_size: 1,
+            max_product_size: 5000,
+        }
+    }
+}
+
+/// Where a primer anneals. `start` and `end` are 0-based, half-open
+/// forward-strand coordinates; a `Reverse` site is where the primer's
+/// reverse complement occurs, so its 3' end is at `start`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BindingSite {
+    pub start: usize,
+    pub end: usize,
+    pub strand: Strand,
+    pub mismatches: usize,
+}
+
+/// Which primer of a pair produced a binding site.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PrimerRole {
+    Forward,
+    Reverse,
+}
+
+/// A predicted PCR product.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Amplicon {
+    pub primers: String,
+    pub seq_id: String,
+    /// 0-based, half-open forward-strand coordinates of the product.
+    pub start: usize,
+    pub end: usize,
+    pub left: (PrimerRole, BindingSite),
+    pub right: (PrimerRole, BindingSite),
+    /// Product sequence. Primer tails replace the template at the binding
+    /// sites, since mismatched bases are copied from the primers.
+    pub sequence: String,
+}
+
+impl Amplicon {
+    pub fn len(&self) -> usize {
+        self.end - self.start
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.start == self.end
+    }
+}
+
+fn count_mismatches(
+    primer: &[u8],
+    site: &[u8],
+    anchor: Anchor,
+    options: &PcrOptions,
+) -> Option<usize> {
+    let len = primer.len();
+    let anchor_len = options.anchor_len.min(len);
+    let mut mismatches = 0;
+    for (i, (p, s)) in primer.iter().zip(site).enumerate() {
+        if !iupac::covers(*p, *s) {
+            let anchored = match anchor {
+                Anchor::End => i >= len - anchor_len,
+                Anchor::Start => i < anchor_len,
+            };
+            mismatches += 1;
+            if anchored || mismatches > options.max_mismatches {
+                return None;
+            }
+        }
+    }
+    Some(mismatches)
+}
+
+/// Which end of the compared strings holds the primer's 3' end.
+#[derive(Clone, Copy)]
+enum Anchor {
+    Start,
+    End,
+}
+
+/// All sites on either strand of `template` where `primer` anneals within
+/// the mismatch limits, sorted by position.
+///
+/// Degenerate primers are supported: a primer base matches any template
+/// base its IUPAC code covers, so `R` binds `A` or `G`.
+pub fn find_binding_sites(
+    primer: &[u8],
+    template: &[u8],
+    options: &PcrOptions,
+) -> Vec<BindingSite> {
+    let len = primer.len();
+    if len == 0 || len > template.len() {
+        return Vec::new();
+    }
+    let reverse = reverse_complement(primer);
+    let mut sites = Vec::new();
+    for (start, window) in template.windows(len).enumerate() {
+        let end = start + len;
+        if let Some(mismatches) = count_mismatches(primer, window, Anchor::End, options) {
+            sites.push(BindingSite {
+                start,
+                end,
+                strand: Strand::Forward,
+                mismatches,
+            });
+        }
+        if let Some(mismatches) = count_mismatches(&reverse, window, Anchor::Start, options) {
+            sites.push(BindingSite {
+                start,
+                end,
+                strand: Strand::Reverse,
+                mismatches,
+            });
+        }
+    }
+    sites
+}
+
+/// Predicts the products of each primer pair on every sequence of `genome`.
+///
+/// Every forward-strand site followed by a reverse-strand site within the
+/// product size limits yields an amplicon, whichever primer of the pair
+/// bound, so single-primer products are reported too.
+pub fn in_silico_pcr(
+    primers: &[PcrPrimers],
+    genome: &FastaRecord,
+    options: &PcrOptions,
+) -> Vec<Amplicon> {
+    let mut amplicons = Vec::new();
+    for seq in genome.seqs() {
+        let template = seq.sequence().as_bytes();
+        for pair in primers {
+            let oligos = [
+                (PrimerRole::Forward, pair.forward.as_bytes()),
+                (PrimerRole::Reverse, pair.reverse.as_bytes()),
+            ];
+            let mut lefts = Vec::new();
+            let mut rights = Vec::new();
+            for (role, oligo) in oligos {
+                for site in find_binding_sites(oligo, template, options) {
+                    match site.strand {
+                        Strand::Forward => lefts.push((role, oligo, site)),
+                        Strand::Reverse => rights.push((role, oligo, site)),
+                    }
+                }
+            }
+            for &(left_role, left_oligo, left) in &lefts {
+                for &(right_role, right_oligo, right) in &rights {
+                    if right.end <= left.start {
+                        continue;
+                    }
+                    let size = right.end - left.start;
+                    if size < options.min_product_size || size > options.max_product_size {
+                        continue;
+                    }
+                    let sequence = if left.end <= right.start {
+                        let mut product = left_oligo.to_vec();
+                        product.extend_from_slice(&template[left.end..right.start]);
+                        product.extend(reverse_complement(right_oligo));
+                        product
+                    } else {
+                        template[left.start..right.end].to_vec()
+                    };
+                    amplicons.push(Amplicon {
+                        primers: pair.name.clone(),
+                        seq_id: seq.id().to_string(),
+                        start: left.start,
+                        end: right.end,
+                        left: (left_role, left),
+                        right: (right_role, right),
+                        sequence: String::from_utf8_lossy(&sequence).into_owned(),
+                    });
+                }
+            }
+        }
+    }
+    amplicons
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::alphabet::Alphabet;
+    use crate::fasta::FastaSeq;
+
+    fn genome(seq: &str) -> FastaRecord {
+        let seq = FastaSeq::builder()
+            .id("chr1")
+            .sequence(seq)
+            .alphabet(Alphabet::Dna)
+            .build()
+            .unwrap();
+        FastaRecord::new(vec![seq])
+    }
+
+    const FORWARD: &str = "GCTAAAGACAATTACATA";
+    const REVERSE: &str = "TCGTGCTGACGTGTATGT";
+    const TEMPLATE: &str = "GTGTGATGCAGCTAAAGACAATTACATAAACTTGTTGGCCCAGTGTGAATCGCTTAAGGGTTAAGTAAACATACACGTCAGCACGATACGCCTTTA";
+
+    #[test]
+    fn finds_amplicon_with_primer_tails() {
+        let primers = [PcrPrimers::new("p1", FORWARD, REVERSE)];
+        let amplicons = in_silico_pcr(&primers, &genome(TEMPLATE), &PcrOptions::default());
+        assert_eq!(amplicons.len(), 1);
+        let amp = &amplicons[0];
+        assert_eq!((amp.start, amp.end), (10, 86));
+        assert_eq!(amp.seq_id, "chr1");
+        assert_eq!(amp.sequence, &TEMPLATE[10..86]);
+        assert_eq!(amp.left.0, PrimerRole::Forward);
+        assert_eq!(amp.right.0, PrimerRole::Reverse);
+    }
+
+    #[test]
+    fn tolerates_mismatches_outside_the_anchor() {
+        let primers = [PcrPrimers::new("p1", "ACTAAAGACAATTACATA", REVERSE)];
+        let amplicons = in_silico_pcr(&primers, &genome(TEMPLATE), &PcrOptions::default());
+        assert_eq!(amplicons.len(), 1);
+        assert_eq!(amplicons[0].left.1.mismatches, 1);
+        assert!(amplicons[0].sequence.starts_with("ACTAAAGACAATTACATA"));
+
+        // A mismatched 3' end is not extended by the polymerase.
+        let primers = [PcrPrimers::new("p1", "GCTAAAGACAATTACATT", REVERSE)];
+        assert!(in_silico_pcr(&primers, &genome(TEMPLATE), &PcrOptions::default()).is_empty());
+    }
+
+    #[test]
+    fn degenerate_primers_bind_covered_bases() {
+        // R covers the forward primer's first G, Y the reverse primer's last T.
+        let primers = [PcrPrimers::new(
+            "p1",
+            "RCTAAAGACAATTACATA",
+            "TCGTGCTGACGTGTATGY",
+        )];
+        let amplicons = in_silico_pcr(&primers, &genome(TEMPLATE), &PcrOptions::default());
+        assert_eq!(amplicons.len(), 1);
+        assert_eq!(amplicons[0].left.1.mismatches, 0);
+        assert_eq!(amplicons[0].right.1.mismatches, 0);
+    }
+
+    #[test]
+    fn respects_product_size_limits() {
+        let primers = [PcrPrimers::new("p1", FORWARD, REVERSE)];
+        let options = PcrOptions {
+            max_product_size: 50,
+            ..PcrOptions::default()
+        };
+        assert!(in_silico_pcr(&primers, &genome(TEMPLATE), &options).is_empty());
+    }
+
+    #[test]
+    fn reports_reverse_binding_sites() {
+        let sites = find_binding_sites(
+            REVERSE.as_bytes(),
+            TEMPLATE.as_bytes(),
+            &PcrOptions::default(),
+        );
+        assert_eq!(sites.len(), 1);
+        assert_eq!(sites[0].strand, Strand::Reverse);
+        assert_eq!((sites[0].start, sites[0].end), (68, 86));
+    }
+}
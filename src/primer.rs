@@ -0,0 +1,388 @@
+//! PCR primer thermodynamics and primer pair design.
+
+use std::cmp::Ordering;
+use std::ops::{Range, RangeInclusive};
+
+use crate::seq::{gc_content, reverse_complement, Strand};
+
+/// Gas constant in cal/(K·mol).
+const R: f64 = 1.987;
+
+/// SantaLucia (1998) unified nearest-neighbour parameters: ΔH in kcal/mol
+/// and ΔS in cal/(K·mol) for each dinucleotide stack, indexed by
+/// [`stack_index`].
+const NN_PARAMS: [(f64, f64); 16] = [
+    (-7.9, -22.2),  // AA/TT
+    (-8.4, -22.4),  // AC = GT/CA
+    (-7.8, -21.0),  // AG = CT/GA
+    (-7.2, -20.4),  // AT
+    (-8.5, -22.7),  // CA
+    (-8.0, -19.9),  // CC = GG
+    (-10.6, -27.2), // CG
+    (-7.8, -21.0),  // CT
+    (-8.2, -22.2),  // GA
+    (-9.8, -24.4),  // GC
+    (-8.0, -19.9),  // GG
+    (-8.4, -22.4),  // GT
+    (-7.2, -21.3),  // TA
+    (-8.2, -22.2),  // TC = GA/CT
+    (-8.5, -22.7),  // TG = CA/GT
+    (-7.9, -22.2),  // TT = AA
+];
+
+fn base_index(base: u8) -> Option<usize> {
+    match base.to_ascii_uppercase() {
+        b'A' => Some(0),
+        b'C' => Some(1),
+        b'G' => Some(2),
+        b'T' => Some(3),
+        _ => None,
+    }
+}
+
+fn stack_index(a: u8, b: u8) -> Option<usize> {
+    Some(base_index(a)? * 4 + base_index(b)?)
+}
+
+/// Reaction conditions used for melting temperature calculations.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TmConditions {
+    /// Monovalent cation concentration in mol/L.
+    pub sodium: f64,
+    /// Primer strand concentration in mol/L.
+    pub primer_conc: f64,
+}
+
+impl Default for TmConditions {
+    fn default() -> Self {
+        TmConditions {
+            sodium: 0.05,
+            primer_conc: 250e-9,
+        }
+    }
+}
+
+/// Nearest-neighbour melting temperature in °C, or `None` if the oligo is
+/// shorter than two bases or contains anything but `ACGT`.
+pub fn melting_temp(oligo: &[u8], conditions: &TmConditions) -> Option<f64> {
+    if oligo.len() < 2 {
+        return None;
+    }
+    let (mut dh, mut ds) = (0.0, 0.0);
+    for pair in oligo.windows(2) {
+        let (h, s) = NN_PARAMS[stack_index(pair[0], pair[1])?];
+        dh += h;
+        ds += s;
+    }
+    for end in [oligo[0], oligo[oligo.len() - 1]] {
+        let (h, s) = match end.to_ascii_uppercase() {
+            b'G' | b'C' => (0.1, -2.8),
+            _ => (2.3, 4.1),
+        };
+        dh += h;
+        ds += s;
+    }
+    ds += 0.368 * (oligo.len() - 1) as f64 * conditions.sodium.ln();
+    Some(dh * 1000.0 / (ds + R * (conditions.primer_conc / 4.0).ln()) - 273.15)
+}
+
+fn pairs(a: u8, b: u8) -> bool {
+    matches!(
+        (a.to_ascii_uppercase(), b.to_ascii_uppercase()),
+        (b'A', b'T') | (b'T', b'A') | (b'C', b'G') | (b'G', b'C')
+    )
+}
+
+/// Longest run of consecutive Watson–Crick pairs when `a` and `b` anneal
+/// antiparallel at any offset.
+pub fn max_complementarity(a: &[u8], b: &[u8]) -> usize {
+    let mut best = 0;
+    let mut prev = vec![0usize; b.len() + 1];
+    let mut cur = vec![0usize; b.len() + 1];
+    for &x in a {
+        // Walking `b` backwards makes each diagonal an antiparallel duplex.
+        for (j, &y) in b.iter().rev().enumerate() {
+            cur[j + 1] = if pairs(x, y) { prev[j] + 1 } else { 0 };
+            best = best.max(cur[j + 1]);
+        }
+        std::mem::swap(&mut prev, &mut cur);
+    }
+    best
+}
+
+/// Longest stretch at the 3' end of `a` that anneals anywhere on `b`, the
+/// part of a dimer that polymerase can extend.
+pub fn end_complementarity(a: &[u8], b: &[u8]) -> usize {
+    let target = reverse_complement(b).to_ascii_uppercase();
+    let a = a.to_ascii_uppercase();
+    (1..=a.len())
+        .rev()
+        .find(|&k| {
+            let tail = &a[a.len() - k..];
+            target.windows(k).any(|w| w == tail)
+        })
+        .unwrap_or(0)
+}
+
+/// Longest hairpin stem the oligo can fold into, with a loop of at least
+/// three bases.
+pub fn hairpin_stem(oligo: &[u8]) -> usize {
+    const MIN_LOOP: usize = 3;
+    let n = oligo.len();
+    let mut best = 0;
+    // Stem closes between positions i (5' arm) and j (3' arm).
+    for i in 0..n {
+        for j in (i + MIN_LOOP + 1)..n {
+            let mut k = 0;
+            while i + k < j - k && j - k - (i + k) > MIN_LOOP && pairs(oligo[i + k], oligo[j - k]) {
+                k += 1;
+            }
+            best = best.max(k);
+        }
+    }
+    best
+}
+
+/// Limits a primer pair must satisfy.
+#[derive(Debug, Clone)]
+pub struct PrimerConstraints {
+    pub length: RangeInclusive<usize>,
+    pub optimal_length: usize,
+    pub tm: RangeInclusive<f64>,
+    pub optimal_tm: f64,
+    /// Allowed GC content, in percent.
+    pub gc_percent: RangeInclusive<f64>,
+    /// Largest allowed Tm difference between the two primers.
+    pub max_tm_difference: f64,
+    /// Longest complementary run allowed within a primer or between the pair.
+    pub max_complementarity: usize,
+    /// Longest complementary 3' end allowed within a primer or between the pair.
+    pub max_end_complementarity: usize,
+    pub max_hairpin_stem: usize,
+    pub product_size: RangeInclusive<usize>,
+    pub conditions: TmConditions,
+    /// Number of ranked pairs to return.
+    pub max_pairs: usize,
+}
+
+impl Default for PrimerConstraints {
+    fn default() -> Self {
+        PrimerConstraints {
+            length: 18..=25,
+            optimal_length: 20,
+            tm: 55.0..=65.0,
+            optimal_tm: 60.0,
+            gc_percent: 40.0..=60.0,
+            max_tm_difference: 3.0,
+            max_complementarity: 8,
+            max_end_complementarity: 3,
+            max_hairpin_stem: 4,
+            product_size: 100..=1000,
+            conditions: TmConditions::default(),
+            max_pairs: 5,
+        }
+    }
+}
+
+/// A single primer. `start` and `end` are 0-based, half-open coordinates of
+/// its binding site on the template's forward strand.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Primer {
+    /// Primer sequence written 5' to 3'.
+    pub sequence: String,
+    pub start: usize,
+    pub end: usize,
+    pub strand: Strand,
+    pub tm: f64,
+    pub gc_percent: f64,
+    penalty: f64,
+}
+
+impl Primer {
+    pub fn len(&self) -> usize {
+        self.sequence.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.sequence.is_empty()
+    }
+}
+
+/// A forward/reverse pair and the amplicon it produces.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PrimerPair {
+    pub forward: Primer,
+    pub reverse: Primer,
+    pub product_size: usize,
+    /// Deviation from the optimal length and Tm; lower is better.
+    pub penalty: f64,
+}
+
+/// Picks primer pairs whose amplicon covers `target` on `template`.
+///
+/// Forward primers must end at or before `target.start` and reverse primers
+/// must bind at or after `target.end`. Pairs are returned best first,
+/// ranked by how far the primers are from the optimal length and Tm and how
+/// closely their Tms match.
+pub fn design_primers(
+    template: &[u8],
+    target: Range<usize>,
+    constraints: &PrimerConstraints,
+) -> Vec<PrimerPair> {
+    if target.start > target.end || target.end > template.len() {
+        return Vec::new();
+    }
+    let forwards = candidates(template, constraints, Strand::Forward, |_, end| {
+        end <= target.start
+    });
+    let reverses = candidates(template, constraints, Strand::Reverse, |start, _| {
+        start >= target.end
+    });
+
+    let mut pairs = Vec::new();
+    for fwd in &forwards {
+        for rev in &reverses {
+            if rev.end <= fwd.start || !constraints.product_size.contains(&(rev.end - fwd.start)) {
+                continue;
+            }
+            let tm_diff = (fwd.tm - rev.tm).abs();
+            if tm_diff > constraints.max_tm_difference {
+                continue;
+            }
+            let (f, r) = (fwd.sequence.as_bytes(), rev.sequence.as_bytes());
+            if max_complementarity(f, r) > constraints.max_complementarity
+                || end_complementarity(f, r) > constraints.max_end_complementarity
+                || end_complementarity(r, f) > constraints.max_end_complementarity
+            {
+                continue;
+            }
+            pairs.push(PrimerPair {
+                forward: fwd.clone(),
+                reverse: rev.clone(),
+                product_size: rev.end - fwd.start,
+                penalty: fwd.penalty + rev.penalty + tm_diff,
+            });
+        }
+    }
+    pairs.sort_by(|a, b| {
+        a.penalty
+            .partial_cmp(&b.penalty)
+            .unwrap_or(Ordering::Equal)
+            .then(a.forward.start.cmp(&b.forward.start))
+            .then(b.reverse.end.cmp(&a.reverse.end))
+    });
+    pairs.truncate(constraints.max_pairs);
+    pairs
+}
+
+/// All single primers on `strand` that pass the per-primer constraints and
+/// whose binding site satisfies `placement(start, end)`.
+fn candidates<F>(
+    template: &[u8],
+    constraints: &PrimerConstraints,
+    strand: Strand,
+    placement: F,
+) -> Vec<Primer>
+where
+    F: Fn(usize, usize) -> bool,
+{
+    let mut primers = Vec::new();
+    for len in constraints.length.clone() {
+        if len > template.len() {
+            break;
+        }
+        for start in 0..=template.len() - len {
+            let end = start + len;
+            if !placement(start, end) {
+                continue;
+            }
+            let site = &template[start..end];
+            let oligo = match strand {
+                Strand::Forward => site.to_ascii_uppercase(),
+                Strand::Reverse => reverse_complement(site).to_ascii_uppercase(),
+            };
+            let Some(tm) = melting_temp(&oligo, &constraints.conditions) else {
+                continue;
+            };
+            let gc_percent = gc_content(&oligo) * 100.0;
+            if !constraints.tm.contains(&tm)
+                || !constraints.gc_percent.contains(&gc_percent)
+                || max_complementarity(&oligo, &oligo) > constraints.max_complementarity
+                || end_complementarity(&oligo, &oligo) > constraints.max_end_complementarity
+                || hairpin_stem(&oligo) > constraints.max_hairpin_stem
+            {
+                continue;
+            }
+            let penalty = (tm - constraints.optimal_tm).abs()
+                + len.abs_diff(constraints.optimal_length) as f64;
+            primers.push(Primer {
+                sequence: String::from_utf8_lossy(&oligo).into_owned(),
+                start,
+                end,
+                strand,
+                tm,
+                gc_percent,
+                penalty,
+            });
+        }
+    }
+    primers
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TEMPLATE: &[u8] = b"AGCTTGCATGCCTGCAGGTCGACTCTAGAGGATCCCCGGGTACCGAGCTCGAATTCACTGGCCGTCGTTTTACAACGTCGTGACTGGGAAAACCCTGGCGTTACCCAACTTAATCGCCTTGCAGCACATCCCCCTTTCGCCAGCTGGCGTAATAGCGAAGAGGCCCGCACCGATCGCCCTTCCCAACAGTTGCGCAGCCTGAATGGCGAATGGCGCCTGATGCGGTATTTTCTCCTTACGCATCTGTGCGGTATTTCACACCGCATATGGTGCACTCTCAGTACAATCTGCTCTGATGCCGCATAG";
+
+    #[test]
+    fn melting_temp_is_in_expected_range() {
+        let tm = melting_temp(b"AGCGGATAACAATTTCACACAGGA", &TmConditions::default()).unwrap();
+        assert!((55.0..65.0).contains(&tm), "tm = {}", tm);
+        assert!(melting_temp(b"ACGN", &TmConditions::default()).is_none());
+        // GC-rich oligos melt higher than AT-rich ones of the same length.
+        let gc = melting_temp(b"GCGCGCGCGC", &TmConditions::default()).unwrap();
+        let at = melting_temp(b"ATATATATAT", &TmConditions::default()).unwrap();
+        assert!(gc > at);
+    }
+
+    #[test]
+    fn complementarity_measures() {
+        assert_eq!(max_complementarity(b"GAATTC", b"GAATTC"), 6);
+        assert_eq!(max_complementarity(b"AAAA", b"AAAA"), 0);
+        assert_eq!(end_complementarity(b"TTTTGG", b"CCAAAA"), 6);
+        assert_eq!(end_complementarity(b"TTTTGA", b"CCGGGG"), 0);
+        assert_eq!(hairpin_stem(b"GGGGAAAACCCC"), 4);
+        assert_eq!(hairpin_stem(b"AAAAAAAA"), 0);
+    }
+
+    #[test]
+    fn designs_pairs_flanking_target() {
+        let constraints = PrimerConstraints {
+            product_size: 150..=300,
+            ..PrimerConstraints::default()
+        };
+        let pairs = design_primers(TEMPLATE, 120..140, &constraints);
+        assert!(!pairs.is_empty());
+        for pair in &pairs {
+            assert!(pair.forward.end <= 120);
+            assert!(pair.reverse.start >= 140);
+            assert!(constraints.product_size.contains(&pair.product_size));
+            assert!(constraints.tm.contains(&pair.forward.tm));
+            assert_eq!(pair.reverse.strand, Strand::Reverse);
+            let site = &TEMPLATE[pair.reverse.start..pair.reverse.end];
+            assert_eq!(reverse_complement(site), pair.reverse.sequence.as_bytes());
+        }
+        assert!(pairs.windows(2).all(|w| w[0].penalty <= w[1].penalty));
+    }
+
+    #[test]
+    fn impossible_constraints_yield_no_pairs() {
+        let constraints = PrimerConstraints {
+            product_size: 5000..=6000,
+            ..PrimerConstraints::default()
+        };
+        assert!(design_primers(TEMPLATE, 120..140, &constraints).is_empty());
+        assert!(design_primers(TEMPLATE, 120..1000, &PrimerConstraints::default()).is_empty());
+    }
+}
@@ -0,0 +1,97 @@
+//! Progress reporting hooks for the file parsers.
+
+use std::sync::mpsc::Sender;
+
+/// Snapshot of how far a parser has got through its input.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Progress {
+    pub bytes_read: u64,
+    pub records: u64,
+    /// Set on the last report, once the input is exhausted.
+    pub finished: bool,
+}
+
+/// Callback receiving progress snapshots.
+pub type ProgressFn = Box<dyn FnMut(Progress) + Send>;
+
+/// Bytes between reports while inside a single long record.
+pub const DEFAULT_INTERVAL: u64 = 1 << 20;
+
+/// Callback forwarding every snapshot to `sender`, for rendering progress
+/// on another thread. Send errors are ignored so a dropped receiver never
+/// interrupts parsing.
+pub fn to_channel(sender: Sender<Progress>) -> ProgressFn {
+    Box::new(move |progress| {
+        let _ = sender.send(progress);
+    })
+}
+
+/// Counts bytes and records and decides when to call the callback: after
+/// every record, whenever `interval` bytes have passed since the last
+/// report, and once at the end.
+pub(crate) struct ProgressTracker {
+    callback: ProgressFn,
+    interval: u64,
+    last_reported: u64,
+    progress: Progress,
+}
+
+impl ProgressTracker {
+    pub(crate) fn new(callback: ProgressFn, interval: u64) -> Self {
+        ProgressTracker {
+            callback,
+            interval: interval.max(1),
+            last_reported: 0,
+            progress: Progress::default(),
+        }
+    }
+
+    pub(crate) fn add_bytes(&mut self, bytes: usize) {
+        self.progress.bytes_read += bytes as u64;
+        if self.progress.bytes_read - self.last_reported >= self.interval {
+            self.report();
+        }
+    }
+
+    pub(crate) fn add_record(&mut self) {
+        self.progress.records += 1;
+        self.report();
+    }
+
+    pub(crate) fn finish(&mut self) {
+        if !self.progress.finished {
+            self.progress.finished = true;
+            self.report();
+        }
+    }
+
+    fn report(&mut self) {
+        self.last_reported = self.progress.bytes_read;
+        (self.callback)(self.progress);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::mpsc;
+
+    #[test]
+    fn tracker_reports_on_interval_records_and_finish() {
+        let (tx, rx) = mpsc::channel();
+        let mut tracker = ProgressTracker::new(to_channel(tx), 10);
+        tracker.add_bytes(4);
+        tracker.add_bytes(6);
+        tracker.add_record();
+        tracker.add_bytes(3);
+        tracker.finish();
+        tracker.finish();
+        drop(tracker);
+        let reports: Vec<Progress> = rx.iter().collect();
+        let summary: Vec<(u64, u64, bool)> = reports
+            .iter()
+            .map(|p| (p.bytes_read, p.records, p.finished))
+            .collect();
+        assert_eq!(summary, vec![(10, 0, false), (10, 1, false), (13, 1, true)]);
+    }
+}
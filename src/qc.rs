@@ -0,0 +1,377 @@
+//! FastQC-style quality control statistics for FASTQ reads.
+//!
+//! A [`QcCollector`] accumulates statistics one read at a time, so files
+//! of any size are processed in a single streaming pass; [`run`] wires it
+//! to a [`FastqReader`].
+
+use std::collections::{BTreeMap, HashMap};
+use std::io::BufRead;
+
+use crate::fastq::{FastqError, FastqReader, FastqSeq};
+use crate::json;
+
+/// Number of distinct Phred scores tracked per cycle, 0 to 93.
+const QUALITY_LEVELS: usize = 94;
+
+/// Settings for overrepresented sequence detection.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct QcOptions {
+    /// Fraction of all reads a sequence must exceed to be reported.
+    pub overrepresented_fraction: f64,
+    /// Distinct sequences tracked; once reached, only already-tracked
+    /// sequences keep being counted, which bounds memory.
+    pub max_tracked_sequences: usize,
+    /// Reads longer than 75 bases are cut to this many bases before
+    /// counting, so reads differing only in their error-prone tails group.
+    pub overrepresented_prefix: usize,
+}
+
+impl Default for QcOptions {
+    fn default() -> Self {
+        QcOptions {
+            overrepresented_fraction: 0.001,
+            max_tracked_sequences: 100_000,
+            overrepresented_prefix: 50,
+        }
+    }
+}
+
+/// Quality score distribution at one read position.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CycleQuality {
+    /// 1-based read position.
+    pub cycle: usize,
+    pub mean: f64,
+    pub median: u8,
+    pub lower_quartile: u8,
+    pub upper_quartile: u8,
+    pub percentile_10: u8,
+    pub percentile_90: u8,
+}
+
+/// Base percentages at one read position. Symbols other than `ACGTU` are
+/// counted as `N`; `U` is counted as `T`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CycleComposition {
+    /// 1-based read position.
+    pub cycle: usize,
+    pub a: f64,
+    pub c: f64,
+    pub g: f64,
+    pub t: f64,
+    pub n: f64,
+}
+
+/// A sequence making up a suspiciously large share of the reads, often an
+/// adapter or primer dimer.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Overrepresented {
+    pub sequence: String,
+    pub count: u64,
+    pub percent: f64,
+}
+
+/// Summary of a set of reads.
+#[derive(Debug, Clone, PartialEq)]
+pub struct QcReport {
+    pub total_reads: u64,
+    pub total_bases: u64,
+    pub per_cycle_quality: Vec<CycleQuality>,
+    pub per_cycle_composition: Vec<CycleComposition>,
+    /// Number of reads at each rounded GC percentage, 0 to 100.
+    pub gc_distribution: Vec<u64>,
+    /// `(length, reads)` pairs in increasing length order.
+    pub length_distribution: Vec<(usize, u64)>,
+    /// Most frequent first.
+    pub overrepresented: Vec<Overrepresented>,
+}
+
+/// Streaming accumulator behind [`QcReport`].
+#[derive(Debug, Clone)]
+pub struct QcCollector {
+    options: QcOptions,
+    reads: u64,
+    bases: u64,
+    quality: Vec<[u64; QUALITY_LEVELS]>,
+    /// A, C, G, T and N counts per cycle.
+    composition: Vec<[u64; 5]>,
+    gc: [u64; 101],
+    lengths: BTreeMap<usize, u64>,
+    sequences: HashMap<String, u64>,
+}
+
+impl QcCollector {
+    pub fn new(options: QcOptions) -> Self {
+        QcCollector {
+            options,
+            reads: 0,
+            bases: 0,
+            quality: Vec::new(),
+            composition: Vec::new(),
+            gc: [0; 101],
+            lengths: BTreeMap::new(),
+            sequences: HashMap::new(),
+        }
+    }
+
+    pub fn add(&mut self, read: &FastqSeq) {
+        let len = read.len();
+        self.reads += 1;
+        self.bases += len as u64;
+        *self.lengths.entry(len).or_insert(0) += 1;
+        if len > self.quality.len() {
+            self.quality.resize(len, [0; QUALITY_LEVELS]);
+            self.composition.resize(len, [0; 5]);
+        }
+
+        for (cycle, score) in read.phred_scores().enumerate() {
+            self.quality[cycle][(score as usize).min(QUALITY_LEVELS - 1)] += 1;
+        }
+        let mut gc = 0;
+        for (cycle, base) in read.sequence().bytes().enumerate() {
+            let slot = match base.to_ascii_uppercase() {
+                b'A' => 0,
+                b'C' => 1,
+                b'G' => 2,
+                b'T' | b'U' => 3,
+                _ => 4,
+            };
+            if slot == 1 || slot == 2 {
+                gc += 1;
+            }
+            self.composition[cycle][slot] += 1;
+        }
+        if let Some(percent) = (gc * 100 + len / 2).checked_div(len) {
+            self.gc[percent] += 1;
+        }
+
+        let key = if len > 75 {
+            &read.sequence()[..self.options.overrepresented_prefix.min(len)]
+        } else {
+            read.sequence()
+        };
+        if let Some(count) = self.sequences.get_mut(key) {
+            *count += 1;
+        } else if self.sequences.len() < self.options.max_tracked_sequences {
+            self.sequences.insert(key.to_string(), 1);
+        }
+    }
+
+    pub fn finish(self) -> QcReport {
+        let per_cycle_quality = self
+            .quality
+            .iter()
+            .enumerate()
+            .map(|(i, hist)| cycle_quality(i + 1, hist))
+            .collect();
+        let per_cycle_composition = self
+            .composition
+            .iter()
+            .enumerate()
+            .map(|(i, counts)| {
+                let total = counts.iter().sum::<u64>() as f64;
+                let pct = |n: u64| 100.0 * n as f64 / total;
+                CycleComposition {
+                    cycle: i + 1,
+                    a: pct(counts[0]),
+                    c: pct(counts[1]),
+                    g: pct(counts[2]),
+                    t: pct(counts[3]),
+                    n: pct(counts[4]),
+                }
+            })
+            .collect();
+
+        let threshold = self.options.overrepresented_fraction * self.reads as f64;
+        let mut overrepresented: Vec<Overrepresented> = self
+            .sequences
+            .into_iter()
+            .filter(|&(_, count)| count as f64 > threshold)
+            .map(|(sequence, count)| Overrepresented {
+                sequence,
+                count,
+                percent: 100.0 * count as f64 / self.reads as f64,
+            })
+            .collect();
+        overrepresented.sort_by(|a, b| b.count.cmp(&a.count).then(a.sequence.cmp(&b.sequence)));
+
+        QcReport {
+            total_reads: self.reads,
+            total_bases: self.bases,
+            per_cycle_quality,
+            per_cycle_composition,
+            gc_distribution: self.gc.to_vec(),
+            length_distribution: self.lengths.into_iter().collect(),
+            overrepresented,
+        }
+    }
+}
+
+impl Default for QcCollector {
+    fn default() -> Self {
+        Self::new(QcOptions::default())
+    }
+}
+
+fn cycle_quality(cycle: usize, hist: &[u64; QUALITY_LEVELS]) -> CycleQuality {
+    let total: u64 = hist.iter().sum();
+    let weighted: u64 = hist.iter().enumerate().map(|(q, &n)| q as u64 * n).sum();
+    // Lowest score reached by at least `fraction` of the bases.
+    let percentile = |fraction: f64| {
+        let rank = ((fraction * total as f64).ceil() as u64).max(1);
+        let mut seen = 0;
+        for (q, &n) in hist.iter().enumerate() {
+            seen += n;
+            if seen >= rank {
+                return q as u8;
+            }
+        }
+        (QUALITY_LEVELS - 1) as u8
+    };
+    CycleQuality {
+        cycle,
+        mean: weighted as f64 / total as f64,
+        median: percentile(0.5),
+        lower_quartile: percentile(0.25),
+        upper_quartile: percentile(0.75),
+        percentile_10: percentile(0.1),
+        percentile_90: percentile(0.9),
+    }
+}
+
+/// Reads every record from `reader` and summarises them.
+pub fn run<R: BufRead>(reader: FastqReader<R>, options: QcOptions) -> Result<QcReport, FastqError> {
+    let mut collector = QcCollector::new(options);
+    for read in reader {
+        collector.add(&read?);
+    }
+    Ok(collector.finish())
+}
+
+impl QcReport {
+    pub fn to_json(&self) -> String {
+        let quality = self.per_cycle_quality.iter().map(|q| {
+            format!(
+                "{{\"cycle\":{},\"mean\":{},\"median\":{},\"lower_quartile\":{},\"upper_quartile\":{},\"percentile_10\":{},\"percentile_90\":{}}}",
+                q.cycle,
+                json::number(q.mean),
+                q.median,
+                q.lower_quartile,
+                q.upper_quartile,
+                q.percentile_10,
+                q.percentile_90
+            )
+        });
+        let composition = self.per_cycle_composition.iter().map(|c| {
+            format!(
+                "{{\"cycle\":{},\"A\":{},\"C\":{},\"G\":{},\"T\":{},\"N\":{}}}",
+                c.cycle,
+                json::number(c.a),
+                json::number(c.c),
+                json::number(c.g),
+                json::number(c.t),
+                json::number(c.n)
+            )
+        });
+        let lengths = self
+            .length_distribution
+            .iter()
+            .map(|(len, n)| format!("{{\"length\":{},\"count\":{}}}", len, n));
+        let overrepresented = self.overrepresented.iter().map(|o| {
+            format!(
+                "{{\"sequence\":{},\"count\":{},\"percent\":{}}}",
+                json::string(&o.sequence),
+                o.count,
+                json::number(o.percent)
+            )
+        });
+        format!(
+            "{{\"total_reads\":{},\"total_bases\":{},\"per_cycle_quality\":{},\"per_cycle_composition\":{},\"gc_distribution\":{},\"length_distribution\":{},\"overrepresented\":{}}}",
+            self.total_reads,
+            self.total_bases,
+            json::array(quality),
+            json::array(composition),
+            json::array(self.gc_distribution.iter().map(u64::to_string)),
+            json::array(lengths),
+            json::array(overrepresented)
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn report(text: &str) -> QcReport {
+        run(FastqReader::new(text.as_bytes()), QcOptions::default()).unwrap()
+    }
+
+    #[test]
+    fn per_cycle_quality_quartiles() {
+        // Cycle 1 scores: 10, 20, 30, 40; cycle 2 only from the longer reads.
+        let qc = report("@a\nAC\n+\n+5\n@b\nA\n+\n5\n@c\nGG\n+\n?5\n@d\nT\n+\nI\n");
+        assert_eq!(qc.total_reads, 4);
+        assert_eq!(qc.total_bases, 6);
+        let first = qc.per_cycle_quality[0];
+        assert_eq!(first.mean, 25.0);
+        assert_eq!(
+            (first.lower_quartile, first.median, first.upper_quartile),
+            (10, 20, 30)
+        );
+        assert_eq!((first.percentile_10, first.percentile_90), (10, 40));
+        assert_eq!(qc.per_cycle_quality[1].median, 20);
+        assert_eq!(qc.length_distribution, vec![(1, 2), (2, 2)]);
+    }
+
+    #[test]
+    fn composition_and_gc_distribution() {
+        let qc = report("@a\nACGN\n+\nIIII\n@b\nAAGG\n+\nIIII\n");
+        let first = qc.per_cycle_composition[0];
+        assert_eq!((first.a, first.c), (100.0, 0.0));
+        assert_eq!(qc.per_cycle_composition[3].n, 50.0);
+        assert_eq!(qc.gc_distribution.len(), 101);
+        assert_eq!(qc.gc_distribution[50], 2);
+    }
+
+    #[test]
+    fn detects_overrepresented_sequences() {
+        let mut text = String::new();
+        for i in 0..10 {
+            let seq = if i < 6 {
+                "AGATCGGAAG"
+            } else {
+                ["ACGTACGTAA", "TTTTGGGGCC", "CACACACACA", "GTGTGTGTGT"][i - 6]
+            };
+            text.push_str(&format!("@r{}\n{}\n+\nIIIIIIIIII\n", i, seq));
+        }
+        let qc = run(
+            FastqReader::new(text.as_bytes()),
+            QcOptions {
+                overrepresented_fraction: 0.2,
+                ..QcOptions::default()
+            },
+        )
+        .unwrap();
+        assert_eq!(
+            qc.overrepresented,
+            vec![Overrepresented {
+                sequence: "AGATCGGAAG".to_string(),
+                count: 6,
+                percent: 60.0
+            }]
+        );
+        let json = qc.to_json();
+        assert!(json.starts_with("{\"total_reads\":10,\"total_bases\":100,"));
+        assert!(json.ends_with(
+            "\"overrepresented\":[{\"sequence\":\"AGATCGGAAG\",\"count\":6,\"percent\":60}]}"
+        ));
+    }
+
+    #[test]
+    fn empty_input_gives_empty_report() {
+        let qc = report("");
+        assert_eq!(qc.total_reads, 0);
+        assert!(qc.per_cycle_quality.is_empty());
+        assert!(qc.overrepresented.is_empty());
+    }
+}
@@ -0,0 +1,362 @@
+//! Composable read filtering and trimming.
+//!
+//! A [`Pipeline`] chains steps that either drop a read or rewrite it, and
+//! runs them over single or paired reads in one streaming pass, counting
+//! what each step did.
+
+use std::io::{BufRead, Write};
+
+use crate::fastq::{FastqError, FastqReader, FastqSeq};
+use crate::paired::{PairError, PairedReads};
+
+type Predicate = Box<dyn Fn(&FastqSeq) -> bool + Send + Sync>;
+type Transform = Box<dyn Fn(FastqSeq) -> FastqSeq + Send + Sync>;
+
+enum Step {
+    Filter(Predicate),
+    Transform(Transform),
+}
+
+/// What one pipeline step did.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StepStats {
+    pub name: String,
+    /// Reads (or pairs) dropped by this step.
+    pub removed: u64,
+    /// Reads whose sequence or qualities this step changed.
+    pub modified: u64,
+}
+
+/// Summary of a pipeline run. For paired runs counts are in pairs, except
+/// `modified`, which counts reads.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PipelineStats {
+    pub input: u64,
+    pub output: u64,
+    pub steps: Vec<StepStats>,
+}
+
+/// Ordered read filters and transforms. Each read goes through the steps
+/// in the order they were added, and stops at the first filter it fails.
+#[derive(Default)]
+pub struct Pipeline {
+    steps: Vec<(String, Step)>,
+}
+
+impl Pipeline {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Keeps reads passing `predicate`; `name` labels the step in the stats.
+    pub fn filter<F>(mut self, name: &str, predicate: F) -> Self
+    where
+        F: Fn(&FastqSeq) -> bool + Send + Sync + 'static,
+    {
+        self.steps
+            .push((name.to_string(), Step::Filter(Box::new(predicate))));
+        self
+    }
+
+    /// Rewrites reads with `transform`; `name` labels the step in the stats.
+    pub fn transform<F>(mut self, name: &str, transform: F) -> Self
+    where
+        F: Fn(FastqSeq) -> FastqSeq + Send + Sync + 'static,
+    {
+        self.steps
+            .push((name.to_string(), Step::Transform(Box::new(transform))));
+        self
+    }
+
+    pub fn min_length(self, len: usize) -> Self {
+        self.filter("min_length", move |read| read.len() >= len)
+    }
+
+    pub fn max_length(self, len: usize) -> Self {
+        self.filter("max_length", move |read| read.len() <= len)
+    }
+
+    /// Drops reads with more than `max` expected errors; see
+    /// [`FastqSeq::expected_errors`].
+    pub fn max_expected_errors(self, max: f64) -> Self {
+        self.filter("max_expected_errors", move |read| {
+            read.expected_errors() <= max
+        })
+    }
+
+    /// Removes `start` bases from the 5' end and `end` bases from the 3'
+    /// end; reads shorter than that become empty.
+    pub fn trim(self, start: usize, end: usize) -> Self {
+        self.transform("trim", move |read| {
+            let len = read.len();
+            let stop = len.saturating_sub(end);
+            read.slice(start.min(stop)..stop)
+        })
+    }
+
+    /// Trims the 3' end with BWA's algorithm: cuts where the summed
+    /// shortfall of qualities below `threshold` is largest, so isolated
+    /// good bases in a poor tail are removed too.
+    pub fn trim_quality(self, threshold: u8) -> Self {
+        self.transform("trim_quality", move |read| {
+            let scores: Vec<u8> = read.phred_scores().collect();
+            let mut sum = 0i64;
+            let mut best = (0i64, scores.len());
+            for (i, &q) in scores.iter().enumerate().rev() {
+                sum += i64::from(threshold) - i64::from(q);
+                if sum < 0 {
+                    break;
+                }
+                if sum > best.0 {
+                    best = (sum, i);
+                }
+            }
+            read.slice(0..best.1)
+        })
+    }
+
+    /// Replaces bases with quality below `threshold` by `N`.
+    pub fn mask(self, threshold: u8) -> Self {
+        self.transform("mask", move |read| {
+            let masked: String = read
+                .sequence()
+                .chars()
+                .zip(read.phred_scores())
+                .map(|(base, q)| if q < threshold { 'N' } else { base })
+                .collect();
+            read.with_sequence(masked)
+        })
+    }
+
+    /// Zeroed counts for this pipeline's steps, to pass to
+    /// [`apply`](Self::apply) and [`apply_pair`](Self::apply_pair).
+    pub fn new_stats(&self) -> PipelineStats {
+        PipelineStats {
+            input: 0,
+            output: 0,
+            steps: self
+                .steps
+                .iter()
+                .map(|(name, _)| StepStats {
+                    name: name.clone(),
+                    removed: 0,
+                    modified: 0,
+                })
+                .collect(),
+        }
+    }
+
+    /// Runs step `index` on `read`: `None` if it is filtered out.
+    fn apply_step(
+        &self,
+        index: usize,
+        read: FastqSeq,
+        stats: &mut PipelineStats,
+    ) -> Option<FastqSeq> {
+        match &self.steps[index].1 {
+            Step::Filter(predicate) => predicate(&read).then_some(read),
+            Step::Transform(transform) => {
+                let before = (read.sequence().to_string(), read.quality().to_string());
+                let after = transform(read);
+                if after.sequence() != before.0 || after.quality() != before.1 {
+                    stats.steps[index].modified += 1;
+                }
+                Some(after)
+            }
+        }
+    }
+
+    /// Applies every step to one read, updating `stats`.
+    pub fn apply(&self, read: FastqSeq, stats: &mut PipelineStats) -> Option<FastqSeq> {
+        stats.input += 1;
+        let mut read = read;
+        for index in 0..self.steps.len() {
+            match self.apply_step(index, read, stats) {
+                Some(next) => read = next,
+                None => {
+                    stats.steps[index].removed += 1;
+                    return None;
+                }
+            }
+        }
+        stats.output += 1;
+        Some(read)
+    }
+
+    /// Applies every step to both mates; the pair is dropped as soon as
+    /// either mate fails a filter.
+    pub fn apply_pair(
+        &self,
+        pair: (FastqSeq, FastqSeq),
+        stats: &mut PipelineStats,
+    ) -> Option<(FastqSeq, FastqSeq)> {
+        stats.input += 1;
+        let (mut first, mut second) = pair;
+        for index in 0..self.steps.len() {
+            let next = self
+                .apply_step(index, first, stats)
+                .and_then(|a| Some((a, self.apply_step(index, second, stats)?)));
+            match next {
+                Some((a, b)) => (first, second) = (a, b),
+                None => {
+                    stats.steps[index].removed += 1;
+                    return None;
+                }
+            }
+        }
+        stats.output += 1;
+        Some((first, second))
+    }
+
+    /// Streams reads from `reader` through the pipeline into `out`.
+    pub fn run<R: BufRead, W: Write>(
+        &self,
+        reader: FastqReader<R>,
+        out: &mut W,
+    ) -> Result<PipelineStats, FastqError> {
+        let mut stats = self.new_stats();
+        for read in reader {
+            if let Some(read) = self.apply(read?, &mut stats) {
+                write!(out, "{}", read)?;
+            }
+        }
+        Ok(stats)
+    }
+
+    /// Streams R1/R2 pairs through the pipeline, keeping mates in sync.
+    pub fn run_pairs<R1: BufRead, R2: BufRead, W1: Write, W2: Write>(
+        &self,
+        pairs: PairedReads<R1, R2>,
+        out1: &mut W1,
+        out2: &mut W2,
+    ) -> Result<PipelineStats, PairError> {
+        let mut stats = self.new_stats();
+        for pair in pairs {
+            if let Some((first, second)) = self.apply_pair(pair?, &mut stats) {
+                write!(out1, "{}", first)?;
+                write!(out2, "{}", second)?;
+            }
+        }
+        Ok(stats)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn read(id: &str, seq: &str, qual: &str) -> FastqSeq {
+        FastqSeq::new(id, "", seq, qual).unwrap()
+    }
+
+    #[test]
+    fn filters_stop_at_first_failure() {
+        let pipeline = Pipeline::new().min_length(3).max_expected_errors(0.5);
+        let mut stats = pipeline.new_stats();
+        assert!(pipeline.apply(read("a", "AC", "II"), &mut stats).is_none());
+        assert!(pipeline
+            .apply(read("b", "ACG", "I!I"), &mut stats)
+            .is_none());
+        assert!(pipeline
+            .apply(read("c", "ACG", "III"), &mut stats)
+            .is_some());
+        assert_eq!((stats.input, stats.output), (3, 1));
+        assert_eq!(stats.steps[0].removed, 1);
+        assert_eq!(stats.steps[1].removed, 1);
+    }
+
+    fn apply_one(pipeline: Pipeline, read: FastqSeq) -> FastqSeq {
+        let mut stats = pipeline.new_stats();
+        let read = pipeline.apply(read, &mut stats).unwrap();
+        assert_eq!(stats.steps[0].modified, 1);
+        read
+    }
+
+    #[test]
+    fn trims_and_masks() {
+        let trimmed = apply_one(Pipeline::new().trim(1, 2), read("a", "ACGTAC", "IIIIII"));
+        assert_eq!(trimmed.sequence(), "CGT");
+
+        // Qualities 40 40 40 2 30 2 2: the lone 30 in the tail goes too.
+        let trimmed = apply_one(
+            Pipeline::new().trim_quality(20),
+            read("a", "ACGTACG", "III#?##"),
+        );
+        assert_eq!(trimmed.sequence(), "ACG");
+
+        let masked = apply_one(Pipeline::new().mask(20), read("a", "ACGT", "I#I#"));
+        assert_eq!((masked.sequence(), masked.quality()), ("ANGN", "I#I#"));
+        let masked = apply_one(Pipeline::new().mask(255), read("a", "AC", "~~"));
+        assert_eq!(masked.sequence(), "NN");
+        let pipeline = Pipeline::new().mask(20);
+        let placeholder = pipeline.apply(FastqSeq::default(), &mut pipeline.new_stats());
+        assert_eq!(placeholder, Some(FastqSeq::default()));
+    }
+
+    #[test]
+    fn runs_over_a_stream_with_custom_steps() {
+        let pipeline = Pipeline::new()
+            .transform("uppercase", |read| {
+                FastqSeq::new(
+                    read.id(),
+                    read.description(),
+                    &read.sequence().to_ascii_uppercase(),
+                    read.quality(),
+                )
+                .unwrap()
+            })
+            .filter("no_n", |read| !read.sequence().contains('N'))
+            .min_length(2);
+        let input = "@a\nacgt\n+\nIIII\n@b\nANGT\n+\nIIII\n@c\nA\n+\nI\n";
+        let mut out = Vec::new();
+        let stats = pipeline
+            .run(FastqReader::new(input.as_bytes()), &mut out)
+            .unwrap();
+        assert_eq!(String::from_utf8(out).unwrap(), "@a\nACGT\n+\nIIII\n");
+        let summary: Vec<(&str, u64, u64)> = stats
+            .steps
+            .iter()
+            .map(|s| (s.name.as_str(), s.removed, s.modified))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![("uppercase", 0, 1), ("no_n", 1, 0), ("min_length", 1, 0)]
+        );
+    }
+
+    #[test]
+    fn drops_pairs_when_either_mate_fails() {
+        let r1 = "@p1/1\nACGT\n+\nIIII\n@p2/1\nACGT\n+\nIIII\n";
+        let r2 = "@p1/2\nAC\n+\nII\n@p2/2\nACGTA\n+\nIIIII\n";
+        let pairs = PairedReads::new(
+            FastqReader::new(r1.as_bytes()),
+            FastqReader::new(r2.as_bytes()),
+        );
+        let (mut out1, mut out2) = (Vec::new(), Vec::new());
+        let stats = Pipeline::new()
+            .min_length(3)
+            .run_pairs(pairs, &mut out1, &mut out2)
+            .unwrap();
+        assert_eq!(
+            (stats.input, stats.output, stats.steps[0].removed),
+            (2, 1, 1)
+        );
+        assert!(String::from_utf8(out1).unwrap().starts_with("@p2/1\n"));
+        assert!(String::from_utf8(out2).unwrap().starts_with("@p2/2\n"));
+    }
+
+    #[test]
+    fn skips_second_mate_once_the_first_is_dropped() {
+        let pipeline = Pipeline::new().min_length(3).trim(1, 0);
+        let mut stats = pipeline.new_stats();
+        let pair = (read("p/1", "AC", "II"), read("p/2", "ACGT", "IIII"));
+        assert!(pipeline.apply_pair(pair, &mut stats).is_none());
+        assert_eq!((stats.steps[0].removed, stats.steps[1].modified), (1, 0));
+
+        let pipeline = Pipeline::new().trim(1, 0).min_length(3);
+        let mut stats = pipeline.new_stats();
+        let pair = (read("p/1", "AC", "II"), read("p/2", "ACGT", "IIII"));
+        assert!(pipeline.apply_pair(pair, &mut stats).is_none());
+        assert_eq!((stats.steps[0].modified, stats.steps[1].removed), (2, 1));
+    }
+}
@@ -0,0 +1,325 @@
+//! Genomic regions and explicit conversion between the coordinate
+//! conventions of annotation formats.
+//!
+//! [`Interval`] and [`Region`] always store 0-based, half-open coordinates,
+//! the convention used everywhere else in this crate. Positions coming from
+//! or going to a file go through [`Interval::from_format`] and
+//! [`Interval::to_format`], so a BED start and a GFF start can never be
+//! mixed up by accident.
+
+use std::fmt;
+use std::str::FromStr;
+
+use crate::seq::Strand;
+
+/// How a format numbers positions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CoordSystem {
+    /// First base is 0, the end is one past the last base (BED, BAM).
+    ZeroBasedHalfOpen,
+    /// First base is 1, the end is the last base (GFF, VCF, SAM, region
+    /// strings such as `chr1:100-200`).
+    OneBasedClosed,
+}
+
+/// Annotation formats with a known coordinate convention.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CoordFormat {
+    Bed,
+    Gff,
+    Vcf,
+    Sam,
+    /// `seqid:start-end` strings as used by samtools and genome browsers.
+    RegionString,
+}
+
+impl CoordFormat {
+    pub fn coord_system(&self) -> CoordSystem {
+        match self {
+            CoordFormat::Bed => CoordSystem::ZeroBasedHalfOpen,
+            CoordFormat::Gff | CoordFormat::Vcf | CoordFormat::Sam | CoordFormat::RegionString => {
+                CoordSystem::OneBasedClosed
+            }
+        }
+    }
+}
+
+/// Errors raised when converting or parsing coordinates.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CoordError {
+    /// A 1-based position of 0.
+    ZeroPosition,
+    /// An end before the start, in the coordinates as given.
+    Inverted { start: usize, end: usize },
+    /// A region string that is not `seqid`, `seqid:start` or
+    /// `seqid:start-end`.
+    InvalidRegion(String),
+}
+
+impl fmt::Display for CoordError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CoordError::ZeroPosition => write!(f, "1-based position cannot be 0"),
+            CoordError::Inverted { start, end } => {
+                write!(f, "end {} is before start {}", end, start)
+            }
+            CoordError::InvalidRegion(region) => write!(f, "invalid region {:?}", region),
+        }
+    }
+}
+
+impl std::error::Error for CoordError {}
+
+/// A 0-based, half-open span `[start, end)`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Interval {
+    pub start: usize,
+    pub end: usize,
+}
+
+impl Interval {
+    /// # Panics
+    ///
+    /// Panics if `end < start`.
+    pub fn new(start: usize, end: usize) -> Self {
+        assert!(end >= start, "interval end is before its start");
+        Interval { start, end }
+    }
+
+    /// Interval from a start and end written in `format`'s convention.
+    /// An empty span is written `n, n` in BED but `n + 1, n` in 1-based
+    /// formats, so a 1-based end may be one less than its start.
+    pub fn from_format(format: CoordFormat, start: usize, end: usize) -> Result<Self, CoordError> {
+        match format.coord_system() {
+            CoordSystem::ZeroBasedHalfOpen if end < start => {
+                Err(CoordError::Inverted { start, end })
+            }
+            CoordSystem::ZeroBasedHalfOpen => Ok(Interval { start, end }),
+            CoordSystem::OneBasedClosed if start == 0 => Err(CoordError::ZeroPosition),
+            CoordSystem::OneBasedClosed if end.saturating_add(1) < start => {
+                Err(CoordError::Inverted { start, end })
+            }
+            CoordSystem::OneBasedClosed => Ok(Interval {
+                start: start - 1,
+                end,
+            }),
+        }
+    }
+
+    /// Start and end in `format`'s convention.
+    pub fn to_format(&self, format: CoordFormat) -> (usize, usize) {
+        match format.coord_system() {
+            CoordSystem::ZeroBasedHalfOpen => (self.start, self.end),
+            CoordSystem::OneBasedClosed => (self.start + 1, self.end),
+        }
+    }
+
+    /// Span of a VCF record's reference allele, from its `POS` and the
+    /// length of `REF`.
+    pub fn from_vcf(pos: usize, ref_len: usize) -> Result<Self, CoordError> {
+        if pos == 0 {
+            return Err(CoordError::ZeroPosition);
+        }
+        Ok(Interval {
+            start: pos - 1,
+            end: pos - 1 + ref_len,
+        })
+    }
+
+    /// The VCF `POS` of an allele starting here.
+    pub fn vcf_pos(&self) -> usize {
+        self.start + 1
+    }
+
+    pub fn len(&self) -> usize {
+        self.end - self.start
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.start == self.end
+    }
+
+    pub fn contains(&self, pos: usize) -> bool {
+        self.start <= pos && pos < self.end
+    }
+
+    pub fn overlaps(&self, other: &Interval) -> bool {
+        self.start < other.end && other.start < self.end
+    }
+
+    /// The shared part of two intervals, if they overlap.
+    pub fn intersect(&self, other: &Interval) -> Option<Interval> {
+        self.overlaps(other).then(|| Interval {
+            start: self.start.max(other.start),
+            end: self.end.min(other.end),
+        })
+    }
+}
+
+/// An interval on a named sequence, optionally stranded.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Region {
+    pub seqid: String,
+    pub interval: Interval,
+    pub strand: Option<Strand>,
+}
+
+impl Region {
+    pub fn new(seqid: &str, interval: Interval) -> Self {
+        Region {
+            seqid: seqid.to_string(),
+            interval,
+            strand: None,
+        }
+    }
+
+    pub fn with_strand(mut self, strand: Strand) -> Self {
+        self.strand = Some(strand);
+        self
+    }
+
+    /// Region from a start and end written in `format`'s convention.
+    pub fn from_format(
+        format: CoordFormat,
+        seqid: &str,
+        start: usize,
+        end: usize,
+    ) -> Result<Self, CoordError> {
+        Ok(Region::new(
+            seqid,
+            Interval::from_format(format, start, end)?,
+        ))
+    }
+
+    pub fn start(&self) -> usize {
+        self.interval.start
+    }
+
+    pub fn end(&self) -> usize {
+        self.interval.end
+    }
+
+    pub fn len(&self) -> usize {
+        self.interval.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.interval.is_empty()
+    }
+
+    /// Whether both regions are on the same sequence and overlap.
+    /// Strand is ignored.
+    pub fn overlaps(&self, other: &Region) -> bool {
+        self.seqid == other.seqid && self.interval.overlaps(&other.interval)
+    }
+
+    /// The `chrom`, `chromStart` and `chromEnd` columns of a BED line.
+    pub fn to_bed(&self) -> String {
+        let (start, end) = self.interval.to_format(CoordFormat::Bed);
+        format!("{}\t{}\t{}", self.seqid, start, end)
+    }
+}
+
+/// Formats as a 1-based, inclusive `seqid:start-end` string.
+impl fmt::Display for Region {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (start, end) = self.interval.to_format(CoordFormat::RegionString);
+        write!(f, "{}:{}-{}", self.seqid, start, end)
+    }
+}
+
+/// Parses `seqid:start-end` or `seqid:start` (1-based, inclusive, commas
+/// allowed). A bare `seqid` or `seqid:start` is open-ended and gets an end of
+/// `usize::MAX`, to be clamped to the sequence length by the caller.
+impl FromStr for Region {
+    type Err = CoordError;
+
+    fn from_str(text: &str) -> Result<Self, CoordError> {
+        let invalid = || CoordError::InvalidRegion(text.to_string());
+        let Some((seqid, span)) = text.rsplit_once(':') else {
+            if text.is_empty() {
+                return Err(invalid());
+            }
+            return Ok(Region::new(text, Interval::new(0, usize::MAX)));
+        };
+        let parse = |n: &str| n.replace(',', "").parse::<usize>().map_err(|_| invalid());
+        let (start, end) = match span.split_once('-') {
+            Some((start, end)) => (parse(start)?, parse(end)?),
+            None => (parse(span)?, usize::MAX),
+        };
+        if seqid.is_empty() || end < start {
+            return Err(invalid());
+        }
+        Ok(Region::new(
+            seqid,
+            Interval::from_format(CoordFormat::RegionString, start, end)?,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn converts_between_conventions() {
+        // BED 99..200 and GFF 100..200 are the same 101 bases.
+        let bed = Interval::from_format(CoordFormat::Bed, 99, 200).unwrap();
+        let gff = Interval::from_format(CoordFormat::Gff, 100, 200).unwrap();
+        assert_eq!(bed, gff);
+        assert_eq!(bed.len(), 101);
+        assert_eq!(gff.to_format(CoordFormat::Bed), (99, 200));
+        assert_eq!(bed.to_format(CoordFormat::Vcf), (100, 200));
+
+        let snp = Interval::from_vcf(100, 1).unwrap();
+        assert_eq!(snp, Interval::new(99, 100));
+        assert_eq!(snp.vcf_pos(), 100);
+    }
+
+    #[test]
+    fn handles_empty_and_invalid_spans() {
+        let empty = Interval::from_format(CoordFormat::Gff, 11, 10).unwrap();
+        assert!(empty.is_empty());
+        assert_eq!(empty.to_format(CoordFormat::Bed), (10, 10));
+        assert_eq!(
+            Interval::from_format(CoordFormat::Gff, 0, 10),
+            Err(CoordError::ZeroPosition)
+        );
+        assert_eq!(
+            Interval::from_format(CoordFormat::Bed, 10, 9),
+            Err(CoordError::Inverted { start: 10, end: 9 })
+        );
+        assert_eq!(Interval::from_vcf(0, 1), Err(CoordError::ZeroPosition));
+    }
+
+    #[test]
+    fn parses_and_formats_region_strings() {
+        let region: Region = "chr1:1,001-2,000".parse().unwrap();
+        assert_eq!(region.seqid, "chr1");
+        assert_eq!((region.start(), region.end()), (1000, 2000));
+        assert_eq!(region.to_string(), "chr1:1001-2000");
+        assert_eq!(region.to_bed(), "chr1\t1000\t2000");
+
+        let open: Region = "HLA:A:5".parse().unwrap();
+        assert_eq!(open.seqid, "HLA:A");
+        assert_eq!((open.start(), open.end()), (4, usize::MAX));
+        assert_eq!("chr2".parse::<Region>().unwrap().start(), 0);
+        assert!("chr1:0-5".parse::<Region>().is_err());
+        assert!("chr1:9-5".parse::<Region>().is_err());
+        assert!("chr1:x".parse::<Region>().is_err());
+    }
+
+    #[test]
+    fn overlap_needs_same_sequence() {
+        let a = Region::from_format(CoordFormat::Bed, "chr1", 0, 10).unwrap();
+        let b = Region::from_format(CoordFormat::Gff, "chr1", 10, 20).unwrap();
+        let c = Region::from_format(CoordFormat::Gff, "chr2", 1, 20).unwrap();
+        assert!(a.overlaps(&b));
+        assert!(!a.overlaps(&c));
+        assert_eq!(
+            a.interval.intersect(&b.interval),
+            Some(Interval::new(9, 10))
+        );
+        assert!(!Interval::new(0, 5).overlaps(&Interval::new(5, 9)));
+    }
+}
@@ -0,0 +1,382 @@
+//! Restriction enzymes, digests and restriction map reports.
+
+use std::fmt::Write as _;
+
+use crate::json;
+use crate::pattern;
+use crate::seq::{reverse_complement, Strand};
+
+/// A type II restriction enzyme.
+///
+/// `cut` and `complement_cut` are the positions of the cuts on the top and
+/// bottom strands, counted from the first base of the recognition site in
+/// its 5'→3' orientation; EcoRI (`G^AATTC`) has `cut = 1` and
+/// `complement_cut = 5`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Enzyme {
+    pub name: String,
+    pub site: String,
+    pub cut: usize,
+    pub complement_cut: usize,
+}
+
+/// Commercially common enzymes as `(name, site, cut, complement_cut)`.
+const COMMON_ENZYMES: &[(&str, &str, usize, usize)] = &[
+    ("BamHI", "GGATCC", 1, 5),
+    ("BsaI", "GGTCTC", 7, 11),
+    ("EcoRI", "GAATTC", 1, 5),
+    ("EcoRV", "GATATC", 3, 3),
+    ("HindIII", "AAGCTT", 1, 5),
+    ("KpnI", "GGTACC", 5, 1),
+    ("NcoI", "CCATGG", 1, 5),
+    ("NdeI", "CATATG", 2, 4),
+    ("NheI", "GCTAGC", 1, 5),
+    ("NotI", "GCGGCCGC", 2, 6),
+    ("PstI", "CTGCAG", 5, 1),
+    ("SacI", "GAGCTC", 5, 1),
+    ("SalI", "GTCGAC", 1, 5),
+    ("SmaI", "CCCGGG", 3, 3),
+    ("XbaI", "TCTAGA", 1, 5),
+    ("XhoI", "CTCGAG", 1, 5),
+];
+
+impl Enzyme {
+    pub fn new(name: &str, site: &str, cut: usize, complement_cut: usize) -> Self {
+        Enzyme {
+            name: name.to_string(),
+            site: site.to_ascii_uppercase(),
+            cut,
+            complement_cut,
+        }
+    }
+
+    /// A commonly used enzyme by name, e.g. `"EcoRI"`.
+    pub fn by_name(name: &str) -> Option<Enzyme> {
+        COMMON_ENZYMES
+            .iter()
+            .find(|(n, ..)| n.eq_ignore_ascii_case(name))
+            .map(|&(n, site, cut, comp)| Enzyme::new(n, site, cut, comp))
+    }
+
+    /// All built-in enzymes, sorted by name.
+    pub fn common() -> Vec<Enzyme> {
+        COMMON_ENZYMES
+            .iter()
+            .map(|&(n, site, cut, comp)| Enzyme::new(n, site, cut, comp))
+            .collect()
+    }
+
+    /// Whether the recognition site is its own reverse complement.
+    pub fn is_palindromic(&self) -> bool {
+        reverse_complement(self.site.as_bytes()) == self.site.as_bytes()
+    }
+}
+
+/// Whether a sequence is a linear molecule or a circular one like a plasmid.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Topology {
+    #[default]
+    Linear,
+    Circular,
+}
+
+/// Top-strand cut positions of `enzyme` in `seq`, sorted and deduplicated.
+///
+/// A position `p` means the cut falls between bases `p - 1` and `p`
+/// (0-based). Sites are found on both strands; on circular sequences sites
+/// spanning the origin are included. On linear sequences cuts that would
+/// fall outside the sequence are dropped. Degenerate sites such as HincII's
+/// `GTYRAC` are matched with IUPAC semantics.
+pub fn cut_positions(seq: &[u8], enzyme: &Enzyme, topology: Topology) -> Vec<usize> {
+    let site = enzyme.site.as_bytes();
+    let len = seq.len();
+    if site.is_empty() || len == 0 {
+        return Vec::new();
+    }
+    let mut haystack = seq.to_ascii_uppercase();
+    if topology == Topology::Circular {
+        let wrap = (site.len() - 1).min(len);
+        haystack.extend_from_slice(&seq[..wrap].to_ascii_uppercase());
+    }
+
+    let mut cuts = Vec::new();
+    for found in pattern::find_iupac(site, &haystack) {
+        if found.start >= len {
+            break;
+        }
+        // Seen from the top strand, the bottom-strand cut of a reversed
+        // site lands `complement_cut` bases before the site's end; on a
+        // circle that may wrap back past the origin.
+        match (topology, found.strand) {
+            (Topology::Linear, strand) => {
+                let raw = match strand {
+                    Strand::Forward => Some(found.start + enzyme.cut),
+                    Strand::Reverse => found.end.checked_sub(enzyme.complement_cut),
+                };
+                if let Some(cut) = raw.filter(|&cut| cut > 0 && cut < len) {
+                    cuts.push(cut);
+                }
+            }
+            (Topology::Circular, Strand::Forward) => cuts.push((found.start + enzyme.cut) % len),
+            (Topology::Circular, Strand::Reverse) => {
+                cuts.push((found.end + len - enzyme.complement_cut % len) % len)
+            }
+        }
+    }
+    cuts.sort_unstable();
+    cuts.dedup();
+    cuts
+}
+
+/// A piece of a digested sequence. On circular sequences the fragment
+/// spanning the origin has `start + len` beyond the sequence length.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Fragment {
+    pub start: usize,
+    pub len: usize,
+}
+
+/// Fragments produced by cutting a sequence of `len` bases at `cuts`, which
+/// must be sorted.
+fn fragments(len: usize, cuts: &[usize], topology: Topology) -> Vec<Fragment> {
+    match (topology, cuts) {
+        (_, []) => vec![Fragment { start: 0, len }],
+        (Topology::Linear, _) => {
+            let mut bounds = Vec::with_capacity(cuts.len() + 2);
+            bounds.push(0);
+            bounds.extend_from_slice(cuts);
+            bounds.push(len);
+            bounds
+                .windows(2)
+                .map(|w| Fragment {
+                    start: w[0],
+                    len: w[1] - w[0],
+                })
+                .collect()
+        }
+        (Topology::Circular, _) => {
+            let mut frags: Vec<Fragment> = cuts
+                .windows(2)
+                .map(|w| Fragment {
+                    start: w[0],
+                    len: w[1] - w[0],
+                })
+                .collect();
+            let last = cuts[cuts.len() - 1];
+            frags.push(Fragment {
+                start: last,
+                len: len - last + cuts[0],
+            });
+            frags
+        }
+    }
+}
+
+/// Digests `seq` with all `enzymes` together.
+pub fn digest(seq: &[u8], enzymes: &[Enzyme], topology: Topology) -> Vec<Fragment> {
+    let mut cuts: Vec<usize> = enzymes
+        .iter()
+        .flat_map(|e| cut_positions(seq, e, topology))
+        .collect();
+    cuts.sort_unstable();
+    cuts.dedup();
+    fragments(seq.len(), &cuts, topology)
+}
+
+/// Where a single enzyme cuts and the fragments it alone would produce.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EnzymeSites {
+    pub enzyme: Enzyme,
+    pub cuts: Vec<usize>,
+    pub fragment_sizes: Vec<usize>,
+}
+
+/// Per-enzyme restriction map of one sequence.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RestrictionMap {
+    pub seq_len: usize,
+    pub topology: Topology,
+    pub enzymes: Vec<EnzymeSites>,
+}
+
+impl RestrictionMap {
+    /// Maps every enzyme separately, keeping non-cutters in the report.
+    pub fn new(seq: &[u8], enzymes: &[Enzyme], topology: Topology) -> Self {
+        let enzymes = enzymes
+            .iter()
+            .map(|enzyme| {
+                let cuts = cut_positions(seq, enzyme, topology);
+                let fragment_sizes = fragments(seq.len(), &cuts, topology)
+                    .iter()
+                    .map(|f| f.len)
+                    .collect();
+                EnzymeSites {
+                    enzyme: enzyme.clone(),
+                    cuts,
+                    fragment_sizes,
+                }
+            })
+            .collect();
+        RestrictionMap {
+            seq_len: seq.len(),
+            topology,
+            enzymes,
+        }
+    }
+
+    /// Enzymes cutting exactly once, the candidates for cloning sites.
+    pub fn unique_cutters(&self) -> Vec<&Enzyme> {
+        self.cutting(|n| n == 1)
+    }
+
+    /// Enzymes that do not cut the sequence at all.
+    pub fn non_cutters(&self) -> Vec<&Enzyme> {
+        self.cutting(|n| n == 0)
+    }
+
+    fn cutting<F: Fn(usize) -> bool>(&self, count: F) -> Vec<&Enzyme> {
+        self.enzymes
+            .iter()
+            .filter(|s| count(s.cuts.len()))
+            .map(|s| &s.enzyme)
+            .collect()
+    }
+
+    /// Tab-separated table with one row per enzyme. Cut positions and
+    /// fragment sizes are comma-separated; cut positions are the number of
+    /// bases preceding the cut.
+    pub fn to_tsv(&self) -> String {
+        let mut out = String::from("enzyme\tsite\tcut_count\tcut_positions\tfragment_sizes\n");
+        for sites in &self.enzymes {
+            let _ = writeln!(
+                out,
+                "{}\t{}\t{}\t{}\t{}",
+                sites.enzyme.name,
+                sites.enzyme.site,
+                sites.cuts.len(),
+                join(&sites.cuts),
+                join(&sites.fragment_sizes)
+            );
+        }
+        out
+    }
+
+    pub fn to_json(&self) -> String {
+        let topology = match self.topology {
+            Topology::Linear => "linear",
+            Topology::Circular => "circular",
+        };
+        let enzymes = self.enzymes.iter().map(|s| {
+            format!(
+                "{{\"name\":{},\"site\":{},\"cut_positions\":{},\"fragment_sizes\":{}}}",
+                json::string(&s.enzyme.name),
+                json::string(&s.enzyme.site),
+                json::array(s.cuts.iter().map(usize::to_string)),
+                json::array(s.fragment_sizes.iter().map(usize::to_string))
+            )
+        });
+        let unique = self
+            .unique_cutters()
+            .into_iter()
+            .map(|e| json::string(&e.name));
+        format!(
+            "{{\"length\":{},\"topology\":{},\"enzymes\":{},\"unique_cutters\":{}}}",
+            self.seq_len,
+            json::string(topology),
+            json::array(enzymes),
+            json::array(unique)
+        )
+    }
+}
+
+fn join(values: &[usize]) -> String {
+    values
+        .iter()
+        .map(usize::to_string)
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn finds_palindromic_cuts() {
+        let ecori = Enzyme::by_name("ecori").unwrap();
+        let seq = b"AAGAATTCAAAAGAATTCAA";
+        assert_eq!(cut_positions(seq, &ecori, Topology::Linear), vec![3, 13]);
+        let sizes: Vec<usize> = digest(seq, &[ecori], Topology::Linear)
+            .iter()
+            .map(|f| f.len)
+            .collect();
+        assert_eq!(sizes, vec![3, 10, 7]);
+    }
+
+    #[test]
+    fn finds_non_palindromic_sites_on_both_strands() {
+        let bsai = Enzyme::by_name("BsaI").unwrap();
+        // GGTCTC forward, then GAGACC (its reverse complement).
+        let seq = b"GGTCTCAAAAAAAAAAAAAAGAGACCAA";
+        assert_eq!(cut_positions(seq, &bsai, Topology::Linear), vec![7, 15]);
+    }
+
+    #[test]
+    fn matches_degenerate_sites() {
+        let hincii = Enzyme::new("HincII", "GTYRAC", 3, 3);
+        let seq = b"AAGTCGACAAGTTAACAA";
+        assert_eq!(cut_positions(seq, &hincii, Topology::Linear), vec![5, 13]);
+    }
+
+    #[test]
+    fn circular_sites_span_the_origin() {
+        let ecori = Enzyme::by_name("EcoRI").unwrap();
+        let seq = b"ATTCAAAAAAGA";
+        assert!(cut_positions(seq, &ecori, Topology::Linear).is_empty());
+        assert_eq!(cut_positions(seq, &ecori, Topology::Circular), vec![11]);
+        assert_eq!(
+            digest(seq, &[ecori], Topology::Circular),
+            vec![Fragment { start: 11, len: 12 }]
+        );
+    }
+
+    #[test]
+    fn circular_reverse_cuts_wrap_before_the_origin() {
+        let bsai = Enzyme::by_name("BsaI").unwrap();
+        let seq = [b"GAGACC".as_slice(), &[b'A'; 40]].concat();
+        assert!(cut_positions(&seq, &bsai, Topology::Linear).is_empty());
+        assert_eq!(cut_positions(&seq, &bsai, Topology::Circular), vec![41]);
+        let mut rotated = seq.clone();
+        rotated.rotate_right(20);
+        assert_eq!(cut_positions(&rotated, &bsai, Topology::Circular), vec![15]);
+        // The site itself spanning the origin.
+        rotated.rotate_left(23);
+        assert_eq!(cut_positions(&rotated, &bsai, Topology::Circular), vec![38]);
+    }
+
+    #[test]
+    fn map_reports_unique_cutters() {
+        let enzymes = vec![
+            Enzyme::by_name("EcoRI").unwrap(),
+            Enzyme::by_name("BamHI").unwrap(),
+            Enzyme::by_name("NotI").unwrap(),
+        ];
+        let seq = b"GAATTCAAGGATCCAAGGATCC";
+        let map = RestrictionMap::new(seq, &enzymes, Topology::Linear);
+        let names: Vec<&str> = map
+            .unique_cutters()
+            .iter()
+            .map(|e| e.name.as_str())
+            .collect();
+        assert_eq!(names, vec!["EcoRI"]);
+        assert_eq!(map.non_cutters()[0].name, "NotI");
+
+        let tsv = map.to_tsv();
+        assert!(tsv.contains("BamHI\tGGATCC\t2\t9,17\t9,8,5\n"));
+        assert!(tsv.contains("NotI\tGCGGCCGC\t0\t\t22\n"));
+
+        let json = map.to_json();
+        assert!(json.starts_with("{\"length\":22,\"topology\":\"linear\""));
+        assert!(json.ends_with("\"unique_cutters\":[\"EcoRI\"]}"));
+    }
+}
@@ -0,0 +1,398 @@
+//! Sequence-level helpers shared by the higher-level modules.
+
+use std::fmt;
+use std::ops::Range;
+
+use crate::alphabet::SeqType;
+
+pub mod checksum;
+
+/// Strand of a nucleotide feature relative to its source sequence.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Strand {
+    Forward,
+    Reverse,
+}
+
+impl Strand {
+    /// The GFF/BED strand symbol.
+    pub fn symbol(&self) -> char {
+        match self {
+            Strand::Forward => '+',
+            Strand::Reverse => '-',
+        }
+    }
+}
+
+impl fmt::Display for Strand {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.symbol())
+    }
+}
+
+/// Complement of a single nucleotide, IUPAC-aware and case-preserving.
+///
+/// `U` complements to `A`; symbols without a complement are returned as is.
+pub fn complement(base: u8) -> u8 {
+    let comp = match base.to_ascii_uppercase() {
+        b'A' => b'T',
+        b'T' | b'U' => b'A',
+        b'C' => b'G',
+        b'G' => b'C',
+        b'R' => b'Y',
+        b'Y' => b'R',
+        b'K' => b'M',
+        b'M' => b'K',
+        b'B' => b'V',
+        b'V' => b'B',
+        b'D' => b'H',
+        b'H' => b'D',
+        other => other,
+    };
+    if base.is_ascii_lowercase() {
+        comp.to_ascii_lowercase()
+    } else {
+        comp
+    }
+}
+
+/// Reverse complement of a nucleotide sequence.
+pub fn reverse_complement(seq: &[u8]) -> Vec<u8> {
+    seq.iter().rev().map(|&b| complement(b)).collect()
+}
+
+/// Fraction of `G`, `C` and `S` among the non-gap symbols of `seq`, so
+/// aligned rows agree with their ungapped sequence; 0 when there are none.
+pub fn gc_content(seq: &[u8]) -> f64 {
+    let (mut gc, mut residues) = (0usize, 0usize);
+    for b in seq {
+        match b.to_ascii_uppercase() {
+            b'-' | b'.' => continue,
+            b'G' | b'C' | b'S' => gc += 1,
+            _ => {}
+        }
+        residues += 1;
+    }
+    if residues == 0 {
+        0.0
+    } else {
+        gc as f64 / residues as f64
+    }
+}
+
+/// GC skew `(G - C) / (G + C)`, 0 when the sequence has neither base.
+pub fn gc_skew(seq: &[u8]) -> f64 {
+    let (mut g, mut c) = (0usize, 0usize);
+    for b in seq {
+        match b.to_ascii_uppercase() {
+            b'G' => g += 1,
+            b'C' => c += 1,
+            _ => {}
+        }
+    }
+    if g + c == 0 {
+        0.0
+    } else {
+        (g as f64 - c as f64) / (g + c) as f64
+    }
+}
+
+/// Shannon entropy of the symbol distribution in bits, ignoring case.
+pub fn shannon_entropy(seq: &[u8]) -> f64 {
+    if seq.is_empty() {
+        return 0.0;
+    }
+    let mut counts = [0usize; 256];
+    for &b in seq {
+        counts[b.to_ascii_uppercase() as usize] += 1;
+    }
+    let total = seq.len() as f64;
+    counts
+        .iter()
+        .filter(|&&n| n > 0)
+        .map(|&n| {
+            let p = n as f64 / total;
+            -p * p.log2()
+        })
+        .sum()
+}
+
+/// Whether `base` is anything other than an unambiguous nucleotide.
+pub fn is_ambiguous(base: u8) -> bool {
+    !matches!(base.to_ascii_uppercase(), b'A' | b'C' | b'G' | b'T' | b'U')
+}
+
+/// Ranges of the pieces left after removing every run of at least
+/// `min_run` ambiguous symbols, the scaffold-to-contig split. Shorter runs
+/// stay inside their piece; a `min_run` of 0 is treated as 1.
+pub fn split_at_ambiguous_runs(seq: &[u8], min_run: usize) -> Vec<Range<usize>> {
+    let min_run = min_run.max(1);
+    let mut pieces = Vec::new();
+    let mut piece_start = 0;
+    let mut i = 0;
+    while i < seq.len() {
+        if !is_ambiguous(seq[i]) {
+            i += 1;
+            continue;
+        }
+        let run_start = i;
+        while i < seq.len() && is_ambiguous(seq[i]) {
+            i += 1;
+        }
+        if i - run_start >= min_run {
+            if run_start > piece_start {
+                pieces.push(piece_start..run_start);
+            }
+            piece_start = i;
+        }
+    }
+    if seq.len() > piece_start {
+        pieces.push(piece_start..seq.len());
+    }
+    pieces
+}
+
+/// Summary statistics of one sequence, gathered in a single pass.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SeqStats {
+    pub seq_type: SeqType,
+    pub length: usize,
+    /// Count of each symbol, upper-cased.
+    pub counts: [usize; 256],
+    pub gap_count: usize,
+    /// For nucleotides, percentage of `G`, `C` and `S` among the non-gap
+    /// symbols.
+    pub gc_percent: Option<f64>,
+    /// For nucleotides, the number of `N` symbols.
+    pub n_count: Option<usize>,
+    /// For nucleotides, the number of non-gap symbols other than `ACGTU`,
+    /// `N` included.
+    pub ambiguous_count: Option<usize>,
+}
+
+impl SeqStats {
+    pub fn compute(seq: &[u8], seq_type: SeqType) -> SeqStats {
+        let mut counts = [0usize; 256];
+        for &b in seq {
+            counts[b.to_ascii_uppercase() as usize] += 1;
+        }
+        let count = |symbols: &[u8]| symbols.iter().map(|&b| counts[b as usize]).sum::<usize>();
+        let gap_count = count(b"-.");
+        let nucleotide = seq_type != SeqType::Protein;
+        let (gc_percent, n_count, ambiguous_count) = if nucleotide {
+            let residues = seq.len() - gap_count;
+            let gc = if residues == 0 {
+                0.0
+            } else {
+                count(b"GCS") as f64 * 100.0 / residues as f64
+            };
+            let ambiguous = seq.len() - gap_count - count(b"ACGTU");
+            (Some(gc), Some(counts[b'N' as usize]), Some(ambiguous))
+        } else {
+            (None, None, None)
+        };
+        SeqStats {
+            seq_type,
+            length: seq.len(),
+            counts,
+            gap_count,
+            gc_percent,
+            n_count,
+            ambiguous_count,
+        }
+    }
+
+    /// Count of `symbol`, ignoring case.
+    pub fn count(&self, symbol: u8) -> usize {
+        self.counts[symbol.to_ascii_uppercase() as usize]
+    }
+
+    /// Fraction of the non-gap symbols taken by each symbol present, in
+    /// symbol order; for proteins this is the amino acid composition.
+    pub fn composition(&self) -> Vec<(u8, f64)> {
+        let residues = self.length - self.gap_count;
+        if residues == 0 {
+            return Vec::new();
+        }
+        (0..=255u8)
+            .filter(|&b| self.counts[b as usize] > 0 && !matches!(b, b'-' | b'.'))
+            .map(|b| (b, self.counts[b as usize] as f64 / residues as f64))
+            .collect()
+    }
+}
+
+/// A window of a sequence with its 0-based, half-open coordinates.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SeqWindow<'a> {
+    pub start: usize,
+    pub end: usize,
+    pub sequence: &'a [u8],
+}
+
+impl SeqWindow<'_> {
+    pub fn len(&self) -> usize {
+        self.end - self.start
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.start == self.end
+    }
+
+    pub fn gc_content(&self) -> f64 {
+        gc_content(self.sequence)
+    }
+
+    pub fn gc_skew(&self) -> f64 {
+        gc_skew(self.sequence)
+    }
+
+    pub fn entropy(&self) -> f64 {
+        shannon_entropy(self.sequence)
+    }
+}
+
+/// Iterator over fixed-size windows of a sequence, see [`windows`].
+#[derive(Debug, Clone)]
+pub struct Windows<'a> {
+    seq: &'a [u8],
+    size: usize,
+    step: usize,
+    start: usize,
+}
+
+impl<'a> Iterator for Windows<'a> {
+    type Item = SeqWindow<'a>;
+
+    fn next(&mut self) -> Option<SeqWindow<'a>> {
+        let end = self.start.checked_add(self.size)?;
+        if end > self.seq.len() {
+            return None;
+        }
+        let window = SeqWindow {
+            start: self.start,
+            end,
+            sequence: &self.seq[self.start..end],
+        };
+        self.start = self.start.saturating_add(self.step);
+        Some(window)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let n = match self.start.checked_add(self.size) {
+            Some(end) if end <= self.seq.len() => (self.seq.len() - end) / self.step + 1,
+            _ => 0,
+        };
+        (n, Some(n))
+    }
+}
+
+impl ExactSizeIterator for Windows<'_> {}
+
+/// Windows of `size` symbols starting every `step` symbols. A trailing
+/// window shorter than `size` is not produced.
+///
+/// # Panics
+///
+/// Panics if `size` or `step` is 0.
+pub fn windows(seq: &[u8], size: usize, step: usize) -> Windows<'_> {
+    assert!(size > 0, "window size must be non-zero");
+    assert!(step > 0, "window step must be non-zero");
+    Windows {
+        seq,
+        size,
+        step,
+        start: 0,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reverse_complement_handles_case_and_ambiguity() {
+        assert_eq!(reverse_complement(b"ATGCn"), b"nGCAT");
+        assert_eq!(reverse_complement(b"RYKM"), b"KMRY");
+        assert_eq!(reverse_complement(b""), b"");
+    }
+
+    #[test]
+    fn windows_step_through_sequence() {
+        let coords: Vec<(usize, usize)> = windows(b"ACGTACGTAC", 4, 3)
+            .map(|w| (w.start, w.end))
+            .collect();
+        assert_eq!(coords, vec![(0, 4), (3, 7), (6, 10)]);
+        assert_eq!(windows(b"ACGTACGTAC", 4, 3).len(), 3);
+        assert_eq!(windows(b"ACG", 4, 1).count(), 0);
+        let first = windows(b"GGGCAAAA", 4, 4).next().unwrap();
+        assert_eq!(first.sequence, b"GGGC");
+        assert_eq!(first.gc_skew(), 0.5);
+        assert_eq!(first.gc_content(), 1.0);
+    }
+
+    #[test]
+    #[should_panic(expected = "window step must be non-zero")]
+    fn windows_reject_zero_step() {
+        windows(b"ACGT", 2, 0);
+    }
+
+    #[test]
+    fn splits_on_long_ambiguous_runs() {
+        let seq = b"NNACGTNNNNNACNGTNNNNNNTTNN";
+        assert_eq!(split_at_ambiguous_runs(seq, 5), vec![0..6, 11..16, 22..26]);
+        assert_eq!(split_at_ambiguous_runs(seq, 2), vec![2..6, 11..16, 22..24]);
+        assert_eq!(
+            split_at_ambiguous_runs(b"NNNN", 1),
+            Vec::<Range<usize>>::new()
+        );
+        assert_eq!(split_at_ambiguous_runs(b"ACGT", 1), vec![0..4]);
+    }
+
+    #[test]
+    fn stats_for_nucleotides() {
+        let stats = SeqStats::compute(b"ACGTNNRy--gc", SeqType::Dna);
+        assert_eq!(stats.length, 12);
+        assert_eq!(stats.gap_count, 2);
+        assert_eq!(stats.count(b'g'), 2);
+        assert_eq!(stats.n_count, Some(2));
+        assert_eq!(stats.ambiguous_count, Some(4));
+        assert_eq!(stats.gc_percent, Some(100.0 * 4.0 / 10.0));
+        let aligned = SeqStats::compute(b"ACGT--", SeqType::Dna);
+        assert_eq!(aligned.gc_percent, Some(50.0));
+        assert_eq!(SeqStats::compute(b"--", SeqType::Dna).gc_percent, Some(0.0));
+    }
+
+    #[test]
+    fn stats_for_proteins() {
+        let stats = SeqStats::compute(b"MKKA", SeqType::Protein);
+        assert_eq!(stats.gc_percent, None);
+        assert_eq!(stats.n_count, None);
+        assert_eq!(
+            stats.composition(),
+            vec![(b'A', 0.25), (b'K', 0.5), (b'M', 0.25)]
+        );
+        assert!(SeqStats::compute(b"", SeqType::Protein)
+            .composition()
+            .is_empty());
+    }
+
+    #[test]
+    fn entropy_of_uniform_and_constant_sequences() {
+        assert_eq!(shannon_entropy(b"ACGTacgt"), 2.0);
+        assert_eq!(shannon_entropy(b"AAAA"), 0.0);
+        assert_eq!(shannon_entropy(b""), 0.0);
+    }
+
+    #[test]
+    fn gc_content_counts_strong_bases() {
+        assert_eq!(gc_content(b"GCgcATat"), 0.5);
+        assert_eq!(gc_content(b""), 0.0);
+        assert_eq!(gc_content(b"--"), 0.0);
+        let row = b"AC-GT.";
+        assert_eq!(gc_content(row), gc_content(b"ACGT"));
+        assert_eq!(
+            SeqStats::compute(row, SeqType::Dna).gc_percent,
+            Some(gc_content(row) * 100.0)
+        );
+    }
+}
@@ -0,0 +1,219 @@
+//! Sequence fingerprints for matching references across files.
+//!
+//! Every checksum is taken over the [`normalize`]d sequence, so case and
+//! line wrapping do not change it.
+
+use std::fmt::Write as _;
+
+/// Upper-cased sequence with everything outside printable ASCII (`!` to
+/// `~`) removed, the normalisation SAM specifies for `@SQ M5` tags.
+pub fn normalize(seq: &[u8]) -> Vec<u8> {
+    seq.iter()
+        .filter(|b| b.is_ascii_graphic())
+        .map(u8::to_ascii_uppercase)
+        .collect()
+}
+
+/// SEGUID: the base64-encoded SHA-1 digest without `=` padding, as used
+/// by Biopython and the SEGUID database.
+pub fn seguid(seq: &[u8]) -> String {
+    base64(&sha1(&normalize(seq)))
+        .trim_end_matches('=')
+        .to_string()
+}
+
+/// Lower-case hex MD5 digest, the `M5` value of a SAM `@SQ` line.
+pub fn md5(seq: &[u8]) -> String {
+    hex(&md5_digest(&normalize(seq)))
+}
+
+/// CRC64 with the ISO 3309 polynomial, as in UniProt entries. Format it
+/// with `{:016X}` for the usual 16-digit form.
+pub fn crc64(seq: &[u8]) -> u64 {
+    const POLY: u64 = 0xD800_0000_0000_0000;
+    let table: Vec<u64> = (0..256u64)
+        .map(|i| {
+            (0..8).fold(i, |crc, _| {
+                if crc & 1 == 1 {
+                    (crc >> 1) ^ POLY
+                } else {
+                    crc >> 1
+                }
+            })
+        })
+        .collect();
+    normalize(seq).iter().fold(0, |crc, &b| {
+        table[((crc ^ b as u64) & 0xFF) as usize] ^ (crc >> 8)
+    })
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().fold(String::new(), |mut out, b| {
+        let _ = write!(out, "{:02x}", b);
+        out
+    })
+}
+
+fn base64(bytes: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut out = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let n = chunk
+            .iter()
+            .enumerate()
+            .fold(0u32, |n, (i, &b)| n | (b as u32) << (16 - 8 * i));
+        for i in 0..4 {
+            if i <= chunk.len() {
+                out.push(ALPHABET[(n >> (18 - 6 * i) & 0x3F) as usize] as char);
+            } else {
+                out.push('=');
+            }
+        }
+    }
+    out
+}
+
+/// Pads a message into 64-byte blocks the way SHA-1 and MD5 both do: a
+/// `0x80` byte, zeros, then the bit length in the given byte order.
+fn pad(data: &[u8], big_endian: bool) -> Vec<u8> {
+    let bits = (data.len() as u64).wrapping_mul(8);
+    let mut msg = data.to_vec();
+    msg.push(0x80);
+    while msg.len() % 64 != 56 {
+        msg.push(0);
+    }
+    msg.extend_from_slice(&if big_endian {
+        bits.to_be_bytes()
+    } else {
+        bits.to_le_bytes()
+    });
+    msg
+}
+
+fn sha1(data: &[u8]) -> [u8; 20] {
+    let mut h: [u32; 5] = [
+        0x6745_2301,
+        0xEFCD_AB89,
+        0x98BA_DCFE,
+        0x1032_5476,
+        0xC3D2_E1F0,
+    ];
+    for block in pad(data, true).chunks(64) {
+        let mut w = [0u32; 80];
+        for (i, word) in block.chunks(4).enumerate() {
+            w[i] = u32::from_be_bytes([word[0], word[1], word[2], word[3]]);
+        }
+        for i in 16..80 {
+            w[i] = (w[i - 3] ^ w[i - 8] ^ w[i - 14] ^ w[i - 16]).rotate_left(1);
+        }
+        let [mut a, mut b, mut c, mut d, mut e] = h;
+        for (i, &word) in w.iter().enumerate() {
+            let (f, k) = match i {
+                0..=19 => ((b & c) | (!b & d), 0x5A82_7999),
+                20..=39 => (b ^ c ^ d, 0x6ED9_EBA1),
+                40..=59 => ((b & c) | (b & d) | (c & d), 0x8F1B_BCDC),
+                _ => (b ^ c ^ d, 0xCA62_C1D6),
+            };
+            let temp = a
+                .rotate_left(5)
+                .wrapping_add(f)
+                .wrapping_add(e)
+                .wrapping_add(k)
+                .wrapping_add(word);
+            e = d;
+            d = c;
+            c = b.rotate_left(30);
+            b = a;
+            a = temp;
+        }
+        for (x, y) in h.iter_mut().zip([a, b, c, d, e]) {
+            *x = x.wrapping_add(y);
+        }
+    }
+    let mut digest = [0u8; 20];
+    for (out, word) in digest.chunks_mut(4).zip(h) {
+        out.copy_from_slice(&word.to_be_bytes());
+    }
+    digest
+}
+
+fn md5_digest(data: &[u8]) -> [u8; 16] {
+    const SHIFTS: [u32; 16] = [7, 12, 17, 22, 5, 9, 14, 20, 4, 11, 16, 23, 6, 10, 15, 21];
+    // K[i] = floor(|sin(i + 1)| * 2^32).
+    let k: Vec<u32> = (0..64)
+        .map(|i| ((i as f64 + 1.0).sin().abs() * 4_294_967_296.0) as u32)
+        .collect();
+    let mut h: [u32; 4] = [0x6745_2301, 0xEFCD_AB89, 0x98BA_DCFE, 0x1032_5476];
+    for block in pad(data, false).chunks(64) {
+        let m: Vec<u32> = block
+            .chunks(4)
+            .map(|w| u32::from_le_bytes([w[0], w[1], w[2], w[3]]))
+            .collect();
+        let [mut a, mut b, mut c, mut d] = h;
+        for i in 0..64 {
+            let (f, g) = match i / 16 {
+                0 => ((b & c) | (!b & d), i),
+                1 => ((d & b) | (!d & c), (5 * i + 1) % 16),
+                2 => (b ^ c ^ d, (3 * i + 5) % 16),
+                _ => (c ^ (b | !d), (7 * i) % 16),
+            };
+            let rotated = a
+                .wrapping_add(f)
+                .wrapping_add(k[i])
+                .wrapping_add(m[g])
+                .rotate_left(SHIFTS[i / 16 * 4 + i % 4]);
+            a = d;
+            d = c;
+            c = b;
+            b = b.wrapping_add(rotated);
+        }
+        for (x, y) in h.iter_mut().zip([a, b, c, d]) {
+            *x = x.wrapping_add(y);
+        }
+    }
+    let mut digest = [0u8; 16];
+    for (out, word) in digest.chunks_mut(4).zip(h) {
+        out.copy_from_slice(&word.to_le_bytes());
+    }
+    digest
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hashes_match_reference_implementations() {
+        assert_eq!(
+            hex(&sha1(b"abc")),
+            "a9993e364706816aba3e25717850c26c9cd0d89d"
+        );
+        assert_eq!(
+            hex(&sha1(&[b'a'; 1000])),
+            "291e9a6c66994949b57ba5e650361e98fc36b1ba"
+        );
+        assert_eq!(md5(b""), "d41d8cd98f00b204e9800998ecf8427e");
+        assert_eq!(md5(&[b'a'; 100]), "8adc5937e635f6c9af646f0b23560fae");
+        assert_eq!(base64(b"ab"), "YWI=");
+    }
+
+    #[test]
+    fn sequence_checksums() {
+        let protein = b"MKTAYIAKQRQISFVKSHFSRQ";
+        assert_eq!(seguid(protein), "mxX9X2aOWUk3ChHW0N0w9qTgaH4");
+        assert_eq!(md5(protein), "cfbd03aae5e619bfa52873996d0e4c34");
+        assert_eq!(format!("{:016X}", crc64(protein)), "B0947C4BDFFF1FE5");
+        assert_eq!(seguid(b"ACGT"), "IQiZThf2zKn/I1KtqStlEdsHYDQ");
+        assert_eq!(format!("{:016X}", crc64(b"ACGT")), "71A87EBDB0000000");
+        assert_eq!(crc64(b""), 0);
+    }
+
+    #[test]
+    fn checksums_ignore_case_and_line_breaks() {
+        let wrapped = b"acgtACGT\nnnAGT\r\n";
+        assert_eq!(normalize(wrapped), b"ACGTACGTNNAGT");
+        assert_eq!(md5(wrapped), "1e9e26ea915155fb6a3ad7a031d14de8");
+        assert_eq!(seguid(wrapped), "/nEeEr+aFPLuW3UjS66GVj7b5FM");
+        assert_eq!(format!("{:016X}", crc64(wrapped)), "8EED2E1D9B54A87E");
+    }
+}
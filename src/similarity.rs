@@ -0,0 +1,267 @@
+//! Longest common substring and subsequence of two sequences.
+//!
+//! Comparison is byte-exact; upper-case both sequences first to ignore
+//! case.
+
+/// A substring shared by two sequences: `a[a_start..a_start + len]` equals
+/// `b[b_start..b_start + len]`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CommonSubstring {
+    pub a_start: usize,
+    pub b_start: usize,
+    pub len: usize,
+}
+
+/// Suffix automaton state. Transitions are a short list, which beats a map
+/// on nucleotide and protein alphabets.
+struct State {
+    len: usize,
+    link: Option<usize>,
+    next: Vec<(u8, usize)>,
+    /// End position in the text of the state's first occurrence.
+    first_end: usize,
+}
+
+impl State {
+    fn get(&self, symbol: u8) -> Option<usize> {
+        self.next
+            .iter()
+            .find(|&&(s, _)| s == symbol)
+            .map(|&(_, to)| to)
+    }
+
+    fn set(&mut self, symbol: u8, to: usize) {
+        match self.next.iter_mut().find(|(s, _)| *s == symbol) {
+            Some(slot) => slot.1 = to,
+            None => self.next.push((symbol, to)),
+        }
+    }
+}
+
+/// Suffix automaton of `text`, built online in linear time.
+fn suffix_automaton(text: &[u8]) -> Vec<State> {
+    let mut states = vec![State {
+        len: 0,
+        link: None,
+        next: Vec::new(),
+        first_end: 0,
+    }];
+    let mut last = 0;
+    for (i, &c) in text.iter().enumerate() {
+        let cur = states.len();
+        states.push(State {
+            len: states[last].len + 1,
+            link: None,
+            next: Vec::new(),
+            first_end: i,
+        });
+        let mut p = Some(last);
+        while let Some(s) = p {
+            if states[s].get(c).is_some() {
+                break;
+            }
+            states[s].set(c, cur);
+            p = states[s].link;
+        }
+        states[cur].link = Some(match p {
+            None => 0,
+            Some(p) => {
+                let q = states[p].get(c).expect("loop stopped at a transition");
+                if states[p].len + 1 == states[q].len {
+                    q
+                } else {
+                    let clone = states.len();
+                    states.push(State {
+                        len: states[p].len + 1,
+                        link: states[q].link,
+                        next: states[q].next.clone(),
+                        first_end: states[q].first_end,
+                    });
+                    let mut p = Some(p);
+                    while let Some(s) = p {
+                        if states[s].get(c) != Some(q) {
+                            break;
+                        }
+                        states[s].set(c, clone);
+                        p = states[s].link;
+                    }
+                    states[q].link = Some(clone);
+                    clone
+                }
+            }
+        });
+        last = cur;
+    }
+    states
+}
+
+/// Longest substring of both `a` and `b`, at its first occurrence in each,
+/// or `None` if they share no symbol. Runs in `O(|a| + |b|)` using a suffix
+/// automaton of `a`.
+pub fn longest_common_substring(a: &[u8], b: &[u8]) -> Option<CommonSubstring> {
+    let states = suffix_automaton(a);
+    let mut best: Option<CommonSubstring> = None;
+    let (mut state, mut len) = (0, 0);
+    for (i, &c) in b.iter().enumerate() {
+        while state != 0 && states[state].get(c).is_none() {
+            state = states[state].link.unwrap_or(0);
+            len = states[state].len;
+        }
+        match states[state].get(c) {
+            Some(next) => {
+                state = next;
+                len += 1;
+            }
+            None => continue,
+        }
+        if best.is_none_or(|b| len > b.len) {
+            best = Some(CommonSubstring {
+                a_start: states[state].first_end + 1 - len,
+                b_start: i + 1 - len,
+                len,
+            });
+        }
+    }
+    best
+}
+
+/// Length of the longest common subsequence, in `O(|a| * |b|)` time and
+/// `O(min(|a|, |b|))` memory.
+pub fn lcs_length(a: &[u8], b: &[u8]) -> usize {
+    let (long, short) = if a.len() >= b.len() { (a, b) } else { (b, a) };
+    let mut row = vec![0usize; short.len() + 1];
+    for &x in long {
+        let mut diagonal = 0;
+        for (j, &y) in short.iter().enumerate() {
+            let above = row[j + 1];
+            row[j + 1] = if x == y {
+                diagonal + 1
+            } else {
+                above.max(row[j])
+            };
+            diagonal = above;
+        }
+    }
+    row[short.len()]
+}
+
+/// A longest common subsequence as the `(a_index, b_index)` pairs of its
+/// matched symbols, in increasing order. Keeps the full dynamic
+/// programming table, so use [`lcs_length`] when only the length matters.
+pub fn longest_common_subsequence(a: &[u8], b: &[u8]) -> Vec<(usize, usize)> {
+    let width = b.len() + 1;
+    let mut table = vec![0u32; (a.len() + 1) * width];
+    for (i, &x) in a.iter().enumerate() {
+        for (j, &y) in b.iter().enumerate() {
+            table[(i + 1) * width + j + 1] = if x == y {
+                table[i * width + j] + 1
+            } else {
+                table[i * width + j + 1].max(table[(i + 1) * width + j])
+            };
+        }
+    }
+    let mut pairs = Vec::with_capacity(table[a.len() * width + b.len()] as usize);
+    let (mut i, mut j) = (a.len(), b.len());
+    while i > 0 && j > 0 {
+        if a[i - 1] == b[j - 1] {
+            pairs.push((i - 1, j - 1));
+            i -= 1;
+            j -= 1;
+        } else if table[(i - 1) * width + j] >= table[i * width + j - 1] {
+            i -= 1;
+        } else {
+            j -= 1;
+        }
+    }
+    pairs.reverse();
+    pairs
+}
+
+/// Quick similarity estimate, `2 * LCS / (|a| + |b|)`: 1 for identical
+/// sequences, 0 for ones sharing no symbol. Two empty sequences give 1.
+pub fn lcs_similarity(a: &[u8], b: &[u8]) -> f64 {
+    if a.is_empty() && b.is_empty() {
+        return 1.0;
+    }
+    2.0 * lcs_length(a, b) as f64 / (a.len() + b.len()) as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn brute_force_substring_len(a: &[u8], b: &[u8]) -> usize {
+        let mut best = 0;
+        for i in 0..a.len() {
+            for j in 0..b.len() {
+                let len = a[i..]
+                    .iter()
+                    .zip(&b[j..])
+                    .take_while(|(x, y)| x == y)
+                    .count();
+                best = best.max(len);
+            }
+        }
+        best
+    }
+
+    #[test]
+    fn finds_longest_common_substring() {
+        let a = b"TTTGATTACAGG";
+        let b = b"CCGATTACTT";
+        let common = longest_common_substring(a, b).unwrap();
+        assert_eq!(
+            common,
+            CommonSubstring {
+                a_start: 3,
+                b_start: 2,
+                len: 6
+            }
+        );
+        assert_eq!(&a[3..9], b"GATTAC");
+        assert_eq!(longest_common_substring(b"AAAA", b"CCCC"), None);
+        assert_eq!(longest_common_substring(b"", b"ACGT"), None);
+    }
+
+    #[test]
+    fn substring_matches_brute_force() {
+        let mut state = 11u32;
+        let mut random = |n: usize| -> Vec<u8> {
+            (0..n)
+                .map(|_| {
+                    state = state.wrapping_mul(1_103_515_245).wrapping_add(12_345);
+                    b"ACGT"[(state >> 16) as usize & 3]
+                })
+                .collect()
+        };
+        for _ in 0..20 {
+            let (a, b) = (random(60), random(45));
+            let common = longest_common_substring(&a, &b).unwrap();
+            assert_eq!(common.len, brute_force_substring_len(&a, &b));
+            assert_eq!(
+                a[common.a_start..common.a_start + common.len],
+                b[common.b_start..common.b_start + common.len]
+            );
+        }
+    }
+
+    #[test]
+    fn computes_lcs_with_traceback() {
+        let (a, b) = (b"AGGTAB", b"GXTXAYB");
+        assert_eq!(lcs_length(a, b), 4);
+        assert_eq!(lcs_length(b, a), 4);
+        let pairs = longest_common_subsequence(a, b);
+        let lcs: Vec<u8> = pairs.iter().map(|&(i, _)| a[i]).collect();
+        assert_eq!(lcs, b"GTAB");
+        assert!(pairs.iter().all(|&(i, j)| a[i] == b[j]));
+        assert!(longest_common_subsequence(b"", b"ACGT").is_empty());
+    }
+
+    #[test]
+    fn similarity_is_normalised() {
+        assert_eq!(lcs_similarity(b"ACGT", b"ACGT"), 1.0);
+        assert_eq!(lcs_similarity(b"AAAA", b"CCCC"), 0.0);
+        assert_eq!(lcs_similarity(b"ACGT", b"AGT"), 6.0 / 7.0);
+        assert_eq!(lcs_similarity(b"", b""), 1.0);
+    }
+}
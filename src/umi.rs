@@ -0,0 +1,329 @@
+//! Unique molecular identifiers (UMIs): extraction from reads, tagging and
+//! duplicate collapsing.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::hash::Hash;
+use std::io::{BufRead, Write};
+
+use crate::fastq::{FastqError, FastqReader, FastqSeq};
+
+/// Where the UMI sits in a read. Extracted bases are removed from the read.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum UmiSource {
+    /// `len` bases starting at 0-based `start`.
+    Range { start: usize, len: usize },
+    /// A umi_tools-style layout applied from the read start: `N` marks UMI
+    /// bases and `X` bases kept in the read, e.g. `NNNXXNNN`.
+    Pattern(String),
+}
+
+/// Where the extracted UMI is recorded.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum UmiPlacement {
+    /// Appended to the read ID after `_`, as umi_tools does: `read1_ACGT`.
+    Name,
+    /// Appended to the description as a SAM-style tag, `RX:Z:ACGT` for
+    /// `Tag("RX")`.
+    Tag(String),
+}
+
+/// Errors from [`UmiExtractor::new`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum UmiError {
+    /// The source selects no UMI bases.
+    EmptyUmi,
+    /// A pattern symbol other than `N` or `X`, at a 0-based position.
+    InvalidPattern { position: usize, symbol: char },
+    /// The tag name is not two alphanumeric characters.
+    InvalidTag(String),
+}
+
+impl fmt::Display for UmiError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            UmiError::EmptyUmi => write!(f, "UMI source selects no bases"),
+            UmiError::InvalidPattern { position, symbol } => write!(
+                f,
+                "UMI pattern symbol {:?} at position {} is not N or X",
+                symbol, position
+            ),
+            UmiError::InvalidTag(tag) => write!(f, "{:?} is not a two-character tag", tag),
+        }
+    }
+}
+
+impl std::error::Error for UmiError {}
+
+/// Moves UMIs from read sequences into read headers.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UmiExtractor {
+    /// Per layout position, whether it is a UMI base.
+    layout: Vec<bool>,
+    placement: UmiPlacement,
+}
+
+impl UmiExtractor {
+    pub fn new(source: UmiSource, placement: UmiPlacement) -> Result<Self, UmiError> {
+        let layout = match source {
+            UmiSource::Range { start, len } => {
+                let mut layout = vec![false; start];
+                layout.resize(start + len, true);
+                layout
+            }
+            UmiSource::Pattern(pattern) => pattern
+                .chars()
+                .enumerate()
+                .map(|(position, symbol)| match symbol.to_ascii_uppercase() {
+                    'N' => Ok(true),
+                    'X' => Ok(false),
+                    _ => Err(UmiError::InvalidPattern { position, symbol }),
+                })
+                .collect::<Result<_, _>>()?,
+        };
+        if !layout.contains(&true) {
+            return Err(UmiError::EmptyUmi);
+        }
+        if let UmiPlacement::Tag(tag) = &placement {
+            if tag.len() != 2 || !tag.chars().all(|c| c.is_ascii_alphanumeric()) {
+                return Err(UmiError::InvalidTag(tag.clone()));
+            }
+        }
+        Ok(UmiExtractor { layout, placement })
+    }
+
+    /// The read with its UMI bases removed and recorded in the header,
+    /// and the UMI itself; `None` if the read is shorter than the layout.
+    pub fn extract(&self, read: &FastqSeq) -> Option<(FastqSeq, String)> {
+        if read.len() < self.layout.len() {
+            return None;
+        }
+        let (seq, qual) = (read.sequence(), read.quality());
+        let mut umi = String::new();
+        let mut kept_seq = String::with_capacity(read.len());
+        let mut kept_qual = String::with_capacity(read.len());
+        for (i, (base, q)) in seq.chars().zip(qual.chars()).enumerate() {
+            if self.layout.get(i) == Some(&true) {
+                umi.push(base);
+            } else {
+                kept_seq.push(base);
+                kept_qual.push(q);
+            }
+        }
+        let (id, description) = match &self.placement {
+            UmiPlacement::Name => (
+                format!("{}_{}", read.id(), umi),
+                read.description().to_string(),
+            ),
+            UmiPlacement::Tag(tag) if read.description().is_empty() => {
+                (read.id().to_string(), format!("{}:Z:{}", tag, umi))
+            }
+            UmiPlacement::Tag(tag) => (
+                read.id().to_string(),
+                format!("{} {}:Z:{}", read.description(), tag, umi),
+            ),
+        };
+        let tagged = FastqSeq::new(&id, &description, &kept_seq, &kept_qual)
+            .expect("extraction keeps a valid read valid");
+        Some((tagged, umi))
+    }
+}
+
+/// Tracks which UMIs were seen at each position, treating UMIs within
+/// `max_mismatches` of one already seen there as the same molecule.
+///
+/// Positions are whatever identifies a fragment's origin: an aligned
+/// `(reference, start, strand)`, or for unaligned reads the read sequence.
+#[derive(Debug, Clone)]
+pub struct UmiCollapser<P> {
+    max_mismatches: usize,
+    seen: HashMap<P, Vec<(String, u64)>>,
+}
+
+impl<P: Hash + Eq> UmiCollapser<P> {
+    pub fn new(max_mismatches: usize) -> Self {
+        UmiCollapser {
+            max_mismatches,
+            seen: HashMap::new(),
+        }
+    }
+
+    /// Records an observation; `true` if it is the first of its molecule.
+    pub fn observe(&mut self, position: P, umi: &str) -> bool {
+        let umis = self.seen.entry(position).or_default();
+        let max = self.max_mismatches;
+        let known = umis.iter_mut().find(|(seen, _)| {
+            seen.len() == umi.len()
+                && seen
+                    .bytes()
+                    .zip(umi.bytes())
+                    .filter(|(a, b)| a != b)
+                    .count()
+                    <= max
+        });
+        match known {
+            Some((_, count)) => {
+                *count += 1;
+                false
+            }
+            None => {
+                umis.push((umi.to_string(), 1));
+                true
+            }
+        }
+    }
+
+    /// Number of distinct molecules observed.
+    pub fn molecules(&self) -> usize {
+        self.seen.values().map(Vec::len).sum()
+    }
+
+    /// Representative UMIs at `position` with their read counts, in order
+    /// of first observation.
+    pub fn umis_at(&self, position: &P) -> &[(String, u64)] {
+        self.seen.get(position).map_or(&[], Vec::as_slice)
+    }
+}
+
+/// Counts from [`extract_reads`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct UmiStats {
+    pub reads: u64,
+    pub written: u64,
+    /// Reads too short to hold the UMI, which are dropped.
+    pub too_short: u64,
+    /// Reads dropped as duplicates of an earlier read of the same molecule.
+    pub duplicates: u64,
+}
+
+/// Extracts UMIs from every read and writes the tagged reads to `out`.
+///
+/// With `collapse` set to a mismatch tolerance, reads with the same
+/// remaining sequence and a matching UMI are PCR duplicates, and only the
+/// first is written.
+pub fn extract_reads<R: BufRead, W: Write>(
+    reader: FastqReader<R>,
+    extractor: &UmiExtractor,
+    collapse: Option<usize>,
+    out: &mut W,
+) -> Result<UmiStats, FastqError> {
+    let mut stats = UmiStats::default();
+    let mut collapser = collapse.map(UmiCollapser::new);
+    for read in reader {
+        let read = read?;
+        stats.reads += 1;
+        let Some((tagged, umi)) = extractor.extract(&read) else {
+            stats.too_short += 1;
+            continue;
+        };
+        if let Some(collapser) = collapser.as_mut() {
+            if !collapser.observe(tagged.sequence().to_string(), &umi) {
+                stats.duplicates += 1;
+                continue;
+            }
+        }
+        write!(out, "{}", tagged)?;
+        stats.written += 1;
+    }
+    Ok(stats)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn read(id: &str, desc: &str, seq: &str) -> FastqSeq {
+        let qual: String = (0..seq.len()).map(|i| (b'A' + i as u8) as char).collect();
+        FastqSeq::new(id, desc, seq, &qual).unwrap()
+    }
+
+    #[test]
+    fn extracts_range_into_read_name() {
+        let extractor =
+            UmiExtractor::new(UmiSource::Range { start: 0, len: 4 }, UmiPlacement::Name).unwrap();
+        let (tagged, umi) = extractor.extract(&read("r1", "x", "ACGTTTGG")).unwrap();
+        assert_eq!(umi, "ACGT");
+        assert_eq!(tagged.id(), "r1_ACGT");
+        assert_eq!(tagged.description(), "x");
+        assert_eq!((tagged.sequence(), tagged.quality()), ("TTGG", "EFGH"));
+        assert!(extractor.extract(&read("r2", "", "ACG")).is_none());
+    }
+
+    #[test]
+    fn extracts_pattern_into_tag() {
+        let extractor = UmiExtractor::new(
+            UmiSource::Pattern("NNXXN".to_string()),
+            UmiPlacement::Tag("RX".to_string()),
+        )
+        .unwrap();
+        let (tagged, umi) = extractor.extract(&read("r1", "", "ACGTACC")).unwrap();
+        assert_eq!(umi, "ACA");
+        assert_eq!(tagged.description(), "RX:Z:ACA");
+        assert_eq!((tagged.sequence(), tagged.quality()), ("GTCC", "CDFG"));
+        let (tagged, _) = extractor.extract(&read("r2", "1:N:0", "ACGTACC")).unwrap();
+        assert_eq!(tagged.description(), "1:N:0 RX:Z:ACA");
+    }
+
+    #[test]
+    fn rejects_bad_configuration() {
+        assert_eq!(
+            UmiExtractor::new(UmiSource::Pattern("XXNZ".to_string()), UmiPlacement::Name),
+            Err(UmiError::InvalidPattern {
+                position: 3,
+                symbol: 'Z'
+            })
+        );
+        assert_eq!(
+            UmiExtractor::new(UmiSource::Range { start: 3, len: 0 }, UmiPlacement::Name),
+            Err(UmiError::EmptyUmi)
+        );
+        assert_eq!(
+            UmiExtractor::new(
+                UmiSource::Range { start: 0, len: 4 },
+                UmiPlacement::Tag("UMI".to_string())
+            ),
+            Err(UmiError::InvalidTag("UMI".to_string()))
+        );
+    }
+
+    #[test]
+    fn collapses_umis_per_position() {
+        let mut collapser = UmiCollapser::new(1);
+        assert!(collapser.observe(("chr1", 100), "AAAA"));
+        assert!(!collapser.observe(("chr1", 100), "AAAT"));
+        assert!(collapser.observe(("chr1", 100), "CCCC"));
+        assert!(collapser.observe(("chr1", 200), "AAAA"));
+        assert_eq!(collapser.molecules(), 3);
+        assert_eq!(
+            collapser.umis_at(&("chr1", 100)),
+            &[("AAAA".to_string(), 2), ("CCCC".to_string(), 1)]
+        );
+        assert!(collapser.umis_at(&("chr2", 1)).is_empty());
+    }
+
+    #[test]
+    fn extracts_and_deduplicates_reads() {
+        let extractor =
+            UmiExtractor::new(UmiSource::Range { start: 0, len: 3 }, UmiPlacement::Name).unwrap();
+        let input = "@a\nAAAGGGG\n+\nIIIIIII\n@b\nAAAGGGG\n+\nIIIIIII\n@c\nCCCGGGG\n+\nIIIIIII\n@d\nAA\n+\nII\n";
+        let mut out = Vec::new();
+        let stats = extract_reads(
+            FastqReader::new(input.as_bytes()),
+            &extractor,
+            Some(0),
+            &mut out,
+        )
+        .unwrap();
+        assert_eq!(
+            stats,
+            UmiStats {
+                reads: 4,
+                written: 2,
+                too_short: 1,
+                duplicates: 1
+            }
+        );
+        let text = String::from_utf8(out).unwrap();
+        assert_eq!(text, "@a_AAA\nGGGG\n+\nIIII\n@c_CCC\nGGGG\n+\nIIII\n");
+    }
+}